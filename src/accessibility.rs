@@ -0,0 +1,97 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::AppConfig;
+
+/// Smallest and largest GUI text scale, and how much one key press changes it
+pub const MIN_TEXT_SCALE: f32 = 0.5;
+pub const MAX_TEXT_SCALE: f32 = 3.0;
+pub const TEXT_SCALE_STEP: f32 = 0.25;
+
+/// Set by --accessible, on top of the `accessible` setting
+static ACCESSIBLE_FLAG: AtomicBool = AtomicBool::new(false);
+
+/// Turns the accessible mode on for this process (the --accessible flag)
+pub fn enable() {
+    ACCESSIBLE_FLAG.store(true, Ordering::Relaxed);
+}
+
+/// Whether the terminal frontends should write the linear screen-reader stream
+pub fn enabled(config: &AppConfig) -> bool {
+    config.accessible || ACCESSIBLE_FLAG.load(Ordering::Relaxed)
+}
+
+/// The configured GUI text scale, kept within bounds
+pub fn text_scale(config: &AppConfig) -> f32 {
+    if config.text_scale.is_finite() {
+        config.text_scale.clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE)
+    } else {
+        1.0
+    }
+}
+
+/// `scale` made `steps` steps larger (or smaller when negative)
+pub fn step_text_scale(scale: f32, steps: i32) -> f32 {
+    (scale + steps as f32 * TEXT_SCALE_STEP).clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE)
+}
+
+/// What is said before each word: "next word: the", or "last word: end"
+pub fn word_prompt(word: &str, last: bool) -> String {
+    format!("{} word: {}", if last { "last" } else { "next" }, word)
+}
+
+/// What is said after a word was typed. Words are spelled out when wrong,
+/// since a reader says "teh" and "the" almost the same way.
+pub fn word_feedback(typed: &str, expected: &str) -> String {
+    if typed == expected {
+        "correct".to_string()
+    } else if typed.is_empty() {
+        format!("skipped {}", expected)
+    } else {
+        format!("wrong: typed {}, expected {}", spell(typed), spell(expected))
+    }
+}
+
+/// "t e h", so each letter is read on its own
+fn spell(word: &str) -> String {
+    word.chars().map(|c| c.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// Marks of a typed word for the characters of the expected one, as the
+/// frontends keep them: 2 typed right, -1 typed wrong or left out
+pub fn word_marks(typed: &str, expected: &str) -> Vec<i32> {
+    let mut typed = typed.chars();
+    expected.chars().map(|c| if typed.next() == Some(c) { 2 } else { -1 }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_feedback() {
+        assert_eq!(word_prompt("the", false), "next word: the");
+        assert_eq!(word_prompt("end", true), "last word: end");
+        assert_eq!(word_feedback("the", "the"), "correct");
+        assert_eq!(word_feedback("teh", "the"), "wrong: typed t e h, expected t h e");
+        assert_eq!(word_feedback("", "the"), "skipped the");
+    }
+
+    #[test]
+    fn test_word_marks() {
+        assert_eq!(word_marks("the", "the"), [2, 2, 2]);
+        assert_eq!(word_marks("teh", "the"), [2, -1, -1]);
+        assert_eq!(word_marks("th", "the"), [2, 2, -1]);
+        assert_eq!(word_marks("there", "the"), [2, 2, 2]);
+    }
+
+    #[test]
+    fn test_text_scale_stays_in_bounds() {
+        assert_eq!(step_text_scale(1.0, 2), 1.5);
+        assert_eq!(step_text_scale(MAX_TEXT_SCALE, 1), MAX_TEXT_SCALE);
+        assert_eq!(step_text_scale(0.75, -4), MIN_TEXT_SCALE);
+        let config = AppConfig { text_scale: f32::NAN, ..AppConfig::default() };
+        assert_eq!(text_scale(&config), 1.0);
+        let config = AppConfig { text_scale: 10.0, ..AppConfig::default() };
+        assert_eq!(text_scale(&config), MAX_TEXT_SCALE);
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::goals;
+use crate::leaderboard::{self, LeaderboardEntry, LeaderboardError, TestType};
+use crate::storage::json::{atomic_write, load_entries_from_file};
+
+/// Rules are data: a new achievement is a new entry in this file
+const ACHIEVEMENTS: &str = include_str!("../assets/achievements.json");
+/// Unlocks of the active profile
+pub const UNLOCKS_FILE: &str = "achievements.json";
+
+/// Something to unlock, and the rule that unlocks it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub rule: Rule,
+}
+
+/// What has to be true of the stored results for an achievement to unlock.
+/// Only unflagged results count for speed and accuracy.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Rule {
+    /// A result at least this fast, on one kind of test or any
+    Wpm { min: f64, #[serde(default)] test_type: Option<TestType> },
+    /// A result at least this accurate, on one kind of test or any
+    Accuracy { min: f64, #[serde(default)] test_type: Option<TestType> },
+    /// Finished tests in total
+    Tests { count: usize },
+    /// Minutes typed in total
+    Minutes { total: f64 },
+    /// Days in a row with at least one test
+    DayStreak { days: usize },
+    /// Days in a row with a daily challenge result
+    DailyStreak { days: usize },
+    /// Completed practice levels; every level when no count is given
+    PracticeLevels { #[serde(default)] count: Option<usize> },
+}
+
+/// When an achievement was unlocked
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Unlock {
+    pub id: String,
+    pub timestamp: String,
+}
+
+/// State of the achievements screen
+pub struct AchievementsData {
+    pub open: bool,
+    pub unlocks: Vec<Unlock>,
+    pub scroll: usize,
+}
+
+impl AchievementsData {
+    pub fn new() -> Self {
+        AchievementsData { open: false, unlocks: Vec::new(), scroll: 0 }
+    }
+
+    /// Opens or closes the screen, reading the unlocks when it opens
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        if self.open {
+            self.unlocks = load_saved();
+            self.scroll = 0;
+        }
+    }
+}
+
+/// What the rules are checked against
+pub struct Progress<'a> {
+    pub history: &'a [LeaderboardEntry],
+    /// Completion of every practice level, in order
+    pub completed_levels: &'a [bool],
+}
+
+impl Rule {
+    pub fn is_met(&self, progress: &Progress) -> bool {
+        let counted = || progress.history.iter().filter(|e| e.flag.is_none());
+        let matches = |e: &LeaderboardEntry, test_type: &Option<TestType>| {
+            test_type.as_ref().is_none_or(|t| *t == e.test_type)
+        };
+        match self {
+            Rule::Wpm { min, test_type } => counted().any(|e| matches(e, test_type) && e.wpm >= *min),
+            Rule::Accuracy { min, test_type } => counted().any(|e| matches(e, test_type) && e.accuracy >= *min),
+            Rule::Tests { count } => progress.history.len() >= *count,
+            Rule::Minutes { total } => {
+                progress.history.iter().map(|e| e.test_duration).sum::<f64>() / 60.0 >= *total
+            }
+            Rule::DayStreak { days } => {
+                longest_run(progress.history.iter().filter_map(goals::entry_day).collect()) >= *days
+            }
+            Rule::DailyStreak { days } => {
+                let dailies = progress
+                    .history
+                    .iter()
+                    .filter_map(|e| match e.test_type {
+                        TestType::Daily(date) => Some(date),
+                        _ => None,
+                    })
+                    .collect();
+                longest_run(dailies) >= *days
+            }
+            Rule::PracticeLevels { count } => {
+                let done = progress.completed_levels.iter().filter(|done| **done).count();
+                done >= count.unwrap_or(progress.completed_levels.len()) && done > 0
+            }
+        }
+    }
+}
+
+/// Most consecutive days in `days`
+fn longest_run(days: BTreeSet<NaiveDate>) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        run = match previous {
+            Some(previous) if day - previous == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+    longest
+}
+
+/// Every achievement, in the order they are listed
+pub fn all() -> &'static [Achievement] {
+    static ALL: OnceLock<Vec<Achievement>> = OnceLock::new();
+    ALL.get_or_init(|| serde_json::from_str(ACHIEVEMENTS).unwrap_or_default())
+}
+
+/// Achievements whose rule is met but that aren't unlocked yet
+pub fn newly_met<'a>(
+    achievements: &'a [Achievement],
+    unlocked: &[Unlock],
+    progress: &Progress,
+) -> Vec<&'a Achievement> {
+    achievements
+        .iter()
+        .filter(|a| !unlocked.iter().any(|u| u.id == a.id))
+        .filter(|a| a.rule.is_met(progress))
+        .collect()
+}
+
+pub fn unlocks_path(dir: &Path) -> PathBuf {
+    dir.join(UNLOCKS_FILE)
+}
+
+pub fn load(dir: &Path) -> Result<Vec<Unlock>, LeaderboardError> {
+    let path = unlocks_path(dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    load_entries_from_file(&path)
+}
+
+/// Checks every rule against the stored results of the profile in `dir` and
+/// records the new unlocks. Returns what was unlocked just now.
+pub fn check(dir: &Path, progress: &Progress) -> Result<Vec<Achievement>, LeaderboardError> {
+    let mut unlocked = load(dir)?;
+    let new: Vec<Achievement> = newly_met(all(), &unlocked, progress).into_iter().cloned().collect();
+    if new.is_empty() {
+        return Ok(new);
+    }
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    unlocked.extend(new.iter().map(|a| Unlock { id: a.id.clone(), timestamp: timestamp.clone() }));
+    atomic_write(&unlocks_path(dir), &unlocked)?;
+    Ok(new)
+}
+
+/// `check` for the active profile, to run after every saved result
+pub fn check_saved() -> Vec<Achievement> {
+    let Ok(dir) = crate::profile::current_data_dir() else {
+        return Vec::new();
+    };
+    let history = leaderboard::load_history(None).unwrap_or_default();
+    let completed_levels = crate::practice::completed_levels();
+    let progress = Progress { history: &history, completed_levels: &completed_levels };
+    match check(&dir, &progress) {
+        Ok(new) => new,
+        Err(e) => {
+            eprintln!("Failed to record achievements: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Unlocks of the active profile
+pub fn load_saved() -> Vec<Unlock> {
+    match crate::profile::current_data_dir() {
+        Ok(dir) => load(&dir).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Every achievement with the local time it was unlocked at, if it was
+pub fn list(unlocked: &[Unlock]) -> Vec<(&'static Achievement, Option<String>)> {
+    all()
+        .iter()
+        .map(|a| {
+            let when = unlocked.iter().find(|u| u.id == a.id).map(|u| {
+                chrono::DateTime::parse_from_rfc3339(&u.timestamp)
+                    .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|_| u.timestamp.clone())
+            });
+            (a, when)
+        })
+        .collect()
+}
+
+/// e.g. "Achievement unlocked: Triple digits", `None` if nothing was
+pub fn unlocked_line(new: &[Achievement]) -> Option<String> {
+    if new.is_empty() {
+        return None;
+    }
+    let names: Vec<&str> = new.iter().map(|a| a.name.as_str()).collect();
+    let plural = if new.len() == 1 { "" } else { "s" };
+    Some(format!("Achievement{} unlocked: {}", plural, names.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+
+    fn entry(day: u32, wpm: f64, accuracy: f64, test_type: TestType) -> LeaderboardEntry {
+        LeaderboardEntry {
+            wpm,
+            accuracy,
+            test_type,
+            test_mode: "time".to_string(),
+            word_count: 40,
+            test_duration: 60.0,
+            timestamp: format!("2025-09-{:02}T12:00:00+00:00", day),
+            language: Language::English,
+            flag: None,
+        }
+    }
+
+    fn find(id: &str) -> &'static Achievement {
+        all().iter().find(|a| a.id == id).unwrap()
+    }
+
+    #[test]
+    fn test_bundled_rules_parse() {
+        assert!(all().len() >= 4);
+        assert_eq!(find("wpm_100").rule, Rule::Wpm { min: 100.0, test_type: None });
+        assert_eq!(
+            find("perfect_60s").rule,
+            Rule::Accuracy { min: 100.0, test_type: Some(TestType::Time(60)) }
+        );
+    }
+
+    #[test]
+    fn test_rules_against_history() {
+        let mut flagged = entry(1, 140.0, 100.0, TestType::Time(60));
+        flagged.flag = Some(crate::anticheat::CheatFlag::ImpossibleSpeed { wpm: 140.0 });
+        let mut history: Vec<LeaderboardEntry> = (1..=6).map(|day| entry(day, 80.0, 97.0, TestType::Time(60))).collect();
+        history.push(flagged);
+        history.push(entry(9, 101.0, 100.0, TestType::Time(30)));
+        let levels = [true, false];
+        let progress = Progress { history: &history, completed_levels: &levels };
+
+        let met: Vec<&str> = newly_met(all(), &[], &progress).iter().map(|a| a.id.as_str()).collect();
+        assert!(met.contains(&"wpm_100"));
+        // 100% only on a 30s test; the flagged 60s one doesn't count
+        assert!(!met.contains(&"perfect_60s"));
+        // Six days in a row, then a gap
+        assert!(!met.contains(&"streak_7"));
+        assert!(!met.contains(&"all_levels"));
+
+        history.push(entry(7, 60.0, 100.0, TestType::Time(60)));
+        let levels = [true, true];
+        let progress = Progress { history: &history, completed_levels: &levels };
+        let unlocked = vec![Unlock { id: "wpm_100".to_string(), timestamp: String::new() }];
+        let met: Vec<&str> = newly_met(all(), &unlocked, &progress).iter().map(|a| a.id.as_str()).collect();
+        assert!(met.contains(&"perfect_60s"));
+        assert!(met.contains(&"streak_7"));
+        assert!(met.contains(&"all_levels"));
+        assert!(!met.contains(&"wpm_100"));
+    }
+
+    #[test]
+    fn test_rules_count_results_off_the_leaderboard() {
+        use crate::storage::backend::Storage;
+        use crate::storage::json::JsonStorage;
+
+        let dir = std::env::temp_dir().join(format!("typeman-achievements-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let storage = JsonStorage::new(dir.clone(), dir.join("practice_results"));
+
+        // A slow week, then 110 faster tests that push it off the leaderboard
+        let mut results: Vec<LeaderboardEntry> = (1..=7).map(|day| entry(day, 20.0, 90.0, TestType::Time(60))).collect();
+        results.extend((0..110).map(|i| LeaderboardEntry {
+            timestamp: format!("2025-09-20T12:{:02}:{:02}+00:00", i / 60, i % 60),
+            ..entry(20, 60.0, 95.0, TestType::Time(60))
+        }));
+        storage.save_entries(&results).unwrap();
+        assert!(storage.load_entries().unwrap().iter().all(|e| e.wpm > 20.0));
+
+        let history = storage.load_history(None).unwrap();
+        let progress = Progress { history: &history, completed_levels: &[] };
+        assert!(Rule::Tests { count: 117 }.is_met(&progress));
+        assert!(Rule::Minutes { total: 117.0 }.is_met(&progress));
+        assert!(Rule::DayStreak { days: 7 }.is_met(&progress));
+
+        let new = check(&dir, &progress).unwrap();
+        assert!(new.iter().any(|a| a.id == "streak_7"));
+        assert_eq!(load(&dir).unwrap().len(), new.len());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+/// Keystrokes closer together than this are treated as one pasted block
+const PASTE_INTERVAL_MS: f64 = 3.0;
+/// Shortest run of near-simultaneous keystrokes reported as a paste
+const PASTE_MIN_CHARS: usize = 8;
+/// Keystrokes in the sliding window used for the sustained speed check
+const SPEED_WINDOW: usize = 50;
+/// Nobody sustains more than this over a full window
+const MAX_HUMAN_WPM: f64 = 250.0;
+/// Timing uniformity is only judged on reasonably long tests
+const UNIFORM_MIN_INTERVALS: usize = 30;
+/// Human keystroke intervals vary far more than this (stddev / mean)
+const UNIFORM_MAX_VARIATION: f64 = 0.05;
+
+/// Why a result looks like it wasn't typed by hand
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CheatFlag {
+    PasteBurst { chars: usize },
+    ImpossibleSpeed { wpm: f64 },
+    UniformTiming { variation: f64 },
+}
+
+impl CheatFlag {
+    /// Compact form used in CSV exports
+    pub fn to_code(&self) -> String {
+        match self {
+            CheatFlag::PasteBurst { chars } => format!("paste:{}", chars),
+            CheatFlag::ImpossibleSpeed { wpm } => format!("speed:{:.1}", wpm),
+            CheatFlag::UniformTiming { variation } => format!("uniform:{:.3}", variation),
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        let (kind, value) = code.split_once(':')?;
+        match kind {
+            "paste" => value.parse().ok().map(|chars| CheatFlag::PasteBurst { chars }),
+            "speed" => value.parse().ok().map(|wpm| CheatFlag::ImpossibleSpeed { wpm }),
+            "uniform" => value.parse().ok().map(|variation| CheatFlag::UniformTiming { variation }),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CheatFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheatFlag::PasteBurst { chars } => write!(f, "pasted {} chars", chars),
+            CheatFlag::ImpossibleSpeed { wpm } => write!(f, "{:.0} WPM burst", wpm),
+            CheatFlag::UniformTiming { .. } => write!(f, "robotic timing"),
+        }
+    }
+}
+
+/// Checks a keystroke timeline (milliseconds since the test started) for
+/// pasted text, inhuman speed and machine-like regular timing
+pub fn analyze(times_ms: &[f64]) -> Option<CheatFlag> {
+    let intervals: Vec<f64> = times_ms.windows(2).map(|w| w[1] - w[0]).collect();
+
+    // Paste: a run of keystrokes arriving practically at once
+    let mut run = 0;
+    let mut longest = 0;
+    for &interval in &intervals {
+        if interval < PASTE_INTERVAL_MS {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    if longest + 1 >= PASTE_MIN_CHARS {
+        return Some(CheatFlag::PasteBurst { chars: longest + 1 });
+    }
+
+    // Speed: the fastest window of SPEED_WINDOW keystrokes
+    if times_ms.len() > SPEED_WINDOW {
+        let fastest_ms = times_ms
+            .windows(SPEED_WINDOW + 1)
+            .map(|w| w[SPEED_WINDOW] - w[0])
+            .fold(f64::MAX, f64::min)
+            .max(1.0);
+        let wpm = (SPEED_WINDOW as f64 / 5.0) / (fastest_ms / 60_000.0);
+        if wpm > MAX_HUMAN_WPM {
+            return Some(CheatFlag::ImpossibleSpeed { wpm });
+        }
+    }
+
+    // Uniformity: scripted input tends to use a fixed delay
+    if intervals.len() >= UNIFORM_MIN_INTERVALS {
+        let mean = intervals.iter().sum::<f64>() / intervals.len() as f64;
+        if mean > 0.0 {
+            let variance = intervals.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / intervals.len() as f64;
+            let variation = variance.sqrt() / mean;
+            if variation < UNIFORM_MAX_VARIATION {
+                return Some(CheatFlag::UniformTiming { variation });
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Roughly 80 WPM with natural jitter
+    fn human(count: usize) -> Vec<f64> {
+        let jitter = [0.0, 45.0, -30.0, 80.0, -55.0, 20.0, 110.0, -10.0];
+        let mut t = 0.0;
+        (0..count)
+            .map(|i| {
+                t += 150.0 + jitter[i % jitter.len()];
+                t
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_human_timing_passes() {
+        assert_eq!(analyze(&human(200)), None);
+        assert_eq!(analyze(&[]), None);
+    }
+
+    #[test]
+    fn test_detects_paste_burst() {
+        let mut times = human(20);
+        let last = *times.last().unwrap();
+        times.extend((1..=12).map(|i| last + 100.0 + i as f64 * 0.5));
+        assert_eq!(analyze(&times), Some(CheatFlag::PasteBurst { chars: 12 }));
+    }
+
+    #[test]
+    fn test_detects_impossible_speed() {
+        // 20ms per key is 600 WPM
+        let times: Vec<f64> = (0..80).map(|i| i as f64 * 20.0 + (i % 3) as f64 * 6.0).collect();
+        assert!(matches!(analyze(&times), Some(CheatFlag::ImpossibleSpeed { .. })));
+    }
+
+    #[test]
+    fn test_detects_uniform_timing() {
+        let times: Vec<f64> = (0..60).map(|i| i as f64 * 120.0).collect();
+        assert!(matches!(analyze(&times), Some(CheatFlag::UniformTiming { .. })));
+    }
+
+    #[test]
+    fn test_flag_code_round_trip() {
+        for flag in [
+            CheatFlag::PasteBurst { chars: 14 },
+            CheatFlag::ImpossibleSpeed { wpm: 412.5 },
+            CheatFlag::UniformTiming { variation: 0.012 },
+        ] {
+            assert_eq!(CheatFlag::from_code(&flag.to_code()), Some(flag));
+        }
+        assert_eq!(CheatFlag::from_code("bogus"), None);
+    }
+}
@@ -1,8 +1,9 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::custom_colors::MyColor;
+use crate::themes::{self, Role};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColorScheme {
     Default,
     Dark,
@@ -13,10 +14,86 @@ pub enum ColorScheme {
     Forest,
     ForestDark,
     Pink,
+    /// Pure black and white with saturated accents, for low vision
+    HighContrast,
+    /// A theme from a file in the themes directory
+    User(&'static str),
+}
+
+/// How a scheme is written in the config: built-ins by variant name,
+/// user themes as `{"User": "<name>"}`
+#[derive(Serialize, Deserialize)]
+enum SchemeName {
+    Default,
+    Dark,
+    Light,
+    Monochrome,
+    Ocean,
+    OceanDark,
+    Forest,
+    ForestDark,
+    Pink,
+    HighContrast,
+    User(String),
+}
+
+impl From<SchemeName> for ColorScheme {
+    fn from(name: SchemeName) -> Self {
+        match name {
+            SchemeName::Default => ColorScheme::Default,
+            SchemeName::Dark => ColorScheme::Dark,
+            SchemeName::Light => ColorScheme::Light,
+            SchemeName::Monochrome => ColorScheme::Monochrome,
+            SchemeName::Ocean => ColorScheme::Ocean,
+            SchemeName::OceanDark => ColorScheme::OceanDark,
+            SchemeName::Forest => ColorScheme::Forest,
+            SchemeName::ForestDark => ColorScheme::ForestDark,
+            SchemeName::Pink => ColorScheme::Pink,
+            SchemeName::HighContrast => ColorScheme::HighContrast,
+            SchemeName::User(name) => ColorScheme::User(themes::intern(&name)),
+        }
+    }
+}
+
+impl From<ColorScheme> for SchemeName {
+    fn from(scheme: ColorScheme) -> Self {
+        match scheme {
+            ColorScheme::Default => SchemeName::Default,
+            ColorScheme::Dark => SchemeName::Dark,
+            ColorScheme::Light => SchemeName::Light,
+            ColorScheme::Monochrome => SchemeName::Monochrome,
+            ColorScheme::Ocean => SchemeName::Ocean,
+            ColorScheme::OceanDark => SchemeName::OceanDark,
+            ColorScheme::Forest => SchemeName::Forest,
+            ColorScheme::ForestDark => SchemeName::ForestDark,
+            ColorScheme::Pink => SchemeName::Pink,
+            ColorScheme::HighContrast => SchemeName::HighContrast,
+            ColorScheme::User(name) => SchemeName::User(name.to_string()),
+        }
+    }
+}
+
+impl Serialize for ColorScheme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SchemeName::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorScheme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SchemeName::deserialize(deserializer).map(ColorScheme::from)
+    }
 }
 
 impl ColorScheme {
+    /// The built-in schemes followed by the user themes
     pub fn all() -> Vec<ColorScheme> {
+        let mut all = Self::builtin();
+        all.extend(themes::names().into_iter().map(ColorScheme::User));
+        all
+    }
+
+    pub fn builtin() -> Vec<ColorScheme> {
         vec![
             ColorScheme::Default,
             ColorScheme::Dark,
@@ -27,6 +104,7 @@ impl ColorScheme {
             ColorScheme::Forest,
             ColorScheme::ForestDark,
             ColorScheme::Pink,
+            ColorScheme::HighContrast,
         ]
     }
 
@@ -41,6 +119,8 @@ impl ColorScheme {
             ColorScheme::Forest => "Forest",
             ColorScheme::ForestDark => "Forest Dark",
             ColorScheme::Pink => "Pink",
+            ColorScheme::HighContrast => "High Contrast",
+            ColorScheme::User(name) => name,
         }
     }
 
@@ -64,6 +144,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(50, 100, 50, 255),
             ColorScheme::ForestDark => MyColor::new(60, 120, 60, 255),
             ColorScheme::Pink => MyColor::new(100, 20, 70, 255),
+            ColorScheme::HighContrast => MyColor::new(255, 255, 255, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Border).unwrap_or(ColorScheme::Default.border_color()),
         }
         .into()
     }
@@ -79,6 +161,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(100, 150, 100, 255),
             ColorScheme::ForestDark => MyColor::new(70, 80, 70, 255),
             ColorScheme::Pink => MyColor::new(80, 70, 70, 255),
+            ColorScheme::HighContrast => MyColor::new(150, 150, 150, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Ref).unwrap_or(ColorScheme::Default.ref_color()),
         }
         .into()
     }
@@ -94,6 +178,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(20, 40, 20, 255),
             ColorScheme::ForestDark => MyColor::new(10, 10, 10, 255),
             ColorScheme::Pink => MyColor::new(7, 0, 2, 255),
+            ColorScheme::HighContrast => MyColor::new(0, 0, 0, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Bg).unwrap_or(ColorScheme::Default.bg_color()),
         }
         .into()
     }
@@ -109,6 +195,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(150, 255, 150, 255),
             ColorScheme::ForestDark => MyColor::new(100, 200, 100, 255),
             ColorScheme::Pink => MyColor::new(255, 20, 147, 255),
+            ColorScheme::HighContrast => MyColor::new(255, 255, 0, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Main).unwrap_or(ColorScheme::Default.main_color()),
         }
         .into()
     }
@@ -124,6 +212,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(100, 180, 100, 255),
             ColorScheme::ForestDark => MyColor::new(150, 230, 100, 255),
             ColorScheme::Pink => MyColor::new(200, 10, 120, 255),
+            ColorScheme::HighContrast => MyColor::new(200, 200, 0, 255),
+            ColorScheme::User(name) => themes::color(name, Role::DimmerMain).unwrap_or(ColorScheme::Default.dimmer_main()),
         }
         .into()
     }
@@ -139,6 +229,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(200, 255, 200, 255),
             ColorScheme::ForestDark => MyColor::new(180, 255, 180, 255),
             ColorScheme::Pink => MyColor::new(200, 200, 200, 255),
+            ColorScheme::HighContrast => MyColor::new(255, 255, 255, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Text).unwrap_or(ColorScheme::Default.text_color()),
         }
         .into()
     }
@@ -154,6 +246,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(150, 255, 150, 255),
             ColorScheme::ForestDark => MyColor::new(100, 200, 100, 255),
             ColorScheme::Pink => MyColor::new(100, 20, 70, 255),
+            ColorScheme::HighContrast => MyColor::new(255, 255, 0, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Chart).unwrap_or(ColorScheme::Default.chart_color()),
         }
         .into()
     }
@@ -169,6 +263,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(200, 255, 255, 255),
             ColorScheme::ForestDark => MyColor::new(200, 255, 255, 255),
             ColorScheme::Pink => MyColor::new(200, 255, 255, 255),
+            ColorScheme::HighContrast => MyColor::new(255, 255, 255, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Correct).unwrap_or(ColorScheme::Default.correct_color()),
         }
         .into()
     }
@@ -184,6 +280,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(255, 100, 100, 255),
             ColorScheme::ForestDark => MyColor::new(180, 100, 0, 255),
             ColorScheme::Pink => MyColor::new(255, 100, 100, 255),
+            ColorScheme::HighContrast => MyColor::new(0, 200, 255, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Corrected).unwrap_or(ColorScheme::Default.corrected_color()),
         }
         .into()
     }
@@ -199,6 +297,8 @@ impl ColorScheme {
             ColorScheme::Forest => MyColor::new(200, 30, 30, 255),
             ColorScheme::ForestDark => MyColor::new(150, 30, 30, 255),
             ColorScheme::Pink => MyColor::new(255, 30, 30, 255),
+            ColorScheme::HighContrast => MyColor::new(255, 60, 60, 255),
+            ColorScheme::User(name) => themes::color(name, Role::Incorrect).unwrap_or(ColorScheme::Default.incorrect_color()),
         }
         .into()
     }
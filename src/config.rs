@@ -1,18 +1,121 @@
 use crate::color_scheme::ColorScheme;
+use crate::fonts::{self, FontChoice};
+use crate::goals::Goals;
+use crate::keymap::{KeyConflict, Keymap};
 use crate::language::Language;
+use crate::pace::Pace;
+use crate::presets::{self, Preset};
+use crate::profile;
+use crate::storage::backend::StorageBackend;
+use crate::storage::json::create_backup;
+use crate::terminal_colors::ColorDepth;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt::Display;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+pub const CONFIG_FILE: &str = "config.json";
+/// Version of the config layout written by this build. Bump it and add a
+/// migration to `MIGRATIONS` whenever a setting is renamed or changes meaning.
+pub const CONFIG_VERSION: u32 = 3;
+
+/// `MIGRATIONS[i]` turns a version `i + 1` config into a version `i + 2` one
+const MIGRATIONS: [fn(&mut Map<String, Value>); 2] = [migrate_v1, migrate_v2];
+
+/// Version 1 files predate the version field; their settings still mean the same
+fn migrate_v1(_config: &mut Map<String, Value>) {}
+
+/// Version 2 stored the mode as five flags; the first one set wins, as it did
+fn migrate_v2(config: &mut Map<String, Value>) {
+    let flags = [
+        ("practice_mode", Mode::Practice),
+        ("time_mode", Mode::Time),
+        ("word_mode", Mode::Words),
+        ("quote", Mode::Quote),
+        ("wiki_mode", Mode::Wiki),
+    ];
+    let mut mode = None;
+    for (flag, flag_mode) in flags {
+        if config.remove(flag).and_then(|value| value.as_bool()) == Some(true) {
+            mode = mode.or(Some(flag_mode));
+        }
+    }
+    if let Ok(mode) = serde_json::to_value(mode.unwrap_or_default()) {
+        config.insert("mode".to_string(), mode);
+    }
+}
+
+/// Kind of test; exactly one is selected at a time
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    #[default]
+    Time,
+    Words,
+    Quote,
+    Wiki,
+    Practice,
+}
+
+impl Mode {
+    pub fn all() -> &'static [Mode] {
+        &[Mode::Time, Mode::Words, Mode::Quote, Mode::Wiki, Mode::Practice]
+    }
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Time => write!(f, "time"),
+            Mode::Words => write!(f, "words"),
+            Mode::Quote => write!(f, "quote"),
+            Mode::Wiki => write!(f, "wiki"),
+            Mode::Practice => write!(f, "practice"),
+        }
+    }
+}
+
+/// Something wrong with a config file that was still loaded as well as possible
+#[derive(Debug)]
+pub enum ConfigIssue {
+    /// Not a JSON object at all; every setting is back to its default
+    Unreadable(String),
+    /// Written by a newer typeman; settings this version doesn't know are dropped
+    NewerVersion(u32),
+    /// A setting whose value doesn't fit, reset to its default
+    InvalidField { field: String, error: String },
+    /// A setting this version doesn't know, most likely a typo; it is dropped
+    UnknownField(String),
+    /// Two actions had the same key binding
+    KeyConflict(KeyConflict),
+}
+
+impl Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigIssue::Unreadable(error) => write!(f, "not a valid config file ({}), using the defaults", error),
+            ConfigIssue::NewerVersion(version) => write!(
+                f,
+                "written by a newer typeman (config version {}, this one knows {})",
+                version, CONFIG_VERSION
+            ),
+            ConfigIssue::InvalidField { field, error } => write!(f, "'{}' reset to its default: {}", field, error),
+            ConfigIssue::UnknownField(field) => write!(f, "unknown setting '{}' ignored", field),
+            ConfigIssue::KeyConflict(conflict) => write!(f, "{}", conflict),
+        }
+    }
+}
+
+/// Missing settings take their default, so adding one never discards the rest
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct AppConfig {
+    /// Layout version of the file, see `CONFIG_VERSION`
+    pub version: u32,
     pub punctuation: bool,
     pub numbers: bool,
-    pub time_mode: bool,
-    pub word_mode: bool,
-    pub quote: bool,
-    pub wiki_mode: bool,
-    pub practice_mode: bool,
+    pub mode: Mode,
     pub batch_size: usize,
     pub test_time: f32,
     pub selected_level: usize,
@@ -20,18 +123,47 @@ pub struct AppConfig {
     pub color_scheme: ColorScheme,
     pub word_number: usize,
     pub top_words: usize,
+    pub storage_backend: StorageBackend,
+    /// Shared (synced) directory holding the team leaderboard
+    pub team_dir: Option<PathBuf>,
+    /// Name shown next to this user's results on the team leaderboard
+    pub player_name: Option<String>,
+    /// Leaderboard server results are synced to (see `typeman sync`)
+    pub server_url: Option<String>,
+    /// Shared token sent with uploads, for servers started with one
+    pub server_token: Option<String>,
+    /// Speed of the pace caret shown while typing
+    pub pace: Pace,
+    /// Daily time, test count and speed targets (see `typeman goals`)
+    pub goals: Goals,
+    /// Named sets of test settings (see `typeman preset`)
+    pub presets: Vec<Preset>,
+    /// Preset applied last, where cycling through them continues from
+    pub preset: Option<String>,
+    /// Colors the terminal frontends use: "truecolor", "256", "16" or "mono".
+    /// Detected from the terminal when left out.
+    pub color_depth: Option<ColorDepth>,
+    /// Key bindings of the actions the frontends share, e.g. `"restart": ["Tab+Enter"]`
+    pub keymap: Keymap,
+    /// Plain line-by-line output in the terminal for screen readers (also `--accessible`)
+    pub accessible: bool,
+    /// Size of the GUI text relative to what fits the window, see `text_scale`
+    pub text_scale: f32,
+    /// Font of the GUI text, e.g. `"roboto_mono"` or `{"file": "/usr/share/fonts/Hack-Regular.ttf"}`
+    pub font: FontChoice,
+    /// Height of the GUI text lines in multiples of the text height
+    pub line_spacing: f32,
+    /// Whether tests typed in CLI mode go on the leaderboard too
+    pub save_cli_results: bool,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             punctuation: false,
             numbers: false,
-            time_mode: true,
-            word_mode: false,
-            quote: false,
-            practice_mode: false,
-            wiki_mode: false,
+            mode: Mode::default(),
             batch_size: 50,
             test_time: 30.0,
             selected_level: 0,
@@ -39,48 +171,267 @@ impl Default for AppConfig {
             color_scheme: ColorScheme::default(),
             word_number: 50,
             top_words: 500,
+            storage_backend: StorageBackend::default(),
+            team_dir: None,
+            player_name: None,
+            server_url: None,
+            server_token: None,
+            pace: Pace::default(),
+            goals: Goals::default(),
+            presets: presets::defaults(),
+            preset: None,
+            color_depth: None,
+            keymap: Keymap::default(),
+            accessible: false,
+            text_scale: 1.0,
+            font: FontChoice::default(),
+            line_spacing: fonts::DEFAULT_LINE_SPACING,
+            save_cli_results: false,
         }
     }
 }
 
 impl AppConfig {
-    fn get_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let home = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE"))
-            .map_err(|_| "Unable to find home directory")?;
+    /// Loads the current profile's config without a word, as it's done all
+    /// the time (and while a frontend owns the terminal)
+    pub fn load() -> Self {
+        match profile::current_config_dir() {
+            Ok(dir) => Self::load_from(&dir),
+            Err(_) => Self::default(),
+        }
+    }
 
-        let config_dir = PathBuf::from(home).join(".config").join("typeman");
-        fs::create_dir_all(&config_dir)?;
-        Ok(config_dir)
+    /// Loads the config stored in a profile directory, as well as possible
+    /// and without touching the file
+    pub fn load_from(dir: &Path) -> Self {
+        match fs::read_to_string(dir.join(CONFIG_FILE)) {
+            Ok(content) => Self::parse(&content).0,
+            Err(_) => Self::default(),
+        }
     }
 
-    fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
-        Ok(Self::get_config_dir()?.join("config.json"))
+    /// Loads the current profile's config, migrates or repairs the file and
+    /// reports problems on stderr. Runs once at startup, before a frontend
+    /// takes over the terminal.
+    pub fn load_and_report() -> Self {
+        let Ok(dir) = profile::current_config_dir() else {
+            return Self::default();
+        };
+        let (config, notes) = Self::repair(&dir);
+        for note in notes {
+            eprintln!("{}", note);
+        }
+        config
     }
 
-    pub fn load() -> Self {
-        match Self::get_config_path() {
-            Ok(path) => {
-                if path.exists() {
-                    match fs::read_to_string(&path) {
-                        Ok(content) => match serde_json::from_str::<AppConfig>(&content) {
-                            Ok(config) => config,
-                            Err(_) => Self::default(),
-                        },
-                        Err(_) => Self::default(),
-                    }
-                } else {
-                    Self::default()
+    /// Loads the config in `dir`. Older files are migrated and broken ones
+    /// repaired, each time after backing up the original. Files of a newer
+    /// typeman are read as well as possible but never written back. Returns
+    /// what the user should be told.
+    fn repair(dir: &Path) -> (Self, Vec<String>) {
+        let path = dir.join(CONFIG_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return (Self::default(), Vec::new());
+        };
+        let (config, issues) = Self::parse(&content);
+        let outdated = file_version(&content).is_some_and(|version| version < CONFIG_VERSION);
+        let mut notes = Vec::new();
+        if issues.is_empty() && !outdated {
+            return (config, notes);
+        }
+
+        if !issues.is_empty() {
+            notes.push(format!("Problems in {}:", path.display()));
+            notes.extend(issues.iter().map(|issue| format!("  {}", issue)));
+        }
+        // Repairing it would throw away what the newer version wrote
+        if issues.iter().any(|issue| matches!(issue, ConfigIssue::NewerVersion(_))) {
+            notes.push(format!("Leaving {} as is; the settings are only used for this run", path.display()));
+            return (config, notes);
+        }
+        match create_backup(&path) {
+            Ok(backup) => {
+                if !issues.is_empty() {
+                    notes.push(format!("The original file was backed up to {}", backup.display()));
+                }
+                if let Err(e) = config.save_to(dir) {
+                    notes.push(format!("Failed to save the repaired config: {}", e));
                 }
             }
-            Err(_) => Self::default(),
+            // Better to ask again next time than to overwrite the only copy
+            Err(e) => notes.push(format!("Failed to back up {}, leaving it as is: {}", path.display(), e)),
+        }
+        (config, notes)
+    }
+
+    /// Reads a config file's content, migrating it to the current version.
+    /// Settings that don't fit are reset one by one instead of all at once.
+    pub fn parse(content: &str) -> (Self, Vec<ConfigIssue>) {
+        let mut fields = match serde_json::from_str::<Value>(content) {
+            Ok(Value::Object(fields)) => fields,
+            Ok(_) => return (Self::default(), vec![ConfigIssue::Unreadable("expected an object".to_string())]),
+            Err(e) => return (Self::default(), vec![ConfigIssue::Unreadable(e.to_string())]),
+        };
+
+        let mut issues = Vec::new();
+        let version = fields.get("version").and_then(Value::as_u64).unwrap_or(1) as u32;
+        if version > CONFIG_VERSION {
+            issues.push(ConfigIssue::NewerVersion(version));
+        }
+        for migration in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+            migration(&mut fields);
+        }
+        fields.insert("version".to_string(), Value::from(CONFIG_VERSION));
+
+        // A newer version's own settings are expected to be unknown here
+        if version <= CONFIG_VERSION {
+            if let Ok(Value::Object(known)) = serde_json::to_value(AppConfig::default()) {
+                let unknown = fields.keys().filter(|field| !known.contains_key(*field));
+                issues.extend(unknown.cloned().map(ConfigIssue::UnknownField));
+            }
         }
+
+        let mut config = match serde_json::from_value::<AppConfig>(Value::Object(fields.clone())) {
+            Ok(config) => config,
+            Err(_) => {
+                let mut kept = Map::new();
+                for (field, value) in fields {
+                    kept.insert(field.clone(), value);
+                    if let Err(e) = serde_json::from_value::<AppConfig>(Value::Object(kept.clone())) {
+                        kept.remove(&field);
+                        issues.push(ConfigIssue::InvalidField { field, error: e.to_string() });
+                    }
+                }
+                serde_json::from_value(Value::Object(kept)).unwrap_or_default()
+            }
+        };
+        issues.extend(config.keymap.resolve_conflicts().into_iter().map(ConfigIssue::KeyConflict));
+        (config, issues)
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let path = Self::get_config_path()?;
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(&path, content)?;
+        self.save_to(&profile::current_config_dir()?)
+    }
+
+    /// Saves the config into a profile directory
+    pub fn save_to(&self, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let content = serde_json::to_string_pretty(&AppConfig { version: CONFIG_VERSION, ..self.clone() })?;
+        fs::write(dir.join(CONFIG_FILE), content)?;
         Ok(())
     }
 }
+
+/// Version a config file says it has; files without one are version 1
+fn file_version(content: &str) -> Option<u32> {
+    let fields: Map<String, Value> = serde_json::from_str(content).ok()?;
+    Some(fields.get("version").and_then(Value::as_u64).unwrap_or(1) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::Action;
+
+    #[test]
+    fn test_old_and_partial_configs_keep_their_settings() {
+        // A version 1 file, from before most settings existed
+        let (config, issues) = AppConfig::parse(r#"{"punctuation": true, "test_time": 60.0, "language": "Italian"}"#);
+        assert!(issues.is_empty());
+        assert!(config.punctuation);
+        assert_eq!(config.test_time, 60.0);
+        assert_eq!(config.language, Language::Italian);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.top_words, AppConfig::default().top_words);
+    }
+
+    #[test]
+    fn test_mode_flags_are_migrated() {
+        let (config, issues) =
+            AppConfig::parse(r#"{"version": 2, "time_mode": false, "word_mode": true, "quote": false, "wiki_mode": false, "practice_mode": false}"#);
+        assert!(issues.is_empty());
+        assert_eq!(config.mode, Mode::Words);
+        let (config, _) = AppConfig::parse(r#"{"time_mode": true, "practice_mode": true}"#);
+        assert_eq!(config.mode, Mode::Practice);
+        assert_eq!(AppConfig::parse("{}").0.mode, Mode::Time);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_reset_alone() {
+        let (config, issues) = AppConfig::parse(r#"{"version": 3, "numbers": true, "language": "Klingon", "word_number": "many"}"#);
+        assert!(config.numbers);
+        assert_eq!(config.language, Language::default());
+        let fields: Vec<&str> = issues
+            .iter()
+            .map(|issue| match issue {
+                ConfigIssue::InvalidField { field, .. } => field.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(fields, ["language", "word_number"]);
+
+        let (config, issues) = AppConfig::parse("{ not json");
+        assert!(matches!(issues[..], [ConfigIssue::Unreadable(_)]));
+        assert!(!config.numbers);
+        assert!(matches!(AppConfig::parse(r#"{"version": 9}"#).1[..], [ConfigIssue::NewerVersion(9)]));
+    }
+
+    #[test]
+    fn test_unknown_settings_are_reported() {
+        let (config, issues) = AppConfig::parse(r#"{"version": 3, "numbers": true, "punctation": true}"#);
+        assert!(config.numbers);
+        assert!(!config.punctuation);
+        assert!(matches!(&issues[..], [ConfigIssue::UnknownField(field)] if field == "punctation"));
+
+        // Old mode flags are migrated, not unknown
+        assert!(AppConfig::parse(r#"{"version": 2, "word_mode": true}"#).1.is_empty());
+    }
+
+    #[test]
+    fn test_repair_keeps_a_backup() {
+        let dir = std::env::temp_dir().join(format!("typeman-config-repair-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(CONFIG_FILE), r#"{"version": 3, "numbers": true, "language": "Klingon"}"#).unwrap();
+
+        // Plain loads never touch the file
+        assert!(AppConfig::load_from(&dir).numbers);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let (config, notes) = AppConfig::repair(&dir);
+        assert!(config.numbers);
+        assert!(notes.iter().any(|note| note.contains("backed up")));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        assert!(AppConfig::repair(&dir).1.is_empty());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_key_conflicts_are_reported() {
+        let (config, issues) = AppConfig::parse(r#"{"keymap": {"quit": ["Esc", "Tab+A"]}}"#);
+        assert!(matches!(&issues[..], [ConfigIssue::KeyConflict(conflict)] if conflict.dropped == Action::Achievements));
+        assert_eq!(config.keymap.hint(Action::Achievements), "(unbound)");
+        assert_eq!(config.keymap.bindings(Action::Quit).len(), 2);
+
+        let (config, issues) = AppConfig::parse(r#"{"keymap": {"quit": ["Q"]}}"#);
+        assert!(matches!(&issues[..], [ConfigIssue::InvalidField { field, .. }] if field == "keymap"));
+        assert_eq!(config.keymap, Keymap::default());
+    }
+
+    #[test]
+    fn test_newer_config_is_left_alone() {
+        let dir = std::env::temp_dir().join(format!("typeman-config-newer-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let content = r#"{"version": 99, "numbers": true, "from_the_future": 1}"#;
+        fs::write(dir.join(CONFIG_FILE), content).unwrap();
+
+        let (config, notes) = AppConfig::repair(&dir);
+        assert!(config.numbers);
+        assert!(notes.iter().any(|note| note.starts_with("Leaving")));
+        assert_eq!(fs::read_to_string(dir.join(CONFIG_FILE)).unwrap(), content);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
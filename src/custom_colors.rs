@@ -1,3 +1,6 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MyColor {
     pub r: u8,
     pub g: u8,
@@ -9,6 +12,59 @@ impl MyColor {
     pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
         Self { r, g, b, a }
     }
+
+    /// "#rgb", "#rrggbb" or "#rrggbbaa", the leading # being optional
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if !hex.is_ascii() {
+            return None;
+        }
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        match hex.len() {
+            3 => {
+                let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+                Some(Self::new(digit(0)?, digit(1)?, digit(2)?, 255))
+            }
+            6 => Some(Self::new(byte(0)?, byte(2)?, byte(4)?, 255)),
+            8 => Some(Self::new(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
+            _ => None,
+        }
+    }
+
+    /// "#rrggbb", with the alpha only when it isn't opaque
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    /// `self` moved towards `other` by `amount` (0.0 is `self`, 1.0 is `other`)
+    pub fn mix(&self, other: MyColor, amount: f32) -> Self {
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+        Self::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            channel(self.a, other.a),
+        )
+    }
+}
+
+/// Colors are written as hex strings in theme files
+impl Serialize for MyColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for MyColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        MyColor::from_hex(&hex)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid color {:?}, expected #rrggbb", hex)))
+    }
 }
 
 #[cfg(feature = "gui")]
@@ -25,7 +81,8 @@ impl From<MyColor> for macroquad::color::Color {
 
 #[cfg(any(feature = "cli", feature = "tui"))]
 impl From<MyColor> for ratatui::style::Color {
+    /// As close as the terminal gets, see `terminal_colors`
     fn from(c: MyColor) -> Self {
-        ratatui::style::Color::Rgb(c.r, c.g, c.b)
+        crate::terminal_colors::ColorDepth::current().nearest(c).into()
     }
 }
@@ -0,0 +1,169 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::language::Language;
+use crate::leaderboard::{self, LeaderboardEntry, LeaderboardError, TestType};
+use crate::utils;
+
+/// Words in a daily challenge
+pub const DAILY_WORDS: usize = 30;
+/// The challenge sticks to common words so it's fair for everyone
+const DAILY_TOP_WORDS: usize = 200;
+
+/// The text of one day, the same for everyone on the same version
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyChallenge {
+    pub date: NaiveDate,
+    pub text: String,
+}
+
+impl DailyChallenge {
+    pub fn new(date: NaiveDate, language: Language) -> Self {
+        DailyChallenge { date, text: text(date, language) }
+    }
+
+    pub fn today(language: Language) -> Self {
+        Self::new(today(), language)
+    }
+}
+
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Text of the challenge on `date`, generated from a seed derived from the date
+pub fn text(date: NaiveDate, language: Language) -> String {
+    let mut rng = StdRng::seed_from_u64(date.num_days_from_ce() as u64);
+    let word_list = utils::read_first_n_words(DAILY_TOP_WORDS, language);
+    utils::get_reference_with(false, false, &word_list, DAILY_WORDS, &mut rng)
+}
+
+/// The scored attempt of `date`, if there was one
+pub fn result_on(history: &[LeaderboardEntry], date: NaiveDate) -> Option<&LeaderboardEntry> {
+    history.iter().find(|e| e.test_type == TestType::Daily(date))
+}
+
+/// Days in a row with a daily result, up to today. Today not being played yet
+/// doesn't break the streak.
+pub fn streak(history: &[LeaderboardEntry], today: NaiveDate) -> usize {
+    let mut day = if result_on(history, today).is_some() {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut streak = 0;
+    while result_on(history, day).is_some() {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+/// Daily results, newest first
+pub fn recent(history: &[LeaderboardEntry], count: usize) -> Vec<(NaiveDate, &LeaderboardEntry)> {
+    let mut results: Vec<(NaiveDate, &LeaderboardEntry)> = history
+        .iter()
+        .filter_map(|e| match e.test_type {
+            TestType::Daily(date) => Some((date, e)),
+            _ => None,
+        })
+        .collect();
+    results.sort_by(|a, b| b.0.cmp(&a.0));
+    results.truncate(count);
+    results
+}
+
+/// e.g. "Daily 2025-09-11 · scored 72 wpm · streak 3 days"
+pub fn status(history: &[LeaderboardEntry], date: NaiveDate) -> String {
+    let score = match result_on(history, date) {
+        Some(entry) => format!("scored {:.0} wpm", entry.wpm),
+        None => "one scored attempt".to_string(),
+    };
+    let days = streak(history, date);
+    format!("Daily {} · {} · streak {} day{}", date, score, days, if days == 1 { "" } else { "s" })
+}
+
+/// Earlier daily results on one line, e.g. "09-10 68 wpm  09-09 70 wpm"
+pub fn recent_line(history: &[LeaderboardEntry], date: NaiveDate, count: usize) -> String {
+    recent(history, count + 1)
+        .iter()
+        .filter(|(day, _)| *day != date)
+        .take(count)
+        .map(|(day, e)| format!("{} {:.0} wpm", day.format("%m-%d"), e.wpm))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Status and earlier results for the screens, read from the stored history
+pub fn summary(date: NaiveDate) -> Vec<String> {
+    let history = leaderboard::load_history(None).unwrap_or_default();
+    vec![status(&history, date), recent_line(&history, date, 7)]
+}
+
+/// Saves a daily result and shares it with the team, unless that day was
+/// already scored. Returns whether it counted.
+pub fn submit(entry: &LeaderboardEntry) -> Result<bool, LeaderboardError> {
+    let TestType::Daily(date) = entry.test_type else {
+        return Ok(false);
+    };
+    if result_on(&leaderboard::load_history(None)?, date).is_some() {
+        return Ok(false);
+    }
+    leaderboard::save_entry(entry)?;
+
+    if let Err(e) = crate::team::publish_configured(entry) {
+        eprintln!("Failed to update the team leaderboard: {}", e);
+    }
+    #[cfg(feature = "sync")]
+    if let Err(e) = crate::sync::submit_configured(entry) {
+        eprintln!("Failed to queue the result for the leaderboard server: {}", e);
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(date: NaiveDate, wpm: f64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            wpm,
+            accuracy: 95.0,
+            test_type: TestType::Daily(date),
+            test_mode: "daily".to_string(),
+            word_count: DAILY_WORDS,
+            test_duration: 25.0,
+            timestamp: format!("{}T08:00:00Z", date),
+            language: Language::English,
+            flag: None,
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).unwrap()
+    }
+
+    #[test]
+    fn test_text_depends_only_on_the_date() {
+        let text = text(date(11), Language::English);
+        assert_eq!(text, super::text(date(11), Language::English));
+        assert_ne!(text, super::text(date(12), Language::English));
+        assert_eq!(text.split_whitespace().count(), DAILY_WORDS);
+    }
+
+    #[test]
+    fn test_streak_and_recent_results() {
+        let history = vec![daily(date(8), 60.0), daily(date(9), 65.0), daily(date(10), 70.0)];
+        // Not played today yet: the streak up to yesterday still stands
+        assert_eq!(streak(&history, date(11)), 3);
+        assert_eq!(streak(&history, date(12)), 0);
+        assert_eq!(streak(&history, date(9)), 2);
+
+        assert_eq!(result_on(&history, date(10)).map(|e| e.wpm), Some(70.0));
+        assert!(result_on(&history, date(11)).is_none());
+        assert_eq!(recent(&history, 2).iter().map(|(d, _)| *d).collect::<Vec<_>>(), [date(10), date(9)]);
+        assert_eq!(recent_line(&history, date(10), 2), "09-09 65 wpm  09-08 60 wpm");
+        assert_eq!(status(&history, date(10)), "Daily 2025-09-10 · scored 70 wpm · streak 3 days");
+    }
+}
@@ -0,0 +1,391 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+use crate::anticheat::CheatFlag;
+use crate::language::Language;
+use crate::leaderboard::{self, LeaderboardEntry, LeaderboardError, TestType};
+
+const CSV_HEADER: &str = "timestamp,wpm,accuracy,test_type,test_mode,word_count,test_duration,language,flag";
+/// Header written before results could be flagged
+const LEGACY_CSV_HEADER: &str = "timestamp,wpm,accuracy,test_type,test_mode,word_count,test_duration,language";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    IoError(std::io::Error),
+    SerializationError(serde_json::Error),
+    InvalidCsv(String),
+    InvalidDate(String),
+    LeaderboardError(LeaderboardError),
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::IoError(e) => write!(f, "{}", e),
+            ExportError::SerializationError(e) => write!(f, "invalid JSON: {}", e),
+            ExportError::InvalidCsv(e) => write!(f, "invalid CSV: {}", e),
+            ExportError::InvalidDate(date) => write!(f, "{:?} is not a date, use YYYY-MM-DD", date),
+            ExportError::LeaderboardError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(error: std::io::Error) -> Self {
+        ExportError::IoError(error)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(error: serde_json::Error) -> Self {
+        ExportError::SerializationError(error)
+    }
+}
+
+impl From<LeaderboardError> for ExportError {
+    fn from(error: LeaderboardError) -> Self {
+        ExportError::LeaderboardError(error)
+    }
+}
+
+/// Outcome of an import, reported back to the user
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+}
+
+/// Parses a `--since` argument in YYYY-MM-DD format
+pub fn parse_since(date: &str) -> Result<NaiveDate, ExportError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| ExportError::InvalidDate(date.to_string()))
+}
+
+/// Keeps entries recorded on or after `since`; entries with unreadable timestamps are dropped
+pub fn filter_since(entries: Vec<LeaderboardEntry>, since: Option<NaiveDate>) -> Vec<LeaderboardEntry> {
+    let Some(since) = since else {
+        return entries;
+    };
+    entries
+        .into_iter()
+        .filter(|entry| {
+            chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+                .map(|t| t.date_naive() >= since)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+pub fn export_entries(entries: &[LeaderboardEntry], format: ExportFormat) -> Result<String, ExportError> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(entries)?),
+        ExportFormat::Csv => Ok(entries_to_csv(entries)),
+    }
+}
+
+/// Parses exported results, detecting JSON or CSV from the content
+pub fn parse_entries(content: &str) -> Result<Vec<LeaderboardEntry>, ExportError> {
+    let trimmed = content.trim_start();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+    if trimmed.starts_with('[') {
+        Ok(serde_json::from_str(trimmed)?)
+    } else {
+        csv_to_entries(content)
+    }
+}
+
+/// Splits incoming entries into new ones and a count of duplicates.
+/// Entries are considered duplicates when their timestamps match.
+pub fn dedup_entries(
+    existing: &[LeaderboardEntry],
+    incoming: Vec<LeaderboardEntry>,
+) -> (Vec<LeaderboardEntry>, usize) {
+    let mut seen: std::collections::HashSet<String> =
+        existing.iter().map(|e| e.timestamp.clone()).collect();
+    let mut new_entries = Vec::new();
+    let mut duplicates = 0;
+
+    for entry in incoming {
+        if seen.insert(entry.timestamp.clone()) {
+            new_entries.push(entry);
+        } else {
+            duplicates += 1;
+        }
+    }
+    (new_entries, duplicates)
+}
+
+/// First moment at which it is `date` somewhere (UTC+14), so the storage can
+/// skip everything older before the local dates are checked
+fn earliest_moment(date: NaiveDate) -> String {
+    format!("{}T00:00:00+14:00", date.format("%Y-%m-%d"))
+}
+
+pub fn export_to_string(format: ExportFormat, since: Option<&str>) -> Result<String, ExportError> {
+    let since = since.map(parse_since).transpose()?;
+    let from = since.map(earliest_moment);
+    let entries = filter_since(leaderboard::load_history(from.as_deref())?, since);
+    export_entries(&entries, format)
+}
+
+pub fn import_file(path: &Path) -> Result<ImportSummary, ExportError> {
+    let content = fs::read_to_string(path)?;
+    let incoming = parse_entries(&content)?;
+
+    let mut summary = ImportSummary::default();
+    let mut valid = Vec::new();
+    for entry in incoming {
+        match entry.validate() {
+            Ok(()) => valid.push(entry),
+            Err(e) => {
+                eprintln!("Skipping invalid entry from {}: {}", entry.timestamp, e);
+                summary.invalid += 1;
+            }
+        }
+    }
+
+    let existing = leaderboard::load_history(None)?;
+    let (new_entries, duplicates) = dedup_entries(&existing, valid);
+    summary.duplicates = duplicates;
+    summary.imported = new_entries.len();
+
+    if !new_entries.is_empty() {
+        leaderboard::save_entries(&new_entries)?;
+    }
+    Ok(summary)
+}
+
+fn format_test_type(test_type: &TestType) -> String {
+    match test_type {
+        TestType::Time(secs) => format!("time:{}", secs),
+        TestType::Word(words) => format!("word:{}", words),
+        TestType::Quote => "quote".to_string(),
+        TestType::Practice(level) => format!("practice:{}", level),
+        TestType::Wiki => "wiki".to_string(),
+        TestType::Race(place) => format!("race:{}", place),
+        TestType::Daily(date) => format!("daily:{}", date),
+    }
+}
+
+fn parse_test_type(value: &str) -> Option<TestType> {
+    let (kind, arg) = match value.split_once(':') {
+        Some((kind, arg)) => (kind, Some(arg)),
+        None => (value, None),
+    };
+    match (kind, arg) {
+        ("time", Some(arg)) => arg.parse().ok().map(TestType::Time),
+        ("word", Some(arg)) => arg.parse().ok().map(TestType::Word),
+        ("practice", Some(arg)) => arg.parse().ok().map(TestType::Practice),
+        ("race", Some(arg)) => arg.parse().ok().map(TestType::Race),
+        ("daily", Some(arg)) => arg.parse().ok().map(TestType::Daily),
+        ("quote", None) => Some(TestType::Quote),
+        ("wiki", None) => Some(TestType::Wiki),
+        _ => None,
+    }
+}
+
+fn parse_language(value: &str) -> Option<Language> {
+    Language::all()
+        .iter()
+        .copied()
+        .find(|lang| lang.to_string().eq_ignore_ascii_case(value))
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+fn entries_to_csv(entries: &[LeaderboardEntry]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for entry in entries {
+        let fields = [
+            entry.timestamp.clone(),
+            entry.wpm.to_string(),
+            entry.accuracy.to_string(),
+            format_test_type(&entry.test_type),
+            entry.test_mode.clone(),
+            entry.word_count.to_string(),
+            entry.test_duration.to_string(),
+            entry.language.to_string(),
+            entry.flag.as_ref().map(|f| f.to_code()).unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_to_entries(content: &str) -> Result<Vec<LeaderboardEntry>, ExportError> {
+    let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+
+    let field_count = match lines.next().map(str::trim) {
+        Some(CSV_HEADER) => 9,
+        Some(LEGACY_CSV_HEADER) => 8,
+        _ => return Err(ExportError::InvalidCsv("missing or unknown header".to_string())),
+    };
+
+    let mut entries = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let row = i + 2;
+        let fields = split_csv_line(line);
+        if fields.len() != field_count {
+            return Err(ExportError::InvalidCsv(format!(
+                "line {}: expected {} fields, found {}",
+                row,
+                field_count,
+                fields.len()
+            )));
+        }
+        let invalid = |name: &str| ExportError::InvalidCsv(format!("line {}: invalid {}", row, name));
+
+        entries.push(LeaderboardEntry {
+            timestamp: fields[0].clone(),
+            wpm: fields[1].parse().map_err(|_| invalid("wpm"))?,
+            accuracy: fields[2].parse().map_err(|_| invalid("accuracy"))?,
+            test_type: parse_test_type(&fields[3]).ok_or_else(|| invalid("test_type"))?,
+            test_mode: fields[4].clone(),
+            word_count: fields[5].parse().map_err(|_| invalid("word_count"))?,
+            test_duration: fields[6].parse().map_err(|_| invalid("test_duration"))?,
+            language: parse_language(&fields[7]).ok_or_else(|| invalid("language"))?,
+            flag: match fields.get(8).map(String::as_str) {
+                None | Some("") => None,
+                Some(code) => Some(CheatFlag::from_code(code).ok_or_else(|| invalid("flag"))?),
+            },
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, test_type: TestType) -> LeaderboardEntry {
+        LeaderboardEntry {
+            wpm: 72.5,
+            accuracy: 96.1,
+            test_type,
+            test_mode: "time".to_string(),
+            word_count: 40,
+            test_duration: 30.0,
+            timestamp: timestamp.to_string(),
+            language: Language::Italian,
+            flag: None,
+        }
+    }
+
+    fn sample() -> Vec<LeaderboardEntry> {
+        vec![
+            entry("2025-09-10T10:30:00+02:00", TestType::Time(30)),
+            entry("2025-09-11T08:00:00Z", TestType::Word(50)),
+            entry("2025-09-12T21:15:00Z", TestType::Quote),
+            entry("2025-09-13T07:45:00Z", TestType::Practice(3)),
+            LeaderboardEntry {
+                flag: Some(CheatFlag::PasteBurst { chars: 20 }),
+                ..entry("2025-09-14T12:00:00Z", TestType::Wiki)
+            },
+        ]
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let entries = sample();
+        let csv = export_entries(&entries, ExportFormat::Csv).unwrap();
+        assert!(csv.starts_with(CSV_HEADER));
+        assert_eq!(parse_entries(&csv).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let entries = sample();
+        let json = export_entries(&entries, ExportFormat::Json).unwrap();
+        assert_eq!(parse_entries(&json).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_csv_quoted_fields() {
+        let mut entries = sample();
+        entries[0].test_mode = "a,\"b\"".to_string();
+        let csv = export_entries(&entries, ExportFormat::Csv).unwrap();
+        assert_eq!(parse_entries(&csv).unwrap()[0].test_mode, "a,\"b\"");
+    }
+
+    #[test]
+    fn test_csv_legacy_header() {
+        let csv = format!("{}\n2025-09-11T08:00:00Z,70,96,time:30,time,40,30,English\n", LEGACY_CSV_HEADER);
+        let entries = parse_entries(&csv).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].flag, None);
+    }
+
+    #[test]
+    fn test_csv_rejects_bad_rows() {
+        let csv = format!("{}\n2025-09-11T08:00:00Z,fast,96,time:30,time,40,30,English,\n", CSV_HEADER);
+        assert!(matches!(parse_entries(&csv), Err(ExportError::InvalidCsv(_))));
+        assert!(matches!(parse_entries("wpm,acc\n1,2\n"), Err(ExportError::InvalidCsv(_))));
+    }
+
+    #[test]
+    fn test_filter_since() {
+        let since = parse_since("2025-09-12").unwrap();
+        let filtered = filter_since(sample(), Some(since));
+        assert_eq!(filtered.len(), 3);
+        assert!(parse_since("12/09/2025").is_err());
+
+        // Nothing that is already the 12th in its own time zone is older than the storage cut-off
+        let from = chrono::DateTime::parse_from_rfc3339(&earliest_moment(since)).unwrap();
+        let kiribati = chrono::DateTime::parse_from_rfc3339("2025-09-12T00:00:00+14:00").unwrap();
+        let utc = chrono::DateTime::parse_from_rfc3339("2025-09-12T00:00:00Z").unwrap();
+        assert_eq!(kiribati, from);
+        assert!(utc > from);
+    }
+
+    #[test]
+    fn test_dedup_by_timestamp() {
+        let existing = sample()[..2].to_vec();
+        let mut incoming = sample();
+        incoming.push(entry("2025-09-14T12:00:00Z", TestType::Time(60)));
+
+        let (new_entries, duplicates) = dedup_entries(&existing, incoming);
+        assert_eq!(new_entries.len(), 3);
+        assert_eq!(duplicates, 3);
+    }
+}
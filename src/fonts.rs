@@ -0,0 +1,157 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+
+/// Font files the GUI can load
+const FONT_EXTENSIONS: [&str; 2] = ["ttf", "otf"];
+/// How deep font directories are searched; distributions nest them by foundry
+const MAX_DEPTH: usize = 4;
+
+/// Text sizes offered in the GUI settings, relative to what fits the window
+pub const TEXT_SCALES: [f32; 8] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0];
+/// Line heights offered in the GUI settings, in multiples of the text height
+pub const LINE_SPACINGS: [f32; 6] = [1.2, 1.4, 1.6, 1.8, 2.0, 2.4];
+pub const DEFAULT_LINE_SPACING: f32 = 1.6;
+
+/// Font of the GUI text: one that comes with typeman or a TTF/OTF file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FontChoice {
+    #[default]
+    RobotoMono,
+    DejaVuSans,
+    File(PathBuf),
+}
+
+impl FontChoice {
+    /// The bundled fonts followed by the installed ones
+    pub fn all() -> Vec<FontChoice> {
+        let mut all = vec![FontChoice::RobotoMono, FontChoice::DejaVuSans];
+        all.extend(installed().iter().cloned().map(FontChoice::File));
+        all
+    }
+}
+
+impl Display for FontChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FontChoice::RobotoMono => write!(f, "Roboto Mono (bundled)"),
+            FontChoice::DejaVuSans => write!(f, "DejaVu Sans Condensed (bundled)"),
+            FontChoice::File(path) => match path.file_stem() {
+                Some(stem) => write!(f, "{}", stem.to_string_lossy()),
+                None => write!(f, "{}", path.display()),
+            },
+        }
+    }
+}
+
+/// The configured line spacing, kept readable
+pub fn line_spacing(config: &AppConfig) -> f32 {
+    if config.line_spacing.is_finite() {
+        config.line_spacing.clamp(1.0, 3.0)
+    } else {
+        DEFAULT_LINE_SPACING
+    }
+}
+
+/// Where fonts are installed on Linux, macOS and Windows
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/usr/share/fonts"),
+        PathBuf::from("/usr/local/share/fonts"),
+        PathBuf::from("/Library/Fonts"),
+        PathBuf::from("/System/Library/Fonts"),
+    ];
+    if let Some(windows) = std::env::var_os("WINDIR") {
+        dirs.push(PathBuf::from(windows).join("Fonts"));
+    }
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        dirs.push(PathBuf::from(local).join("Microsoft").join("Windows").join("Fonts"));
+    }
+    if let Some(data) = std::env::var_os("XDG_DATA_HOME") {
+        dirs.push(PathBuf::from(data).join("fonts"));
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        let home = PathBuf::from(home);
+        dirs.push(home.join(".local/share/fonts"));
+        dirs.push(home.join(".fonts"));
+        dirs.push(home.join("Library/Fonts"));
+    }
+    dirs
+}
+
+/// TTF and OTF files installed on this machine, by name. Looked up once,
+/// as the popup asks for them every frame.
+pub fn installed() -> &'static [PathBuf] {
+    static INSTALLED: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    INSTALLED.get_or_init(|| {
+        let mut fonts = Vec::new();
+        for dir in font_dirs() {
+            find_fonts(&dir, MAX_DEPTH, &mut fonts);
+        }
+        fonts.sort_by_key(|path| path.file_name().map(|name| name.to_ascii_lowercase()));
+        fonts.dedup_by(|a, b| a.file_name() == b.file_name());
+        fonts
+    })
+}
+
+fn find_fonts(dir: &Path, depth: usize, fonts: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            if depth > 0 {
+                find_fonts(&path, depth - 1, fonts);
+            }
+        } else if is_font_file(&path) {
+            fonts.push(path);
+        }
+    }
+}
+
+pub fn is_font_file(path: &Path) -> bool {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    FONT_EXTENSIONS.contains(&ext.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fonts_are_found_in_subdirectories() {
+        let dir = std::env::temp_dir().join(format!("typeman-fonts-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("truetype/jetbrains")).unwrap();
+        fs::write(dir.join("FiraCode.OTF"), "").unwrap();
+        fs::write(dir.join("truetype/jetbrains/JetBrainsMono.ttf"), "").unwrap();
+        fs::write(dir.join("fonts.dir"), "").unwrap();
+
+        let mut fonts = Vec::new();
+        find_fonts(&dir, MAX_DEPTH, &mut fonts);
+        fonts.sort();
+        assert_eq!(fonts, [dir.join("FiraCode.OTF"), dir.join("truetype/jetbrains/JetBrainsMono.ttf")]);
+
+        let mut shallow = Vec::new();
+        find_fonts(&dir, 0, &mut shallow);
+        assert_eq!(shallow, [dir.join("FiraCode.OTF")]);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_font_choice_in_config() {
+        let file = FontChoice::File(PathBuf::from("/usr/share/fonts/Hack-Regular.ttf"));
+        assert_eq!(serde_json::to_string(&FontChoice::DejaVuSans).unwrap(), r#""deja_vu_sans""#);
+        assert_eq!(serde_json::from_str::<FontChoice>(&serde_json::to_string(&file).unwrap()).unwrap(), file);
+        assert_eq!(file.to_string(), "Hack-Regular");
+
+        let config = AppConfig { line_spacing: 0.2, ..AppConfig::default() };
+        assert_eq!(line_spacing(&config), 1.0);
+        assert_eq!(line_spacing(&AppConfig::default()), DEFAULT_LINE_SPACING);
+    }
+}
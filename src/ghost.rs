@@ -0,0 +1,310 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::language::Language;
+use crate::leaderboard::LeaderboardError;
+use crate::storage::json::{atomic_write, load_entries_from_file};
+
+/// Recorded runs of the active profile, newest last
+pub const GHOST_FILE: &str = "ghosts.json";
+/// Runs kept per profile; older ones are dropped
+const MAX_GHOSTS: usize = 20;
+
+#[derive(Debug)]
+pub enum GhostError {
+    IoError(std::io::Error),
+    SerializationError(serde_json::Error),
+    LeaderboardError(LeaderboardError),
+    InvalidRun(String),
+    NotFound(usize),
+}
+
+impl std::fmt::Display for GhostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GhostError::IoError(e) => write!(f, "{}", e),
+            GhostError::SerializationError(e) => write!(f, "invalid JSON: {}", e),
+            GhostError::LeaderboardError(e) => write!(f, "{}", e),
+            GhostError::InvalidRun(reason) => write!(f, "invalid run: {}", reason),
+            GhostError::NotFound(id) => write!(f, "no saved run with ID {}, 'typeman ghost list' shows them", id),
+        }
+    }
+}
+
+impl From<std::io::Error> for GhostError {
+    fn from(err: std::io::Error) -> Self {
+        GhostError::IoError(err)
+    }
+}
+
+impl From<serde_json::Error> for GhostError {
+    fn from(err: serde_json::Error) -> Self {
+        GhostError::SerializationError(err)
+    }
+}
+
+impl From<LeaderboardError> for GhostError {
+    fn from(err: LeaderboardError) -> Self {
+        GhostError::LeaderboardError(err)
+    }
+}
+
+/// A key press of a recorded run: when it happened and where it left the cursor
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Keystroke {
+    pub ms: f64,
+    pub pos: usize,
+}
+
+/// A finished run that can be raced again on the same text
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GhostRun {
+    pub player: String,
+    pub timestamp: String,
+    pub wpm: f64,
+    pub language: Language,
+    pub reference: String,
+    pub keystrokes: Vec<Keystroke>,
+}
+
+impl GhostRun {
+    /// Builds a run from a finished test, cut after the last word that was
+    /// fully typed so a ghost never races on text it didn't get through.
+    /// `None` if not even one word was finished.
+    pub fn new(player: String, wpm: f64, language: Language, reference: &str, keystrokes: &[Keystroke]) -> Option<GhostRun> {
+        let chars: Vec<char> = reference.chars().collect();
+        let times = word_times(reference, keystrokes);
+        let end = *word_ends(&chars).get(times.len().checked_sub(1)?)? + 1;
+        let last_ms = times.last()? * 1000.0;
+
+        Some(GhostRun {
+            player,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            wpm,
+            language,
+            reference: chars[..end].iter().collect(),
+            keystrokes: keystrokes
+                .iter()
+                .filter(|k| k.ms <= last_ms)
+                .map(|k| Keystroke { ms: k.ms, pos: k.pos.min(end) })
+                .collect(),
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), GhostError> {
+        let len = self.reference.chars().count();
+        if len == 0 || self.keystrokes.is_empty() {
+            return Err(GhostError::InvalidRun("run has no text or no keystrokes".to_string()));
+        }
+        if self.keystrokes.windows(2).any(|w| w[1].ms < w[0].ms) {
+            return Err(GhostError::InvalidRun("keystrokes are out of order".to_string()));
+        }
+        if self.keystrokes.iter().any(|k| k.pos > len || !k.ms.is_finite() || k.ms < 0.0) {
+            return Err(GhostError::InvalidRun("keystroke outside of the text".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Where the ghost's cursor is `elapsed` into the race
+    pub fn position_at(&self, elapsed: Duration) -> usize {
+        position_at(&self.keystrokes, elapsed)
+    }
+
+    pub fn duration(&self) -> Duration {
+        let ms = self.keystrokes.last().map(|k| k.ms).unwrap_or(0.0);
+        Duration::from_secs_f64(ms / 1000.0)
+    }
+
+    /// Seconds at which the ghost finished each word
+    pub fn word_times(&self) -> Vec<f64> {
+        word_times(&self.reference, &self.keystrokes)
+    }
+
+    /// Chars per minute for every second of the run, in the same shape as the
+    /// `speed_per_second` the apps record, so both fit on one chart
+    pub fn speed_per_second(&self) -> Vec<f64> {
+        let seconds = self.duration().as_secs_f64().ceil() as u64;
+        (0..seconds)
+            .map(|s| {
+                let before = self.position_at(Duration::from_secs(s));
+                let after = self.position_at(Duration::from_secs(s + 1));
+                after.saturating_sub(before) as f64 * 60.0
+            })
+            .collect()
+    }
+}
+
+pub fn position_at(keystrokes: &[Keystroke], elapsed: Duration) -> usize {
+    let ms = elapsed.as_secs_f64() * 1000.0;
+    match keystrokes.partition_point(|k| k.ms <= ms) {
+        0 => 0,
+        i => keystrokes[i - 1].pos,
+    }
+}
+
+/// Seconds at which the cursor first got past the end of each word.
+/// Stops at the first word that was never finished.
+pub fn word_times(reference: &str, keystrokes: &[Keystroke]) -> Vec<f64> {
+    let chars: Vec<char> = reference.chars().collect();
+    let mut times = Vec::new();
+    for end in word_ends(&chars) {
+        match keystrokes.iter().find(|k| k.pos > end) {
+            Some(k) => times.push(k.ms / 1000.0),
+            None => break,
+        }
+    }
+    times
+}
+
+/// Index of the last char of every word
+fn word_ends(chars: &[char]) -> Vec<usize> {
+    (0..chars.len())
+        .filter(|&i| chars[i] != ' ' && chars.get(i + 1).is_none_or(|&c| c == ' '))
+        .collect()
+}
+
+/// Seconds each word was finished behind (+) or ahead of (-) the ghost
+pub fn word_deltas(times: &[f64], ghost_times: &[f64]) -> Vec<f64> {
+    times.iter().zip(ghost_times).map(|(t, g)| t - g).collect()
+}
+
+pub fn ghost_path(dir: &Path) -> PathBuf {
+    dir.join(GHOST_FILE)
+}
+
+pub fn load(dir: &Path) -> Result<Vec<GhostRun>, GhostError> {
+    let path = ghost_path(dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(load_entries_from_file(&path)?)
+}
+
+/// Keeps a run for racing later, dropping the oldest once there are too many
+pub fn save(dir: &Path, run: GhostRun) -> Result<(), GhostError> {
+    run.validate()?;
+    let mut runs = load(dir)?;
+    runs.push(run);
+    if runs.len() > MAX_GHOSTS {
+        runs.drain(..runs.len() - MAX_GHOSTS);
+    }
+    Ok(atomic_write(&ghost_path(dir), &runs)?)
+}
+
+/// Run number `id` as shown by `typeman ghost list` (1 is the oldest)
+pub fn get(dir: &Path, id: usize) -> Result<GhostRun, GhostError> {
+    let runs = load(dir)?;
+    id.checked_sub(1)
+        .and_then(|i| runs.get(i).cloned())
+        .ok_or(GhostError::NotFound(id))
+}
+
+/// Adds a run exported by someone else (e.g. a teammate)
+pub fn import(dir: &Path, file: &Path) -> Result<GhostRun, GhostError> {
+    let run: GhostRun = serde_json::from_str(&fs::read_to_string(file)?)?;
+    save(dir, run.clone())?;
+    Ok(run)
+}
+
+pub fn export(run: &GhostRun, file: &Path) -> Result<(), GhostError> {
+    Ok(fs::write(file, serde_json::to_string_pretty(run)?)?)
+}
+
+/// Runs of the active profile
+pub fn load_saved() -> Vec<GhostRun> {
+    match crate::profile::current_data_dir() {
+        Ok(dir) => load(&dir).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Records a finished run of the active profile
+pub fn record(run: GhostRun) {
+    let result = crate::profile::current_data_dir()
+        .map_err(|e| GhostError::InvalidRun(e.to_string()))
+        .and_then(|dir| save(&dir, run));
+    if let Err(e) = result {
+        eprintln!("Failed to record run for ghost races: {}", e);
+    }
+}
+
+/// Name runs are recorded under: the team/server name if set, else the profile
+pub fn player_name() -> String {
+    crate::config::AppConfig::load()
+        .player_name
+        .unwrap_or_else(crate::profile::active)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(ms: f64, pos: usize) -> Keystroke {
+        Keystroke { ms, pos }
+    }
+
+    fn run() -> GhostRun {
+        GhostRun {
+            player: "ana".to_string(),
+            timestamp: "2025-09-11T10:00:00Z".to_string(),
+            wpm: 60.0,
+            language: Language::English,
+            reference: "ab cd".to_string(),
+            // a typo on "c" fixed with backspace
+            keystrokes: vec![key(100.0, 1), key(300.0, 2), key(900.0, 3), key(1200.0, 4), key(1300.0, 3), key(1500.0, 4), key(2100.0, 5)],
+        }
+    }
+
+    #[test]
+    fn test_ghost_replays_recorded_positions() {
+        let run = run();
+        assert!(run.validate().is_ok());
+        assert_eq!(run.position_at(Duration::ZERO), 0);
+        assert_eq!(run.position_at(Duration::from_millis(1250)), 4);
+        assert_eq!(run.position_at(Duration::from_millis(1350)), 3);
+        assert_eq!(run.position_at(Duration::from_secs(10)), 5);
+        assert_eq!(run.word_times(), [0.3, 2.1]);
+        assert_eq!(run.speed_per_second(), [180.0, 60.0, 60.0]);
+
+        let deltas = word_deltas(&[0.5, 1.6], &run.word_times());
+        assert!((deltas[0] - 0.2).abs() < 1e-9 && (deltas[1] + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_new_cuts_unfinished_words() {
+        // Time ran out halfway through "cd"
+        let keystrokes = [key(100.0, 1), key(300.0, 2), key(900.0, 3), key(1000.0, 4)];
+        let run = GhostRun::new("ana".into(), 60.0, Language::English, "ab cd ef", &keystrokes).unwrap();
+        assert_eq!(run.reference, "ab");
+        assert_eq!(run.keystrokes, [key(100.0, 1), key(300.0, 2)]);
+        assert!(run.validate().is_ok());
+
+        assert!(GhostRun::new("ana".into(), 0.0, Language::English, "ab", &[key(100.0, 1)]).is_none());
+    }
+
+    #[test]
+    fn test_save_keeps_newest_runs() {
+        let dir = std::env::temp_dir().join(format!("typeman-ghost-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        for i in 0..MAX_GHOSTS + 2 {
+            let mut run = run();
+            run.wpm = i as f64;
+            save(&dir, run).unwrap();
+        }
+        let runs = load(&dir).unwrap();
+        assert_eq!(runs.len(), MAX_GHOSTS);
+        assert_eq!(runs[0].wpm, 2.0);
+        assert_eq!(get(&dir, MAX_GHOSTS).unwrap().wpm, (MAX_GHOSTS + 1) as f64);
+        assert!(matches!(get(&dir, 0), Err(GhostError::NotFound(0))));
+
+        let mut broken = run();
+        broken.keystrokes[0].pos = 99;
+        assert!(matches!(save(&dir, broken), Err(GhostError::InvalidRun(_))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::language::Language;
+use crate::leaderboard::{self, LeaderboardEntry, TestType};
+
+/// Targets the user set for themselves; each one is optional
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Goals {
+    #[serde(default)]
+    pub minutes_per_day: Option<u32>,
+    #[serde(default)]
+    pub tests_per_day: Option<u32>,
+    #[serde(default)]
+    pub target_wpm: Option<WpmGoal>,
+}
+
+impl Goals {
+    pub fn is_empty(&self) -> bool {
+        self.minutes_per_day.is_none() && self.tests_per_day.is_none() && self.target_wpm.is_none()
+    }
+}
+
+/// Speed to reach on one kind of test, e.g. 80 WPM on 60s English
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WpmGoal {
+    pub wpm: u32,
+    pub test_type: TestType,
+    pub language: Language,
+}
+
+/// How far along one goal is
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalProgress {
+    pub kind: GoalKind,
+    pub done: f64,
+    pub target: f64,
+    /// Days in a row the goal was met, for the daily goals
+    pub streak: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GoalKind {
+    Minutes,
+    Tests,
+    Wpm(TestType, Language),
+}
+
+impl GoalProgress {
+    pub fn complete(&self) -> bool {
+        self.done >= self.target
+    }
+}
+
+impl Display for GoalProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            GoalKind::Minutes => write!(f, "{:.0}/{:.0} min today", self.done.floor(), self.target)?,
+            GoalKind::Tests => write!(f, "{:.0}/{:.0} tests today", self.done, self.target)?,
+            GoalKind::Wpm(test_type, language) => write!(
+                f,
+                "best {:.0}/{:.0} WPM on {} {}",
+                self.done,
+                self.target,
+                describe(test_type),
+                language
+            )?,
+        }
+        if self.complete() {
+            write!(f, " ✓")?;
+        }
+        match self.streak {
+            Some(days) if days > 0 => write!(f, " (streak {})", days),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Short name of a kind of test, e.g. "60s" or "50 words"
+pub fn describe(test_type: &TestType) -> String {
+    match test_type {
+        TestType::Time(secs) => format!("{}s", secs),
+        TestType::Word(words) => format!("{} words", words),
+        TestType::Quote => "quotes".to_string(),
+        TestType::Practice(level) => format!("practice level {}", level),
+        TestType::Wiki => "wikipedia".to_string(),
+        TestType::Race(_) => "races".to_string(),
+        TestType::Daily(_) => "dailies".to_string(),
+    }
+}
+
+/// Local day a result was finished on
+pub fn entry_day(entry: &LeaderboardEntry) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(&entry.timestamp)
+        .ok()
+        .map(|time| time.with_timezone(&Local).date_naive())
+}
+
+/// Minutes typed and tests finished per day
+fn day_totals(history: &[LeaderboardEntry]) -> HashMap<NaiveDate, (f64, usize)> {
+    let mut totals: HashMap<NaiveDate, (f64, usize)> = HashMap::new();
+    for entry in history {
+        if let Some(day) = entry_day(entry) {
+            let total = totals.entry(day).or_default();
+            total.0 += entry.test_duration / 60.0;
+            total.1 += 1;
+        }
+    }
+    totals
+}
+
+/// Days in a row `met` holds, up to today. Today not being done yet doesn't
+/// break the streak.
+fn streak(today: NaiveDate, met: impl Fn(NaiveDate) -> bool) -> usize {
+    let mut day = if met(today) { today } else { today - Duration::days(1) };
+    let mut streak = 0;
+    while met(day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+pub fn progress(goals: &Goals, history: &[LeaderboardEntry], today: NaiveDate) -> Vec<GoalProgress> {
+    let totals = day_totals(history);
+    let on = |day: NaiveDate| totals.get(&day).copied().unwrap_or_default();
+    let mut progress = Vec::new();
+
+    if let Some(minutes) = goals.minutes_per_day {
+        let target = minutes as f64;
+        progress.push(GoalProgress {
+            kind: GoalKind::Minutes,
+            done: on(today).0,
+            target,
+            streak: Some(streak(today, |day| on(day).0 >= target)),
+        });
+    }
+    if let Some(tests) = goals.tests_per_day {
+        let target = tests as usize;
+        progress.push(GoalProgress {
+            kind: GoalKind::Tests,
+            done: on(today).1 as f64,
+            target: tests as f64,
+            streak: Some(streak(today, |day| on(day).1 >= target)),
+        });
+    }
+    if let Some(goal) = &goals.target_wpm {
+        let best = history
+            .iter()
+            .filter(|e| e.test_type == goal.test_type && e.language == goal.language && e.flag.is_none())
+            .map(|e| e.wpm)
+            .fold(0.0, f64::max);
+        progress.push(GoalProgress {
+            kind: GoalKind::Wpm(goal.test_type.clone(), goal.language),
+            done: best,
+            target: goal.wpm as f64,
+            streak: None,
+        });
+    }
+    progress
+}
+
+/// One line for the start and results screens, `None` without goals
+pub fn summary(goals: &Goals, history: &[LeaderboardEntry], today: NaiveDate) -> Option<String> {
+    if goals.is_empty() {
+        return None;
+    }
+    let parts: Vec<String> = progress(goals, history, today).iter().map(|p| p.to_string()).collect();
+    Some(format!("Goals: {}", parts.join(" · ")))
+}
+
+/// `summary` for today, read from the stored history
+pub fn today_summary(goals: &Goals) -> Option<String> {
+    if goals.is_empty() {
+        return None;
+    }
+    let history = leaderboard::load_history(None).unwrap_or_default();
+    summary(goals, &history, Local::now().date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u32, seconds: f64, wpm: f64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            wpm,
+            accuracy: 95.0,
+            test_type: TestType::Time(60),
+            test_mode: "time".to_string(),
+            word_count: 40,
+            test_duration: seconds,
+            // Midday, so the local day is the same in any timezone
+            timestamp: format!("2025-09-{:02}T12:00:00+00:00", day),
+            language: Language::English,
+            flag: None,
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).unwrap()
+    }
+
+    #[test]
+    fn test_daily_goals_and_streaks() {
+        let goals = Goals { minutes_per_day: Some(2), tests_per_day: Some(2), target_wpm: None };
+        let history = vec![
+            entry(9, 60.0, 50.0),
+            entry(9, 60.0, 50.0),
+            entry(10, 120.0, 50.0),
+            entry(11, 60.0, 50.0),
+        ];
+
+        let progress = progress(&goals, &history, date(11));
+        assert_eq!(progress[0].done, 1.0);
+        assert!(!progress[0].complete());
+        // Not done today yet, but the two days before count
+        assert_eq!(progress[0].streak, Some(2));
+        assert_eq!(progress[1].done, 1.0);
+        assert_eq!(progress[1].streak, Some(0));
+        assert_eq!(progress[0].to_string(), "1/2 min today (streak 2)");
+    }
+
+    #[test]
+    fn test_wpm_goal_uses_best_matching_result() {
+        let goal = WpmGoal { wpm: 80, test_type: TestType::Time(60), language: Language::English };
+        let goals = Goals { target_wpm: Some(goal), ..Goals::default() };
+        let mut other = entry(10, 30.0, 120.0);
+        other.test_type = TestType::Time(30);
+        let history = vec![entry(9, 60.0, 72.0), entry(10, 60.0, 81.0), other];
+
+        let progress = progress(&goals, &history, date(11));
+        assert_eq!(progress.len(), 1);
+        assert!(progress[0].complete());
+        assert_eq!(
+            summary(&goals, &history, date(11)).unwrap(),
+            "Goals: best 81/80 WPM on 60s English ✓"
+        );
+        assert_eq!(summary(&Goals::default(), &history, date(11)), None);
+    }
+
+    #[test]
+    fn test_daily_goals_count_results_off_the_leaderboard() {
+        use crate::storage::backend::Storage;
+        use crate::storage::json::JsonStorage;
+
+        let dir = std::env::temp_dir().join(format!("typeman-goals-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let storage = JsonStorage::new(dir.clone(), dir.join("practice_results"));
+
+        // 120 one-minute tests on one day; the slowest 20 aren't on the leaderboard
+        let results: Vec<LeaderboardEntry> = (0..120)
+            .map(|i| LeaderboardEntry {
+                timestamp: format!("2025-09-11T12:{:02}:{:02}+00:00", i / 60, i % 60),
+                ..entry(11, 60.0, 30.0 + i as f64)
+            })
+            .collect();
+        storage.save_entries(&results).unwrap();
+
+        let goals = Goals { minutes_per_day: Some(120), tests_per_day: Some(120), target_wpm: None };
+        let progress = progress(&goals, &storage.load_history(None).unwrap(), date(11));
+        assert_eq!(progress[0].done, 120.0);
+        assert!(progress[1].complete());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,206 @@
+use std::time::Instant;
+
+use crate::race::RaceTyping;
+use crate::team;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+
+#[derive(Debug)]
+pub enum HotseatError {
+    PlayerCount(usize),
+    InvalidName(String),
+    DuplicateName(String),
+}
+
+impl std::fmt::Display for HotseatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HotseatError::PlayerCount(count) => {
+                write!(f, "{} players, a hotseat game needs {} to {}", count, MIN_PLAYERS, MAX_PLAYERS)
+            }
+            HotseatError::InvalidName(name) => write!(f, "invalid player name {:?}", name),
+            HotseatError::DuplicateName(name) => write!(f, "{} is playing already", name),
+        }
+    }
+}
+
+/// How one player did on their turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnResult {
+    pub name: String,
+    pub wpm: f64,
+    pub accuracy: f64,
+    pub time: f64,
+    pub speed_per_second: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HotseatPhase {
+    /// Waiting for the next player to take the keyboard
+    Handoff,
+    Typing,
+    Done,
+}
+
+/// Players taking turns on the same text on one machine. The clock of each
+/// turn starts at the player's first keystroke, so handing over takes no time.
+pub struct HotseatGame {
+    pub players: Vec<String>,
+    pub text: String,
+    pub turn: usize,
+    pub phase: HotseatPhase,
+    pub typing: Option<RaceTyping>,
+    pub results: Vec<TurnResult>,
+}
+
+impl HotseatGame {
+    pub fn new(players: Vec<String>, text: String) -> Result<Self, HotseatError> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players.len()) {
+            return Err(HotseatError::PlayerCount(players.len()));
+        }
+        let players: Vec<String> = players.iter().map(|p| p.trim().to_string()).collect();
+        for (i, name) in players.iter().enumerate() {
+            if team::validate_player_name(name).is_err() {
+                return Err(HotseatError::InvalidName(name.clone()));
+            }
+            if players[..i].iter().any(|p| p.eq_ignore_ascii_case(name)) {
+                return Err(HotseatError::DuplicateName(name.clone()));
+            }
+        }
+        Ok(HotseatGame {
+            players,
+            text,
+            turn: 0,
+            phase: HotseatPhase::Handoff,
+            typing: None,
+            results: Vec::new(),
+        })
+    }
+
+    /// Player whose turn it is (or is next), `None` once everyone has typed
+    pub fn current_player(&self) -> Option<&str> {
+        self.players.get(self.turn).map(String::as_str)
+    }
+
+    /// The handoff was confirmed, the current player may start typing
+    pub fn begin_turn(&mut self) {
+        if self.phase == HotseatPhase::Handoff {
+            self.phase = HotseatPhase::Typing;
+            self.typing = None;
+        }
+    }
+
+    /// The turn ends once the text is typed correctly, like a race
+    pub fn type_char(&mut self, c: char) {
+        if self.phase != HotseatPhase::Typing {
+            return;
+        }
+        let typing = self.typing.get_or_insert_with(|| RaceTyping::new(&self.text, Instant::now()));
+        typing.type_char(c);
+        if typing.is_finished() {
+            self.finish_turn();
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(typing) = self.typing.as_mut() {
+            typing.backspace();
+        }
+    }
+
+    /// Scores the current turn and moves on to the next player's handoff
+    fn finish_turn(&mut self) {
+        let (Some(typing), Some(name)) = (self.typing.take(), self.current_player()) else {
+            return;
+        };
+        self.results.push(TurnResult {
+            name: name.to_string(),
+            wpm: typing.wpm(),
+            accuracy: typing.accuracy(),
+            time: typing.elapsed().as_secs_f64(),
+            speed_per_second: typing.speed_per_second(),
+        });
+        self.turn += 1;
+        self.phase = if self.turn >= self.players.len() {
+            HotseatPhase::Done
+        } else {
+            HotseatPhase::Handoff
+        };
+    }
+
+    /// Results so far, fastest first
+    pub fn standings(&self) -> Vec<&TurnResult> {
+        let mut standings: Vec<&TurnResult> = self.results.iter().collect();
+        standings.sort_by(|a, b| b.wpm.total_cmp(&a.wpm));
+        standings
+    }
+}
+
+/// Distinct line colors for the players on the comparison chart
+pub fn player_color(index: usize) -> (u8, u8, u8) {
+    const COLORS: [(u8, u8, u8); MAX_PLAYERS] = [
+        (230, 159, 0),
+        (86, 180, 233),
+        (0, 158, 115),
+        (240, 228, 66),
+        (204, 121, 167),
+        (213, 94, 0),
+        (0, 114, 178),
+        (200, 200, 200),
+    ];
+    COLORS[index % MAX_PLAYERS]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_new_validates_players() {
+        let text = "ab cd".to_string();
+        assert!(matches!(HotseatGame::new(names(&["ana"]), text.clone()), Err(HotseatError::PlayerCount(1))));
+        assert!(matches!(HotseatGame::new(names(&["a"; 9]), text.clone()), Err(HotseatError::PlayerCount(9))));
+        assert!(matches!(HotseatGame::new(names(&["ana", " "]), text.clone()), Err(HotseatError::InvalidName(_))));
+        assert!(matches!(
+            HotseatGame::new(names(&["ana", "bo", "Ana"]), text.clone()),
+            Err(HotseatError::DuplicateName(n)) if n == "Ana"
+        ));
+        assert!(HotseatGame::new(names(&["ana", "bo"]), text).is_ok());
+    }
+
+    #[test]
+    fn test_turns_hand_over_until_everyone_typed() {
+        let mut game = HotseatGame::new(names(&["ana", "bo"]), "ab".to_string()).unwrap();
+        // keys pressed on the handoff screen don't count
+        game.type_char('a');
+        assert!(game.typing.is_none());
+
+        game.begin_turn();
+        game.type_char('a');
+        game.type_char('x');
+        // a wrong text isn't done yet, the mistake has to be fixed first
+        assert_eq!(game.phase, HotseatPhase::Typing);
+        assert_eq!(game.current_player(), Some("ana"));
+        game.backspace();
+        game.type_char('b');
+        assert_eq!(game.phase, HotseatPhase::Handoff);
+        assert_eq!(game.current_player(), Some("bo"));
+
+        game.begin_turn();
+        game.type_char('a');
+        game.type_char('b');
+        assert_eq!(game.phase, HotseatPhase::Done);
+        assert_eq!(game.current_player(), None);
+
+        assert_eq!(game.results[0].name, "ana");
+        assert!((game.results[0].accuracy - 66.7).abs() < 0.1);
+        assert_eq!(game.results[1].accuracy, 100.0);
+        let standings = game.standings();
+        assert!(standings[0].wpm >= standings[1].wpm);
+    }
+}
@@ -0,0 +1,536 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// How long after Tab the second key of a "Tab+X" binding may come
+pub const LEADER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Something a key binding does, the same in every frontend
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Starts over; ghost races and daily challenges keep their text
+    Restart,
+    /// Starts over with a new text, leaving a ghost race or daily challenge
+    NewText,
+    /// Starts over on the same text
+    RepeatText,
+    Quit,
+    Settings,
+    /// Searchable list of every action and setting (TUI)
+    CommandPalette,
+    Leaderboard,
+    Practice,
+    Achievements,
+    NextPreset,
+    Daily,
+    GhostRace,
+    ModeTime,
+    ModeWords,
+    ModeQuote,
+    ModeWiki,
+    /// Scales the text up or down (GUI)
+    TextLarger,
+    TextSmaller,
+}
+
+impl Action {
+    /// In the order conflicts are settled: the earlier action keeps the key
+    pub fn all() -> &'static [Action] {
+        &[
+            Action::Quit,
+            Action::Restart,
+            Action::NewText,
+            Action::RepeatText,
+            Action::Settings,
+            Action::CommandPalette,
+            Action::Leaderboard,
+            Action::Practice,
+            Action::Achievements,
+            Action::NextPreset,
+            Action::Daily,
+            Action::GhostRace,
+            Action::ModeTime,
+            Action::ModeWords,
+            Action::ModeQuote,
+            Action::ModeWiki,
+            Action::TextLarger,
+            Action::TextSmaller,
+        ]
+    }
+
+    fn default_bindings(&self) -> &'static [&'static str] {
+        match self {
+            Action::Restart => &["Tab+Enter"],
+            Action::NewText => &["Tab+N"],
+            Action::RepeatText => &["Tab+R"],
+            Action::Quit => &["Esc"],
+            Action::Settings => &["Tab+S"],
+            Action::CommandPalette => &["Ctrl+P"],
+            Action::Leaderboard => &["Tab+L"],
+            Action::Practice => &["Tab+K"],
+            Action::Achievements => &["Tab+A"],
+            Action::NextPreset => &["Tab+P"],
+            Action::Daily => &["Tab+D"],
+            Action::GhostRace => &["Tab+G"],
+            Action::ModeTime => &["Tab+T"],
+            Action::ModeWords => &["Tab+W"],
+            Action::ModeQuote => &["Tab+Q"],
+            Action::ModeWiki => &["Tab+I"],
+            Action::TextLarger => &["Tab+Up"],
+            Action::TextSmaller => &["Tab+Down"],
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Restart => write!(f, "restart"),
+            Action::NewText => write!(f, "new text"),
+            Action::RepeatText => write!(f, "repeat text"),
+            Action::Quit => write!(f, "quit"),
+            Action::Settings => write!(f, "settings"),
+            Action::CommandPalette => write!(f, "command palette"),
+            Action::Leaderboard => write!(f, "leaderboard"),
+            Action::Practice => write!(f, "practice"),
+            Action::Achievements => write!(f, "achievements"),
+            Action::NextPreset => write!(f, "next preset"),
+            Action::Daily => write!(f, "daily challenge"),
+            Action::GhostRace => write!(f, "race as a ghost"),
+            Action::ModeTime => write!(f, "time mode"),
+            Action::ModeWords => write!(f, "words mode"),
+            Action::ModeQuote => write!(f, "quote mode"),
+            Action::ModeWiki => write!(f, "wiki mode"),
+            Action::TextLarger => write!(f, "larger text"),
+            Action::TextSmaller => write!(f, "smaller text"),
+        }
+    }
+}
+
+/// A key, apart from its modifiers; letters are kept lowercase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Esc,
+    Tab,
+    Space,
+    Backspace,
+    Up,
+    Down,
+    Left,
+    Right,
+    F(u8),
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Key::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            Key::Enter => write!(f, "Enter"),
+            Key::Esc => write!(f, "Esc"),
+            Key::Tab => write!(f, "Tab"),
+            Key::Space => write!(f, "Space"),
+            Key::Backspace => write!(f, "Backspace"),
+            Key::Up => write!(f, "Up"),
+            Key::Down => write!(f, "Down"),
+            Key::Left => write!(f, "Left"),
+            Key::Right => write!(f, "Right"),
+            Key::F(n) => write!(f, "F{}", n),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = BindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let mut chars = lower.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(if c == ' ' { Key::Space } else { Key::Char(c) });
+        }
+        Ok(match lower.as_str() {
+            "enter" | "return" => Key::Enter,
+            "esc" | "escape" => Key::Esc,
+            "tab" => Key::Tab,
+            "space" => Key::Space,
+            "backspace" => Key::Backspace,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            f => match f.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => Key::F(n),
+                _ => return Err(BindingError::UnknownKey(s.trim().to_string())),
+            },
+        })
+    }
+}
+
+/// A key as a frontend saw it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress {
+    pub key: Key,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl KeyPress {
+    pub fn new(key: Key) -> Self {
+        KeyPress { key, ctrl: false, alt: false }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BindingError {
+    Empty,
+    UnknownKey(String),
+    /// Tab only works as the first key of a binding
+    MisplacedTab,
+    /// A key without Tab, Ctrl or Alt in front is needed for typing or moving around
+    Unmodified(String),
+}
+
+impl Display for BindingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingError::Empty => write!(f, "empty key binding"),
+            BindingError::UnknownKey(key) => write!(f, "unknown key {:?}", key),
+            BindingError::MisplacedTab => write!(f, "Tab can only come first, as in \"Tab+Enter\""),
+            BindingError::Unmodified(binding) => {
+                write!(f, "{:?} is needed for typing, start it with Tab+, Ctrl+ or Alt+", binding)
+            }
+        }
+    }
+}
+
+/// A key with its modifiers, written like "Esc", "Ctrl+R" or "Tab+Enter".
+/// "Tab+X" means Tab and then X within `LEADER_TIMEOUT`, since terminals
+/// don't report keys being held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub leader: bool,
+    pub press: KeyPress,
+}
+
+impl Binding {
+    fn matches(&self, leader: bool, press: &KeyPress) -> bool {
+        self.leader == leader && self.press == *press
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.leader {
+            write!(f, "Tab+")?;
+        }
+        if self.press.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.press.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.press.key)
+    }
+}
+
+impl FromStr for Binding {
+    type Err = BindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let Some((key, modifiers)) = parts.split_last() else {
+            return Err(BindingError::Empty);
+        };
+        if key.is_empty() {
+            return Err(BindingError::Empty);
+        }
+        let mut binding = Binding { leader: false, press: KeyPress::new(key.parse()?) };
+        for (i, modifier) in modifiers.iter().enumerate() {
+            match modifier.to_lowercase().as_str() {
+                "tab" if i == 0 => binding.leader = true,
+                "tab" => return Err(BindingError::MisplacedTab),
+                "ctrl" | "control" => binding.press.ctrl = true,
+                "alt" => binding.press.alt = true,
+                _ => return Err(BindingError::UnknownKey(modifier.to_string())),
+            }
+        }
+        let modified = binding.leader || binding.press.ctrl || binding.press.alt;
+        match binding.press.key {
+            Key::Tab if binding.leader || !modified => Err(BindingError::MisplacedTab),
+            Key::Esc | Key::F(_) => Ok(binding),
+            _ if !modified => Err(BindingError::Unmodified(s.trim().to_string())),
+            _ => Ok(binding),
+        }
+    }
+}
+
+impl Serialize for Binding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(|e: BindingError| serde::de::Error::custom(e.to_string()))
+    }
+}
+
+/// The same binding on two actions; the earlier one in `Action::all` keeps it
+#[derive(Debug, PartialEq)]
+pub struct KeyConflict {
+    pub binding: Binding,
+    pub kept: Action,
+    pub dropped: Action,
+}
+
+impl Display for KeyConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is bound to both {} and {}, only {} keeps it", self.binding, self.kept, self.dropped, self.kept)
+    }
+}
+
+/// Key bindings of every action. Actions left out of the config keep their
+/// default bindings; an empty list unbinds one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::all()
+            .iter()
+            .map(|&action| {
+                let defaults = action.default_bindings().iter().filter_map(|b| b.parse().ok()).collect();
+                (action, defaults)
+            })
+            .collect();
+        Keymap { bindings }
+    }
+}
+
+impl Serialize for Keymap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.bindings.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut keymap = Keymap::default();
+        keymap.bindings.extend(BTreeMap::<Action, Vec<Binding>>::deserialize(deserializer)?);
+        Ok(keymap)
+    }
+}
+
+impl Keymap {
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn set(&mut self, action: Action, bindings: Vec<Binding>) {
+        self.bindings.insert(action, bindings);
+    }
+
+    fn action_for(&self, leader: bool, press: &KeyPress) -> Option<Action> {
+        Action::all().iter().copied().find(|&action| self.bindings(action).iter().any(|b| b.matches(leader, press)))
+    }
+
+    /// The first binding of an action as shown in the hints, e.g. "Tab + Enter"
+    pub fn hint(&self, action: Action) -> String {
+        match self.bindings(action).first() {
+            Some(binding) => binding.to_string().replace('+', " + "),
+            None => "(unbound)".to_string(),
+        }
+    }
+
+    /// Removes bindings that an earlier action already has, returning what was removed
+    pub fn resolve_conflicts(&mut self) -> Vec<KeyConflict> {
+        let mut conflicts = Vec::new();
+        let mut taken: Vec<(Binding, Action)> = Vec::new();
+        for &action in Action::all() {
+            let Some(bindings) = self.bindings.get_mut(&action) else {
+                continue;
+            };
+            bindings.retain(|binding| match taken.iter().find(|(b, _)| b == binding) {
+                Some(&(_, kept)) if kept != action => {
+                    conflicts.push(KeyConflict { binding: *binding, kept, dropped: action });
+                    false
+                }
+                Some(_) => false,
+                None => {
+                    taken.push((*binding, action));
+                    true
+                }
+            });
+        }
+        conflicts
+    }
+}
+
+/// What a key press turned out to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dispatch {
+    Action(Action),
+    /// Tab, waiting for the second key of a binding
+    Leader,
+    /// Not bound; the frontend handles it as usual
+    Key(KeyPress),
+}
+
+/// Turns key presses into actions, remembering a pending Tab. Each frontend
+/// feeds it its key events and carries out the actions it returns.
+#[derive(Debug, Default)]
+pub struct KeyReader {
+    leader: Option<Instant>,
+}
+
+impl KeyReader {
+    pub fn read(&mut self, keymap: &Keymap, press: KeyPress) -> Dispatch {
+        self.read_at(keymap, press, Instant::now())
+    }
+
+    fn read_at(&mut self, keymap: &Keymap, press: KeyPress, now: Instant) -> Dispatch {
+        let leader = self.leader.take().is_some_and(|at| now.duration_since(at) < LEADER_TIMEOUT);
+        if leader {
+            if let Some(action) = keymap.action_for(true, &press) {
+                return Dispatch::Action(action);
+            }
+        }
+        if press == KeyPress::new(Key::Tab) {
+            self.leader = Some(now);
+            return Dispatch::Leader;
+        }
+        match keymap.action_for(false, &press) {
+            Some(action) => Dispatch::Action(action),
+            None => Dispatch::Key(press),
+        }
+    }
+
+    /// Forgets a pending Tab, e.g. when a popup takes over the keys
+    pub fn reset(&mut self) {
+        self.leader = None;
+    }
+}
+
+#[cfg(any(feature = "cli", feature = "tui"))]
+impl KeyPress {
+    /// `None` for keys no binding can use
+    pub fn from_crossterm(event: &crossterm::event::KeyEvent) -> Option<Self> {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        let key = match event.code {
+            KeyCode::Char(' ') => Key::Space,
+            KeyCode::Char(c) => Key::Char(c.to_ascii_lowercase()),
+            KeyCode::Enter => Key::Enter,
+            KeyCode::Esc => Key::Esc,
+            KeyCode::Tab => Key::Tab,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::F(n) => Key::F(n),
+            _ => return None,
+        };
+        Some(KeyPress {
+            key,
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+            alt: event.modifiers.contains(KeyModifiers::ALT),
+        })
+    }
+}
+
+/// The keys macroquad saw pressed this frame
+#[cfg(feature = "gui")]
+pub fn gui_key_presses() -> Vec<KeyPress> {
+    use macroquad::input::{KeyCode, get_keys_pressed, is_key_down};
+    let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+    let alt = is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt);
+    get_keys_pressed()
+        .into_iter()
+        .filter_map(|code| {
+            let key = match code {
+                KeyCode::Enter | KeyCode::KpEnter => Key::Enter,
+                KeyCode::Escape => Key::Esc,
+                KeyCode::Tab => Key::Tab,
+                KeyCode::Space => Key::Space,
+                KeyCode::Backspace => Key::Backspace,
+                KeyCode::Up => Key::Up,
+                KeyCode::Down => Key::Down,
+                KeyCode::Left => Key::Left,
+                KeyCode::Right => Key::Right,
+                code => {
+                    let name = format!("{:?}", code);
+                    match name.strip_prefix("Key").unwrap_or(&name) {
+                        n if n.len() == 1 => Key::Char(n.chars().next()?.to_ascii_lowercase()),
+                        n => n.parse().ok().filter(|key| matches!(key, Key::F(_)))?,
+                    }
+                }
+            };
+            Some(KeyPress { key, ctrl, alt })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(key: Key) -> KeyPress {
+        KeyPress::new(key)
+    }
+
+    #[test]
+    fn test_parse_bindings() {
+        for text in ["Tab+Enter", "Esc", "Ctrl+R", "Tab+Ctrl+Alt+F5", "F12", "Alt+Space"] {
+            assert_eq!(text.parse::<Binding>().unwrap().to_string(), text);
+        }
+        assert_eq!("tab + l".parse::<Binding>().unwrap().to_string(), "Tab+L");
+        assert_eq!("R".parse::<Binding>(), Err(BindingError::Unmodified("R".to_string())));
+        assert_eq!("Enter".parse::<Binding>(), Err(BindingError::Unmodified("Enter".to_string())));
+        assert_eq!("Ctrl+Tab+R".parse::<Binding>(), Err(BindingError::MisplacedTab));
+        assert_eq!("Tab".parse::<Binding>(), Err(BindingError::MisplacedTab));
+        assert_eq!("Ctrl+Nope".parse::<Binding>(), Err(BindingError::UnknownKey("Nope".to_string())));
+        assert_eq!("Ctrl+".parse::<Binding>(), Err(BindingError::Empty));
+    }
+
+    #[test]
+    fn test_config_and_conflicts() {
+        // Left-out actions keep their defaults
+        let mut keymap: Keymap = serde_json::from_str(r#"{"quit": ["Ctrl+Q", "Esc"], "new_text": ["Tab+L"]}"#).unwrap();
+        assert_eq!(keymap.hint(Action::Quit), "Ctrl + Q");
+        assert_eq!(keymap.hint(Action::Restart), "Tab + Enter");
+
+        let conflicts = keymap.resolve_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].to_string(), "Tab+L is bound to both new text and leaderboard, only new text keeps it");
+        assert!(keymap.bindings(Action::Leaderboard).is_empty());
+        assert_eq!(keymap.hint(Action::Leaderboard), "(unbound)");
+        assert!(Keymap::default().resolve_conflicts().is_empty());
+
+        assert!(serde_json::from_str::<Keymap>(r#"{"quit": ["Q"]}"#).is_err());
+    }
+
+    #[test]
+    fn test_reader_waits_for_the_second_key() {
+        let keymap = Keymap::default();
+        let mut reader = KeyReader::default();
+        let start = Instant::now();
+        assert_eq!(reader.read_at(&keymap, press(Key::Tab), start), Dispatch::Leader);
+        assert_eq!(reader.read_at(&keymap, press(Key::Enter), start), Dispatch::Action(Action::Restart));
+        // Without Tab, or too late, it's just a key
+        assert_eq!(reader.read_at(&keymap, press(Key::Enter), start), Dispatch::Key(press(Key::Enter)));
+        reader.read_at(&keymap, press(Key::Tab), start);
+        let late = start + LEADER_TIMEOUT;
+        assert_eq!(reader.read_at(&keymap, press(Key::Char('l')), late), Dispatch::Key(press(Key::Char('l'))));
+        assert_eq!(reader.read_at(&keymap, press(Key::Esc), late), Dispatch::Action(Action::Quit));
+    }
+}
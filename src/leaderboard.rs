@@ -1,58 +1,87 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use std::io::Write;
-use std::time::{Duration, Instant, SystemTime};
+use std::time::{Duration, Instant};
 use fs2::FileExt;
+use crate::anticheat::CheatFlag;
 use crate::language::Language;
-
-/// Cached leaderboard data with timestamp for invalidation
-#[derive(Debug, Clone)]
-struct LeaderboardCache {
-    entries: Vec<LeaderboardEntry>,
-    last_modified: SystemTime,
-    cached_at: Instant,
-}
+use crate::storage::backend::open_configured;
 
 pub struct LeaderboardData {
     pub open: bool,
     pub entries: Vec<LeaderboardEntry>,
     pub selected: usize,
+    pub view: LeaderboardView,
+    /// Profile or team player of each entry, empty for the local view
+    pub owners: Vec<String>,
 }
 
-impl LeaderboardCache {
-    /// Check if cache is still valid (less than 30 seconds old and file hasn't changed)
-    fn is_valid(&self, file_path: &PathBuf) -> bool {
-        // Cache expires after 30 seconds
-        if self.cached_at.elapsed() > Duration::from_secs(30) {
-            return false;
-        }
-        
-        // Check if file has been modified
-        if let Ok(metadata) = fs::metadata(file_path) {
-            if let Ok(modified) = metadata.modified() {
-                return modified <= self.last_modified;
-            }
+impl LeaderboardData {
+    pub fn new() -> Self {
+        LeaderboardData {
+            open: false,
+            entries: Vec::new(),
+            selected: 0,
+            view: LeaderboardView::default(),
+            owners: Vec::new(),
         }
-        
-        false
     }
-}
 
-use std::sync::{Mutex, OnceLock};
+    /// Reloads entries for the current view
+    pub fn reload(&mut self) {
+        let (owners, entries) = match self.view {
+            LeaderboardView::Local => (Vec::new(), load_entries().unwrap_or_default()),
+            LeaderboardView::AllProfiles => crate::profile::combined_entries().into_iter().unzip(),
+            LeaderboardView::Team => crate::team::configured()
+                .and_then(|(dir, _)| crate::team::load(&dir).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| (e.player, e.entry))
+                .unzip(),
+        };
+        self.owners = owners;
+        self.entries = entries;
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+    }
 
-/// Global cache for leaderboard data
-static LEADERBOARD_CACHE: OnceLock<Mutex<Option<LeaderboardCache>>> = OnceLock::new();
+    /// Switches to the next (or previous) tab and reloads it
+    pub fn cycle_view(&mut self, forward: bool) {
+        let views = LeaderboardView::all();
+        let current = views.iter().position(|v| *v == self.view).unwrap_or(0);
+        let next = if forward {
+            (current + 1) % views.len()
+        } else {
+            (current + views.len() - 1) % views.len()
+        };
+        self.view = views[next];
+        self.selected = 0;
+        self.reload();
+    }
+}
 
-/// Initialize the global cache
-fn get_cache() -> &'static Mutex<Option<LeaderboardCache>> {
-    LEADERBOARD_CACHE.get_or_init(|| Mutex::new(None))
+/// Tabs of the leaderboard screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LeaderboardView {
+    #[default]
+    Local,
+    AllProfiles,
+    Team,
+}
+
+impl LeaderboardView {
+    pub fn all() -> Vec<LeaderboardView> {
+        vec![LeaderboardView::Local, LeaderboardView::AllProfiles, LeaderboardView::Team]
+    }
 }
 
-/// Clear the cache (called after successful save operations)
-fn invalidate_cache() {
-    if let Ok(mut cache) = get_cache().lock() {
-        *cache = None;
+impl std::fmt::Display for LeaderboardView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LeaderboardView::Local => "Local",
+            LeaderboardView::AllProfiles => "All profiles",
+            LeaderboardView::Team => "Team",
+        };
+        write!(f, "{}", name)
     }
 }
 
@@ -66,6 +95,9 @@ pub struct LeaderboardEntry {
     pub test_duration: f64,
     pub timestamp: String,
     pub language: Language,
+    /// Set when the keystroke timeline looked implausible
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<CheatFlag>,
 }
 
 impl LeaderboardEntry {
@@ -114,6 +146,10 @@ pub enum TestType {
     Quote,
     Practice(usize),
     Wiki,
+    /// LAN race, with the place finished in
+    Race(usize),
+    /// Scored attempt at the daily challenge of that date
+    Daily(chrono::NaiveDate),
 }
 
 #[derive(Debug)]
@@ -124,6 +160,7 @@ pub enum ValidationError {
     FieldTooLong(String),
     InvalidTestDuration(f64),
     InvalidWordCount(usize),
+    InvalidPlayerName(String),
 }
 
 #[derive(Debug)]
@@ -133,6 +170,34 @@ pub enum LeaderboardError {
     ValidationError(ValidationError),
     LockTimeout,
     LockError(String),
+    DatabaseError(String),
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::InvalidWpm(wpm) => write!(f, "WPM {} is out of range", wpm),
+            ValidationError::InvalidAccuracy(accuracy) => write!(f, "accuracy {}% is out of range", accuracy),
+            ValidationError::InvalidTimestamp(timestamp) => write!(f, "{:?} is not an RFC 3339 timestamp", timestamp),
+            ValidationError::FieldTooLong(field) => write!(f, "{}", field),
+            ValidationError::InvalidTestDuration(secs) => write!(f, "test duration {}s is out of range", secs),
+            ValidationError::InvalidWordCount(words) => write!(f, "word count {} is out of range", words),
+            ValidationError::InvalidPlayerName(name) => write!(f, "invalid player name {:?}", name),
+        }
+    }
+}
+
+impl std::fmt::Display for LeaderboardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LeaderboardError::IoError(e) => write!(f, "{}", e),
+            LeaderboardError::SerializationError(e) => write!(f, "invalid JSON: {}", e),
+            LeaderboardError::ValidationError(e) => write!(f, "invalid result: {}", e),
+            LeaderboardError::LockTimeout => write!(f, "the leaderboard is locked by another typeman"),
+            LeaderboardError::LockError(e) => write!(f, "{}", e),
+            LeaderboardError::DatabaseError(e) => write!(f, "database error: {}", e),
+        }
+    }
 }
 
 /// File lock guard that automatically releases the lock when dropped
@@ -189,273 +254,33 @@ impl From<ValidationError> for LeaderboardError {
     }
 }
 
-/// Creates a backup of the leaderboard file before modifications with rotation
-/// Maintains up to 3 backup files: .json.bak, .json.bak2, .json.bak3
-fn create_backup(leaderboard_path: &PathBuf) -> Result<PathBuf, std::io::Error> {
-    if !leaderboard_path.exists() {
-        return Ok(leaderboard_path.with_extension("json.bak"));
-    }
-    
-    // Rotate existing backups: .bak2 -> .bak3, .bak -> .bak2
-    let backup3_path = leaderboard_path.with_extension("json.bak3");
-    let backup2_path = leaderboard_path.with_extension("json.bak2");
-    let backup1_path = leaderboard_path.with_extension("json.bak");
-    
-    // Remove oldest backup if it exists
-    if backup3_path.exists() {
-        fs::remove_file(&backup3_path).ok(); // Ignore errors
-    }
-    
-    // Rotate backup2 -> backup3
-    if backup2_path.exists() {
-        fs::rename(&backup2_path, &backup3_path).ok(); // Ignore errors
-    }
-    
-    // Rotate backup1 -> backup2
-    if backup1_path.exists() {
-        fs::rename(&backup1_path, &backup2_path).ok(); // Ignore errors
-    }
-    
-    // Create new backup from current file
-    fs::copy(leaderboard_path, &backup1_path)?;
-    
-    Ok(backup1_path)
-}
-
-/// Performs atomic write by writing to a temporary file first, then moving
-fn atomic_write(path: &PathBuf, entries: &[LeaderboardEntry]) -> Result<(), LeaderboardError> {
-    let temp_path = path.with_extension("json.tmp");
-    
-    // Write to temporary file first
-    let json = serde_json::to_string_pretty(entries)?;
-    {
-        let mut temp_file = fs::File::create(&temp_path)?;
-        temp_file.write_all(json.as_bytes())?;
-        temp_file.sync_all()?; // Ensure data is written to disk
-    }
-    
-    // Verify the written data is valid JSON
-    if let Err(_) = validate_json_file(&temp_path) {
-        fs::remove_file(&temp_path).ok(); // Clean up temp file
-        return Err(LeaderboardError::IoError(
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidData, 
-                "Written data failed validation"
-            )
-        ));
-    }
-    
-    // Atomically move temp file to final location
-    fs::rename(&temp_path, path)?;
-    
-    Ok(())
-}
-
-/// Validates that a JSON file contains valid leaderboard data
-fn validate_json_file(path: &PathBuf) -> Result<(), LeaderboardError> {
-    if !path.exists() {
-        return Ok(()); // Non-existent file is valid (empty leaderboard)
-    }
-    
-    let content = fs::read_to_string(path)?;
-    if content.trim().is_empty() {
-        return Ok(()); // Empty file is valid
-    }
-    
-    // Try to parse as leaderboard entries
-    let _entries: Vec<LeaderboardEntry> = serde_json::from_str(&content)?;
-    Ok(())
-}
-
-/// Attempts to recover from backup files if main file is corrupted
-/// Tries backup files in order: .bak, .bak2, .bak3
-fn recover_from_backup(leaderboard_path: &PathBuf) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
-    let backup_files = [
-        leaderboard_path.with_extension("json.bak"),
-        leaderboard_path.with_extension("json.bak2"), 
-        leaderboard_path.with_extension("json.bak3"),
-    ];
-    
-    for (i, backup_path) in backup_files.iter().enumerate() {
-        if !backup_path.exists() {
-            continue;
-        }
-        
-        eprintln!("Attempting to recover leaderboard from backup {}...", i + 1);
-        
-        if let Ok(()) = validate_json_file(backup_path) {
-            let content = fs::read_to_string(backup_path)?;
-            if content.trim().is_empty() {
-                eprintln!("Backup {} is empty, trying next backup...", i + 1);
-                continue;
-            }
-            
-            match serde_json::from_str::<Vec<LeaderboardEntry>>(&content) {
-                Ok(entries) => {
-                    // Restore this backup as main file
-                    fs::copy(backup_path, leaderboard_path)?;
-                    eprintln!("Successfully recovered leaderboard from backup {}", i + 1);
-                    return Ok(entries);
-                }
-                Err(_) => {
-                    eprintln!("Backup {} is corrupted, trying next backup...", i + 1);
-                    continue;
-                }
-            }
-        } else {
-            eprintln!("Backup {} failed validation, trying next backup...", i + 1);
-        }
-    }
-    
-    eprintln!("No valid backup found, starting with empty leaderboard");
-    Ok(Vec::new())
+pub fn save_entry(entry: &LeaderboardEntry) -> Result<(), LeaderboardError> {
+    save_entries(std::slice::from_ref(entry))
 }
 
-/// Retry helper for operations that might fail temporarily
-fn retry_operation<F, T, E>(mut operation: F, max_retries: usize, delay: Duration) -> Result<T, E>
-where
-    F: FnMut() -> Result<T, E>,
-    E: std::fmt::Debug,
-{
-    let mut last_error = None;
-    
-    for attempt in 0..=max_retries {
-        match operation() {
-            Ok(result) => return Ok(result),
-            Err(error) => {
-                if attempt == max_retries {
-                    return Err(error);
-                }
-                
-                eprintln!("Operation failed (attempt {}), retrying in {:?}: {:?}", 
-                         attempt + 1, delay, error);
-                last_error = Some(error);
-                std::thread::sleep(delay);
-            }
-        }
+/// Saves several entries with a single locked write (used by import)
+pub fn save_entries(new_entries: &[LeaderboardEntry]) -> Result<(), LeaderboardError> {
+    // Validate entries before saving
+    for entry in new_entries {
+        entry.validate()?;
     }
-    
-    // This should never be reached due to the logic above
-    Err(last_error.unwrap())
-}
-
-pub fn get_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let home = std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .map_err(|_| "Unable to find home directory")?;
-    
-    let config_dir = PathBuf::from(home).join(".config").join("typeman");
-    fs::create_dir_all(&config_dir)?;
-    Ok(config_dir)
-}
 
-pub fn save_entry(entry: &LeaderboardEntry) -> Result<(), LeaderboardError> {
-    // Validate entry before saving
-    entry.validate()?;
-    
-    let config_dir = get_config_dir().map_err(|e| LeaderboardError::IoError(
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
-    ))?;
-    let leaderboard_path = config_dir.join("leaderboard.json");
-    let lock_path = config_dir.join("leaderboard.lock");
-    
-    // Retry the entire save operation up to 3 times for temporary failures
-    retry_operation(|| -> Result<(), LeaderboardError> {
-        // Acquire file lock with 5-second timeout
-        let _lock_guard = FileLockGuard::acquire(&lock_path, Duration::from_secs(5))?;
-        
-        // Create backup before making changes
-        create_backup(&leaderboard_path)?;
-        
-        // Load existing entries
-        let mut entries = load_entries().unwrap_or_default();
-        
-        // Add new entry
-        entries.push(entry.clone());
-        
-        // Sort by WPM (highest first)
-        entries.sort_by(|a, b| b.wpm.partial_cmp(&a.wpm).unwrap_or(std::cmp::Ordering::Equal));
-        
-        // Limit to top 100 entries
-        entries.truncate(100);
-        
-        // Save to file using atomic write
-        atomic_write(&leaderboard_path, &entries)?;
-        
-        Ok(())
-    }, 2, Duration::from_millis(100))?; // Retry up to 2 times with 100ms delay
-    
-    // Invalidate cache after successful save
-    invalidate_cache();
-    
-    Ok(())
+    open_configured()?.save_entries(new_entries)
 }
 
 pub fn load_entries() -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
-    let config_dir = get_config_dir().map_err(|e| LeaderboardError::IoError(
-        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
-    ))?;
-    let leaderboard_path = config_dir.join("leaderboard.json");
-    
-    // Return empty vec if file doesn't exist
-    if !leaderboard_path.exists() {
-        return Ok(Vec::new());
-    }
-    
-    // Check cache first
-    if let Ok(cache_guard) = get_cache().lock() {
-        if let Some(ref cache) = *cache_guard {
-            if cache.is_valid(&leaderboard_path) {
-                return Ok(cache.entries.clone());
-            }
-        }
-    }
-    
-    // Cache miss or invalid, load from file
-    let entries = load_entries_from_file(&leaderboard_path)?;
-    
-    // Update cache
-    if let Ok(metadata) = fs::metadata(&leaderboard_path) {
-        if let Ok(modified) = metadata.modified() {
-            if let Ok(mut cache_guard) = get_cache().lock() {
-                *cache_guard = Some(LeaderboardCache {
-                    entries: entries.clone(),
-                    last_modified: modified,
-                    cached_at: Instant::now(),
-                });
-            }
-        }
-    }
-    
-    Ok(entries)
+    open_configured()?.load_entries()
 }
 
-/// Load entries directly from file without caching
-fn load_entries_from_file(leaderboard_path: &PathBuf) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
-    // First try to validate and load the main file
-    if let Ok(()) = validate_json_file(leaderboard_path) {
-        let content = fs::read_to_string(leaderboard_path)?;
-        if content.trim().is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        match serde_json::from_str(&content) {
-            Ok(entries) => return Ok(entries),
-            Err(_) => {
-                eprintln!("Main leaderboard file appears corrupted, attempting recovery...");
-            }
-        }
-    } else {
-        eprintln!("Main leaderboard file validation failed, attempting recovery...");
-    }
-    
-    // If main file is corrupted, try to recover from backup
-    recover_from_backup(leaderboard_path)
+/// Every stored result, oldest first (the leaderboard itself only keeps the best 100)
+pub fn load_history(since: Option<&str>) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+    open_configured()?.load_history(since)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::paths::TempDataDir;
 
     #[test]
     fn test_leaderboard_entry_serialization() {
@@ -468,6 +293,7 @@ mod tests {
             test_duration: 30.0,
             timestamp: "2025-09-11T10:30:00Z".to_string(),
             language: Language::English,
+            flag: None,
         };
 
         // Test serialization
@@ -498,18 +324,16 @@ mod tests {
     }
 
     #[test]
-    fn test_get_config_dir() {
-        // This test will fail until implementation
-        let result = get_config_dir();
-        assert!(result.is_ok(), "Should return a valid config directory path");
-        
-        let path = result.unwrap();
-        assert!(path.to_string_lossy().contains("typeman"), "Path should contain 'typeman'");
+    fn test_data_dir_override() {
+        let data_dir = TempDataDir::new("leaderboard-override");
+        let result = crate::profile::current_data_dir();
+        assert!(result.is_ok(), "Should return a valid data directory path");
+        assert!(result.unwrap().starts_with(&data_dir.path), "Data should live in the overridden directory");
     }
 
     #[test]
     fn test_save_and_load_entries() {
-        // This test will fail until implementation
+        let _data_dir = TempDataDir::new("leaderboard-save");
         let entry = LeaderboardEntry {
             wpm: 85.5,
             accuracy: 98.2,
@@ -519,6 +343,7 @@ mod tests {
             test_duration: 30.0,
             timestamp: "2025-09-11T10:30:00Z".to_string(),
             language: Language::English,
+            flag: None,
         };
 
         // Test saving entry
@@ -546,13 +371,12 @@ mod tests {
 
     #[test]
     fn test_load_entries_empty_file() {
-        // This test will fail until implementation
+        let _data_dir = TempDataDir::new("leaderboard-empty");
         // Should handle empty or non-existent file gracefully
         let result = load_entries();
         assert!(result.is_ok(), "Should handle empty file gracefully");
-        
-        let entries = result.unwrap();
-        // Could be empty on first run - just verify it's a valid vector
-        assert!(entries.is_empty() || !entries.is_empty(), "Should return valid entries vector");
+
+        // A fresh data directory has no results yet
+        assert!(result.unwrap().is_empty(), "Should return no entries");
     }
 }
\ No newline at end of file
@@ -7,39 +7,77 @@ use std::path::PathBuf;
 pub mod ui {
     #[cfg(feature = "cli")]
     pub mod cli {
+        pub mod accessible;
         pub mod main;
         pub mod modes;
+        pub mod results;
     }
 
     #[cfg(feature = "gui")]
     pub mod gui {
+        pub mod achievements;
         pub mod config;
+        pub mod hotseat;
+        pub mod leaderboard;
         pub mod main;
         pub mod popup;
         pub mod practice;
+        pub mod race;
         pub mod results;
     }
 
     #[cfg(feature = "tui")]
     pub mod tui {
         pub mod app;
+        pub mod hotseat;
         pub mod r#mod;
         pub mod ui;
+        pub mod palette;
         pub mod popup;
+        pub mod race;
     }
 }
 
+pub mod accessibility;
+pub mod achievements;
+pub mod anticheat;
 pub mod button_states;
 pub mod color_scheme;
 pub mod config;
 pub mod custom_colors;
+pub mod daily;
+pub mod export;
+pub mod fonts;
+pub mod ghost;
+pub mod goals;
+pub mod hotseat;
+pub mod keymap;
 pub mod language;
 pub mod leaderboard;
+pub mod pace;
+pub mod paths;
 pub mod practice;
+pub mod presets;
+pub mod profile;
+pub mod race;
 pub mod utils;
 pub mod time_selection;
 pub mod word_number_selection;
 pub mod settings;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "sync")]
+pub mod sync;
+pub mod team;
+pub mod terminal_colors;
+pub mod theme_import;
+pub mod themes;
+pub mod storage {
+    pub mod backend;
+    pub mod json;
+    #[cfg(feature = "sqlite")]
+    pub mod sqlite;
+}
 pub mod batch_size_selection;
 pub mod top_words_selection;
 
@@ -82,6 +120,18 @@ pub struct Cli {
 
     #[arg(long = "lang", value_name = "LANGUAGE")]
     pub language: Option<String>,
+
+    #[arg(long = "profile", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    #[arg(long = "data-dir", global = true, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub data_dir: Option<PathBuf>,
+
+    #[arg(long = "preset", value_name = "NAME")]
+    pub preset: Option<String>,
+
+    #[arg(long = "accessible")]
+    pub accessible: bool,
 }
 
 #[derive(Debug, Deserialize)]
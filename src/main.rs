@@ -3,46 +3,85 @@
 #[cfg(not(any(feature = "cli", feature = "tui", feature = "gui")))]
 compile_error!("At least one of 'cli', 'tui', or 'gui' must be enabled");
 
-use clap::{Parser, ValueHint};
+use clap::{Parser, Subcommand, ValueHint};
 use serde::Deserialize;
 use std::path::PathBuf;
+use crate::storage::backend::StorageBackend;
 
 pub mod ui {
     #[cfg(feature = "cli")]
     pub mod cli {
+        pub mod accessible;
         pub mod main;
         pub mod modes;
+        pub mod results;
     }
 
     #[cfg(feature = "gui")]
     pub mod gui {
+        pub mod achievements;
         pub mod config;
+        pub mod hotseat;
+        pub mod leaderboard;
         pub mod main;
         pub mod popup;
         pub mod practice;
+        pub mod race;
         pub mod results;
     }
 
     #[cfg(feature = "tui")]
     pub mod tui {
         pub mod app;
+        pub mod hotseat;
         pub mod r#mod;
         pub mod ui;
+        pub mod palette;
         pub mod popup;
+        pub mod race;
     }
 }
 
+pub mod accessibility;
+pub mod achievements;
+pub mod anticheat;
 pub mod button_states;
 pub mod color_scheme;
 pub mod config;
 pub mod custom_colors;
+pub mod daily;
+pub mod export;
+pub mod fonts;
+pub mod ghost;
+pub mod goals;
+pub mod hotseat;
+pub mod keymap;
 pub mod language;
 pub mod leaderboard;
+pub mod pace;
+pub mod paths;
 pub mod practice;
+pub mod presets;
+pub mod profile;
+pub mod race;
 pub mod utils;
 pub mod time_selection;
 pub mod word_number_selection;
 pub mod settings;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "sync")]
+pub mod sync;
+pub mod team;
+pub mod terminal_colors;
+pub mod theme_import;
+pub mod themes;
+pub mod storage {
+    pub mod backend;
+    pub mod json;
+    #[cfg(feature = "sqlite")]
+    pub mod sqlite;
+}
 pub mod batch_size_selection;
 pub mod top_words_selection;
 
@@ -51,9 +90,17 @@ use crate::ui::cli::modes;
 
 #[cfg(feature = "tui")]
 use crate::ui::tui::r#mod as tui_mod;
+#[cfg(feature = "tui")]
+use crate::ui::tui::race as tui_race;
+#[cfg(feature = "tui")]
+use crate::ui::tui::hotseat as tui_hotseat;
 
 #[cfg(feature = "gui")]
 use crate::ui::gui::main as gui;
+#[cfg(feature = "gui")]
+use crate::ui::gui::race as gui_race;
+#[cfg(feature = "gui")]
+use crate::ui::gui::hotseat as gui_hotseat;
 
 #[derive(Parser)]
 #[command(
@@ -68,6 +115,26 @@ typeman --cli -w=50 -n=500
 typeman --cli -w=50 -n=500 -p -d
 typeman --cli --wiki
 typeman --gui
+typeman export --format csv --since 2025-09-01 > results.csv
+typeman import results.csv
+typeman migrate --to sqlite
+typeman --profile alice
+typeman team join ~/Dropbox/typeman --name alice
+typeman race host --words 40
+typeman race join 192.168.1.20
+typeman ghost race
+typeman ghost import teammate.json
+typeman hotseat ana bo carla --words 20
+typeman daily
+typeman daily --history
+typeman goals set --minutes 15 --tests 3 --wpm 80 --time 60
+typeman achievements
+typeman --preset exam --cli
+typeman --accessible
+typeman preset save sprint
+typeman theme import gruvbox-dark-hard.yaml
+typeman sync --server http://typeman.internal:8080 --name alice
+typeman serve --addr 0.0.0.0:8080
 typeman",
     long_about = "\n
 Run 'typeman --cli -c <path/to/your/file>' to test your typing on a specified text
@@ -84,6 +151,24 @@ Optional:
   - Use --gui for terminal-based interface
   - Use --cli for terminal-based interface
 
+Run 'typeman export --format csv|json [--since YYYY-MM-DD] [-o FILE]' to export your results
+Run 'typeman import <FILE>' to import results exported on another machine
+Run 'typeman migrate --to json|sqlite' to move your results to another storage backend
+Run 'typeman --profile <NAME> ...' to use a separate profile (config, results and practice progress)
+Run 'typeman --data-dir <DIR> ...' (before or after a subcommand, or set TYPEMAN_HOME) to keep config and data in one directory instead of $XDG_CONFIG_HOME/typeman and $XDG_DATA_HOME/typeman
+Run 'typeman team join <DIR> --name <NAME>' to share results on a team leaderboard in a synced folder
+Run 'typeman race host' to host a race on your network, and 'typeman race join <HOST[:PORT]>' to join one (add --gui for the GUI)
+Run 'typeman ghost race [ID]' to race an earlier run on the same text ('typeman ghost list' shows the saved runs)
+Run 'typeman daily' for today's daily challenge, the same text for everyone (one scored attempt per day, --history shows your streak)
+Run 'typeman goals set [--minutes N] [--tests N] [--wpm N --time S|--words N]' to set daily goals, 'typeman goals' to see your progress
+Run 'typeman achievements' to see which achievements you unlocked and when
+Run 'typeman theme import <FILE>' to add a Base16 YAML scheme or a monkeytype theme JSON as a user theme, 'typeman theme' to list the themes
+Run 'typeman --accessible ...' (or set \"accessible\": true in the config) for a screen-reader friendly test that announces each word and reads the results out
+Run 'typeman --preset <NAME> ...' to apply a preset of test settings, 'typeman preset [list|save|remove|use]' to manage them
+Run 'typeman hotseat <NAME> <NAME>...' to let 2-8 players take turns on the same text on this machine
+Run 'typeman sync --server <URL> --name <NAME>' to upload results to a leaderboard server and see its ranking
+Run 'typeman serve [--addr 0.0.0.0:8080]' to host a leaderboard server (built with the 'server' feature)
+
 Default behavior for cli is to test typing on random words for 30 seconds with 500 most common English words.
 Default mode is tui.
     "
@@ -131,6 +216,305 @@ pub struct Cli {
         help = "Language for word lists (english, indonesian)"
     )]
     language: Option<String>,
+
+    #[arg(
+        long = "profile",
+        value_name = "NAME",
+        global = true,
+        help = "Profile to use for config, results and practice progress (created if missing)"
+    )]
+    profile: Option<String>,
+
+    #[arg(
+        long = "data-dir",
+        global = true,
+        value_name = "DIR",
+        value_hint = ValueHint::DirPath,
+        help = "Keep config and data in DIR instead of the XDG directories (also TYPEMAN_HOME)"
+    )]
+    data_dir: Option<PathBuf>,
+
+    #[arg(
+        long = "preset",
+        value_name = "NAME",
+        help = "Apply a saved preset of test settings first ('typeman preset' lists them)"
+    )]
+    preset: Option<String>,
+
+    #[arg(
+        long = "accessible",
+        conflicts_with = "gui",
+        help = "Announce words and results line by line for screen readers instead of drawing the text"
+    )]
+    accessible: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Export leaderboard results as CSV or JSON
+    Export {
+        #[arg(short = 'f', long = "format", value_enum, default_value = "json")]
+        format: export::ExportFormat,
+
+        #[arg(long = "since", value_name = "YYYY-MM-DD")]
+        since: Option<String>,
+
+        #[arg(short = 'o', long = "output", value_name = "FILE", value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+    /// Import results from a CSV or JSON export
+    Import {
+        #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+    },
+    /// Copy all results to another storage backend and switch to it
+    Migrate {
+        #[arg(long = "to", value_enum)]
+        to: StorageBackend,
+    },
+    /// Share results on a team leaderboard kept in a synced folder
+    Team {
+        #[command(subcommand)]
+        action: TeamAction,
+    },
+    /// Race others on the local network
+    Race {
+        #[command(subcommand)]
+        action: RaceAction,
+    },
+    /// Race against an earlier run on the same text
+    Ghost {
+        #[command(subcommand)]
+        action: GhostAction,
+    },
+    /// Type today's daily challenge, the same text for everyone
+    Daily {
+        /// Print the streak and earlier daily results instead
+        #[arg(long = "history")]
+        history: bool,
+
+        #[arg(long = "gui")]
+        gui: bool,
+    },
+    /// Show or set practice goals
+    Goals {
+        #[command(subcommand)]
+        action: Option<GoalsAction>,
+    },
+    /// List the achievements and when they were unlocked
+    Achievements,
+    /// List, save or remove presets of test settings
+    Preset {
+        #[command(subcommand)]
+        action: Option<PresetAction>,
+    },
+    /// List the color themes, or import one from Base16 or monkeytype
+    Theme {
+        #[command(subcommand)]
+        action: Option<ThemeAction>,
+    },
+    /// Let 2-8 players take turns typing the same text on this machine
+    Hotseat {
+        #[arg(value_name = "NAME", num_args = 1.., required = true)]
+        players: Vec<String>,
+
+        /// Number of words everyone types
+        #[arg(short = 'w', long = "words", default_value_t = 30, value_parser = clap::value_parser!(u16).range(1..=500))]
+        words: u16,
+
+        #[arg(long = "gui")]
+        gui: bool,
+    },
+    /// Upload results to a leaderboard server and show its ranking
+    #[cfg(feature = "sync")]
+    Sync {
+        /// Server to use from now on, e.g. http://typeman.internal:8080
+        #[arg(long = "server", value_name = "URL")]
+        server: Option<String>,
+
+        /// Player name to use from now on
+        #[arg(long = "name", value_name = "NAME")]
+        name: Option<String>,
+
+        /// Token to send from now on, if the server was started with one
+        #[arg(long = "token", value_name = "TOKEN")]
+        token: Option<String>,
+
+        /// Upload the whole local history, not just new results
+        #[arg(long = "all")]
+        all: bool,
+
+        /// Only rank results of this test mode (time, word, quote, ...)
+        #[arg(long = "mode", value_name = "MODE")]
+        mode: Option<String>,
+
+        /// Show every result instead of each player's best
+        #[arg(long = "every")]
+        every: bool,
+
+        #[arg(long = "limit", default_value_t = 20)]
+        limit: usize,
+    },
+    /// Run a leaderboard server for your team
+    #[cfg(feature = "server")]
+    Serve {
+        #[arg(long = "addr", default_value = "0.0.0.0:8080")]
+        addr: String,
+
+        /// Only accept uploads that send this token (also TYPEMAN_SERVER_TOKEN)
+        #[arg(long = "token", value_name = "TOKEN")]
+        token: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RaceAction {
+    /// Host a race; others join with 'typeman race join <your address>'
+    Host {
+        #[arg(long = "addr", default_value = "0.0.0.0:7878")]
+        addr: String,
+
+        /// Name shown to the other racers (default: your player or profile name)
+        #[arg(long = "name", value_name = "NAME")]
+        name: Option<String>,
+
+        /// Number of words to race on
+        #[arg(short = 'w', long = "words", default_value_t = 30, value_parser = clap::value_parser!(u16).range(1..=500))]
+        words: u16,
+
+        /// Race in the GUI instead of the terminal
+        #[arg(long = "gui")]
+        gui: bool,
+    },
+    /// Join a race hosted on the local network
+    Join {
+        #[arg(value_name = "HOST[:PORT]")]
+        host: String,
+
+        #[arg(long = "name", value_name = "NAME")]
+        name: Option<String>,
+
+        #[arg(long = "gui")]
+        gui: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GoalsAction {
+    /// Set goals; the ones not given stay as they are
+    Set {
+        /// Minutes of typing per day
+        #[arg(long = "minutes", value_name = "N")]
+        minutes: Option<u32>,
+
+        /// Tests per day
+        #[arg(long = "tests", value_name = "N")]
+        tests: Option<u32>,
+
+        /// Speed to reach, on the test given with --time or --words
+        #[arg(long = "wpm", value_name = "N", requires = "wpm_test")]
+        wpm: Option<u32>,
+
+        #[arg(long = "time", value_name = "SECONDS", group = "wpm_test")]
+        time: Option<u32>,
+
+        #[arg(long = "words", value_name = "N", group = "wpm_test")]
+        words: Option<usize>,
+
+        /// Language of the speed goal (default: the configured one)
+        #[arg(long = "language", value_name = "LANGUAGE")]
+        language: Option<String>,
+    },
+    /// Remove all goals
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum PresetAction {
+    /// List the presets
+    List,
+    /// Save the current test settings as a preset
+    Save {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    /// Remove a preset
+    Remove {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    /// Apply a preset to the saved settings
+    Use {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ThemeAction {
+    /// List the built-in and user themes
+    List,
+    /// Turn a Base16 YAML scheme or a monkeytype theme JSON into a user theme
+    Import {
+        #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+
+        /// Name to give the theme (default: the name in the file)
+        #[arg(long = "name", value_name = "NAME")]
+        name: Option<String>,
+
+        /// Replace a user theme with the same name
+        #[arg(long = "force")]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GhostAction {
+    /// List the runs that can be raced
+    List,
+    /// Race a run (default: the latest)
+    Race {
+        #[arg(value_name = "ID")]
+        id: Option<usize>,
+
+        #[arg(long = "gui")]
+        gui: bool,
+    },
+    /// Save a run to a file to share it
+    Export {
+        #[arg(value_name = "ID")]
+        id: usize,
+
+        #[arg(short = 'o', long = "output", value_name = "FILE", value_hint = ValueHint::FilePath)]
+        output: PathBuf,
+    },
+    /// Add a run someone else exported
+    Import {
+        #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum TeamAction {
+    /// Start sharing results in a shared directory (Dropbox, network drive, ...)
+    Join {
+        #[arg(value_name = "DIR", value_hint = ValueHint::DirPath)]
+        dir: PathBuf,
+
+        #[arg(long = "name", value_name = "NAME")]
+        name: String,
+    },
+    /// Stop sharing results
+    Leave,
+    /// Add all local results to the team leaderboard
+    Push,
+    /// Print the team leaderboard
+    Show,
 }
 
 #[derive(Debug, Deserialize)]
@@ -141,7 +525,7 @@ pub struct Quote {
 
 #[cfg(feature = "gui")]
 pub fn gui_main() {
-    macroquad::Window::new("TypeMan", async { gui::gui_main_async().await });
+    macroquad::Window::new("TypeMan", async { gui::gui_main_async(None, false).await });
 }
 
 #[cfg(feature = "tui")]
@@ -153,7 +537,38 @@ pub fn tui_main() {
 }
 
 fn main() {
-    let args = Cli::parse();
+    #[allow(unused_mut)]
+    let mut args = Cli::parse();
+
+    if let Some(dir) = args.data_dir.as_ref() {
+        paths::set_data_dir(dir.clone());
+    }
+    if let Some(name) = args.profile.as_deref() {
+        if let Err(e) = profile::use_profile(name) {
+            eprintln!("Cannot use profile {:?}: {}", name, e);
+            std::process::exit(1);
+        }
+    }
+    // Reports and repairs a broken config before a frontend takes over the terminal
+    config::AppConfig::load_and_report();
+    if args.accessible {
+        accessibility::enable();
+    }
+
+    if let Some(name) = args.preset.as_deref() {
+        match presets::apply_named(name) {
+            #[cfg(feature = "cli")]
+            Ok(config) => fill_args_from_preset(&mut args, &config),
+            #[cfg(not(feature = "cli"))]
+            Ok(_) => {}
+            Err(e) => exit_with_preset_error(e),
+        }
+    }
+
+    if let Some(command) = args.command.as_ref() {
+        run_command(command);
+        return;
+    }
 
     if args.tui && !cfg!(feature = "tui") {
         eprintln!("TUI mode is not available in this build.");
@@ -182,6 +597,18 @@ fn main() {
         return;
     }
 
+    // The TUI draws all over the screen, so screen readers get the CLI test
+    // with the saved settings instead
+    #[cfg(feature = "cli")]
+    {
+        let config = config::AppConfig::load();
+        if accessibility::enabled(&config) {
+            fill_args_from_preset(&mut args, &config);
+            run_cli(&args);
+            return;
+        }
+    }
+
     #[cfg(feature = "tui")]
     {
         ui::tui::r#mod::main().unwrap();
@@ -189,6 +616,35 @@ fn main() {
     }
 }
 
+/// Settings of an applied preset for the flags not given on the command line
+#[cfg(feature = "cli")]
+fn fill_args_from_preset(args: &mut Cli, config: &config::AppConfig) {
+    let mode_given = args.custom_file.is_some()
+        || args.random_quote
+        || args.time_limit.is_some()
+        || args.word_number.is_some()
+        || args.level.is_some()
+        || args.wiki;
+    if !mode_given {
+        match config.mode {
+            config::Mode::Time => args.time_limit = Some(Some(config.test_time as u64)),
+            config::Mode::Words => args.word_number = Some(Some(config.word_number)),
+            config::Mode::Quote => args.random_quote = true,
+            config::Mode::Wiki => args.wiki = true,
+            config::Mode::Practice => args.level = Some(Some(config.selected_level + 1)),
+        }
+    }
+    args.top_words.get_or_insert(config.top_words);
+    args.punctuation |= config.punctuation;
+    args.digits |= config.numbers;
+    args.language.get_or_insert_with(|| config.language.to_string());
+}
+
+fn exit_with_preset_error(e: presets::PresetError) -> ! {
+    eprintln!("Preset failed: {}", e);
+    std::process::exit(1);
+}
+
 #[cfg(feature = "cli")]
 fn run_cli(args: &Cli) {
     if let Some(path) = args.custom_file.as_ref() {
@@ -204,4 +660,571 @@ fn run_cli(args: &Cli) {
     } else {
         modes::time_mode(args);
     }
-}
\ No newline at end of file
+}
+
+fn run_command(command: &Command) {
+    match command {
+        Command::Export { format, since, output } => {
+            let data = match export::export_to_string(*format, since.as_deref()) {
+                Ok(data) => data,
+                Err(e) => {
+                    eprintln!("Export failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            match output {
+                Some(path) => {
+                    if let Err(e) = std::fs::write(path, data) {
+                        eprintln!("Failed to write {:?}: {}", path, e);
+                        std::process::exit(1);
+                    }
+                }
+                None => print!("{}", data),
+            }
+        }
+        Command::Import { file } => match export::import_file(file) {
+            Ok(summary) => println!(
+                "Imported {} entries ({} duplicates skipped, {} invalid)",
+                summary.imported, summary.duplicates, summary.invalid
+            ),
+            Err(e) => {
+                eprintln!("Import failed: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Command::Migrate { to } => match storage::backend::switch_backend(*to) {
+            Ok(copied) => println!("Migrated {} results, now using {} storage", copied, to),
+            Err(e) => {
+                eprintln!("Migration failed: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Command::Team { action } => run_team_command(action),
+        Command::Race { action } => run_race_command(action),
+        Command::Ghost { action } => run_ghost_command(action),
+        Command::Daily { history, gui } => run_daily(*history, *gui),
+        Command::Goals { action } => run_goals_command(action.as_ref()),
+        Command::Achievements => run_achievements(),
+        Command::Preset { action } => run_preset_command(action.as_ref()),
+        Command::Theme { action } => run_theme_command(action.as_ref()),
+        Command::Hotseat { players, words, gui } => run_hotseat(players, *words as usize, *gui),
+        #[cfg(feature = "sync")]
+        Command::Sync { server, name, token, all, mode, every, limit } => run_sync_command(
+            server.as_deref(),
+            name.as_deref(),
+            token.as_deref(),
+            *all,
+            mode.as_deref(),
+            !*every,
+            *limit,
+        ),
+        #[cfg(feature = "server")]
+        Command::Serve { addr, token } => {
+            // Submitted results live next to the local data, so `--data-dir` moves them too
+            let data_dir = match paths::data_root() {
+                Ok(root) => root.join("server"),
+                Err(e) => {
+                    eprintln!("Failed to find the data directory: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let token = token.clone().or_else(|| std::env::var(server::TOKEN_VAR).ok()).filter(|t| !t.is_empty());
+            let protected = token.is_some();
+            match server::LeaderboardServer::bind(addr, &data_dir) {
+                Ok(server) => {
+                    println!("Serving the leaderboard on http://{} (data in {:?})", addr, data_dir);
+                    if !protected {
+                        println!("Anyone who can reach it can upload results, start it with --token to require one");
+                    }
+                    server.with_token(token).run();
+                }
+                Err(e) => {
+                    eprintln!("Failed to start the server: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn run_race_command(action: &RaceAction) {
+    let config = config::AppConfig::load();
+    let (name, gui) = match action {
+        RaceAction::Host { name, gui, .. } | RaceAction::Join { name, gui, .. } => (name.clone(), *gui),
+    };
+    let name = name.or(config.player_name.clone()).unwrap_or_else(profile::active);
+
+    let session = match action {
+        RaceAction::Host { addr, words, .. } => {
+            let word_list = utils::read_first_n_words(config.top_words, config.language);
+            let text = utils::get_reference(config.punctuation, config.numbers, &word_list, *words as usize);
+            race::RaceSession::host(addr, &name, text, config.language)
+        }
+        RaceAction::Join { host, .. } => {
+            let addr = if host.contains(':') {
+                host.clone()
+            } else {
+                format!("{}:{}", host, race::DEFAULT_PORT)
+            };
+            race::RaceSession::join(addr.as_str(), &name, config.language)
+        }
+    };
+    let session = match session {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("Failed to set up the race: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if gui {
+        #[cfg(feature = "gui")]
+        {
+            macroquad::Window::new("TypeMan race", gui_race::race_main_async(session));
+            return;
+        }
+        #[cfg(not(feature = "gui"))]
+        {
+            eprintln!("GUI mode is not available in this build.");
+            std::process::exit(1);
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    if let Err(e) = tui_race::run(session) {
+        eprintln!("TUI error: {}", e);
+        std::process::exit(1);
+    }
+    #[cfg(not(feature = "tui"))]
+    {
+        drop(session);
+        eprintln!("Races need the TUI or GUI, which are not available in this build.");
+        std::process::exit(1);
+    }
+}
+
+fn run_ghost_command(action: &GhostAction) {
+    let dir = match profile::current_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Cannot find the data directory: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let result = match action {
+        GhostAction::List => ghost::load(&dir).map(|runs| {
+            if runs.is_empty() {
+                println!("No runs yet. Finish a test in the TUI to record one.");
+            }
+            for (i, run) in runs.iter().enumerate() {
+                let date = run.timestamp.get(..16).unwrap_or(&run.timestamp).replace('T', " ");
+                let preview: String = run.reference.chars().take(40).collect();
+                println!(
+                    "{:>3}. {}  {:<16} {:>6.1} wpm {:>4} words  {}...",
+                    i + 1,
+                    date,
+                    run.player,
+                    run.wpm,
+                    run.reference.split_whitespace().count(),
+                    preview
+                );
+            }
+        }),
+        GhostAction::Race { id, gui } => {
+            let run = match id {
+                Some(id) => ghost::get(&dir, *id),
+                None => ghost::load(&dir).and_then(|mut runs| runs.pop().ok_or(ghost::GhostError::NotFound(0))),
+            };
+            match run {
+                Ok(run) => {
+                    run_ghost_race(run, *gui);
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            }
+        }
+        GhostAction::Export { id, output } => ghost::get(&dir, *id).and_then(|run| ghost::export(&run, output)),
+        GhostAction::Import { file } => ghost::import(&dir, file).map(|run| {
+            println!("Imported a {:.0} wpm run by {}; race it with 'typeman ghost race'", run.wpm, run.player);
+        }),
+    };
+    if let Err(e) = result {
+        eprintln!("Ghost command failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run_ghost_race(run: ghost::GhostRun, gui: bool) {
+    if gui {
+        #[cfg(feature = "gui")]
+        {
+            macroquad::Window::new("TypeMan", gui::gui_main_async(Some(run), false));
+            return;
+        }
+        #[cfg(not(feature = "gui"))]
+        {
+            eprintln!("GUI mode is not available in this build.");
+            std::process::exit(1);
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    if let Err(e) = tui_mod::ghost_race(run) {
+        eprintln!("TUI error: {}", e);
+        std::process::exit(1);
+    }
+    #[cfg(not(feature = "tui"))]
+    {
+        drop(run);
+        eprintln!("Ghost races need the TUI or GUI, which are not available in this build.");
+        std::process::exit(1);
+    }
+}
+
+fn run_daily(history: bool, gui: bool) {
+    if history {
+        let entries = match leaderboard::load_history(None) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Failed to load results: {}", e);
+                std::process::exit(1);
+            }
+        };
+        println!("{}", daily::status(&entries, daily::today()));
+        for (date, entry) in daily::recent(&entries, 30) {
+            let note = entry.flag.as_ref().map(|f| format!("  ({})", f)).unwrap_or_default();
+            println!("  {}  {:>6.1} wpm  {:>5.1}%{}", date, entry.wpm, entry.accuracy, note);
+        }
+        return;
+    }
+
+    if gui {
+        #[cfg(feature = "gui")]
+        {
+            macroquad::Window::new("TypeMan", gui::gui_main_async(None, true));
+            return;
+        }
+        #[cfg(not(feature = "gui"))]
+        {
+            eprintln!("GUI mode is not available in this build.");
+            std::process::exit(1);
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    if let Err(e) = tui_mod::daily() {
+        eprintln!("TUI error: {}", e);
+        std::process::exit(1);
+    }
+    #[cfg(not(feature = "tui"))]
+    {
+        eprintln!("The daily challenge needs the TUI or GUI, which are not available in this build.");
+        std::process::exit(1);
+    }
+}
+
+fn run_goals_command(action: Option<&GoalsAction>) {
+    let mut config = config::AppConfig::load();
+    match action {
+        None => {}
+        Some(GoalsAction::Clear) => config.goals = goals::Goals::default(),
+        Some(GoalsAction::Set { minutes, tests, wpm, time, words, language }) => {
+            if minutes.is_some() {
+                config.goals.minutes_per_day = *minutes;
+            }
+            if tests.is_some() {
+                config.goals.tests_per_day = *tests;
+            }
+            if let Some(wpm) = wpm {
+                let language = match language {
+                    Some(name) => match language::Language::all().iter().find(|l| l.to_string().eq_ignore_ascii_case(name)) {
+                        Some(language) => *language,
+                        None => {
+                            eprintln!("Unknown language '{}'", name);
+                            std::process::exit(1);
+                        }
+                    },
+                    None => config.language,
+                };
+                let test_type = match (time, words) {
+                    (Some(secs), _) => leaderboard::TestType::Time(*secs),
+                    (_, Some(words)) => leaderboard::TestType::Word(*words),
+                    _ => unreachable!("clap requires --time or --words"),
+                };
+                config.goals.target_wpm = Some(goals::WpmGoal { wpm: *wpm, test_type, language });
+            }
+        }
+    }
+    if action.is_some() {
+        if let Err(e) = config.save() {
+            eprintln!("Failed to save the config: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if config.goals.is_empty() {
+        println!("No goals set. Set some with 'typeman goals set --minutes 15 --tests 3'.");
+        return;
+    }
+    let history = leaderboard::load_history(None).unwrap_or_default();
+    for progress in goals::progress(&config.goals, &history, chrono::Local::now().date_naive()) {
+        println!("{}", progress);
+    }
+}
+
+fn run_achievements() {
+    // Picks up results imported or synced since the last test
+    achievements::check_saved();
+    let unlocked = achievements::load_saved();
+    let list = achievements::list(&unlocked);
+    println!(
+        "{}/{} achievements unlocked",
+        list.iter().filter(|(_, when)| when.is_some()).count(),
+        list.len()
+    );
+    for (achievement, when) in list {
+        match when {
+            Some(when) => println!("\u{2713} {:<18} {:<48} {}", achievement.name, achievement.description, when),
+            None => println!("  {:<18} {}", achievement.name, achievement.description),
+        }
+    }
+}
+
+fn run_preset_command(action: Option<&PresetAction>) {
+    match action {
+        None | Some(PresetAction::List) => {}
+        Some(PresetAction::Save { name }) => match presets::save_current(name) {
+            Ok(preset) => println!("Saved {}", preset),
+            Err(e) => exit_with_preset_error(e),
+        },
+        Some(PresetAction::Remove { name }) => match presets::remove(name) {
+            Ok(()) => println!("Removed preset '{}'", name),
+            Err(e) => exit_with_preset_error(e),
+        },
+        Some(PresetAction::Use { name }) => match presets::apply_named(name) {
+            Ok(_) => println!("Applied preset '{}'", name),
+            Err(e) => exit_with_preset_error(e),
+        },
+    }
+    if action.is_some_and(|action| !matches!(action, PresetAction::List)) {
+        return;
+    }
+    let config = config::AppConfig::load();
+    if config.presets.is_empty() {
+        println!("No presets, save one with 'typeman preset save <NAME>'");
+    }
+    for preset in &config.presets {
+        let active = config.preset.as_deref() == Some(preset.name.as_str()) && preset.matches(&config);
+        println!("{} {}", if active { "*" } else { " " }, preset);
+    }
+}
+
+fn run_theme_command(action: Option<&ThemeAction>) {
+    if let Some(ThemeAction::Import { file, name, force }) = action {
+        match theme_import::import(file, name.as_deref(), *force) {
+            Ok((theme, path)) => {
+                println!("Imported '{}' to {}", theme.name, path.display());
+                println!("Pick it in the theme popup of the TUI or GUI");
+            }
+            Err(themes::ThemeError::NameTaken(name)) => {
+                eprintln!("There already is a theme called '{}', use --name to pick another or --force to replace it", name);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Import failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let current = config::AppConfig::load().color_scheme;
+    for scheme in color_scheme::ColorScheme::all() {
+        let kind = if matches!(scheme, color_scheme::ColorScheme::User(_)) { "user" } else { "built-in" };
+        println!("{} {:<24} {}", if scheme == current { "*" } else { " " }, scheme.name(), kind);
+    }
+    if let Ok(dir) = themes::themes_dir() {
+        println!("\nUser themes are read from {}", dir.display());
+    }
+    #[cfg(any(feature = "cli", feature = "tui"))]
+    {
+        let source = if config::AppConfig::load().color_depth.is_some() { "set in the config" } else { "detected" };
+        println!("Terminal colors: {} ({})", terminal_colors::ColorDepth::current(), source);
+    }
+    for error in themes::errors() {
+        eprintln!("Skipped {}", error);
+    }
+}
+
+fn run_hotseat(players: &[String], words: usize, gui: bool) {
+    let config = config::AppConfig::load();
+    let word_list = utils::read_first_n_words(config.top_words, config.language);
+    let text = utils::get_reference(config.punctuation, config.numbers, &word_list, words);
+    let game = match hotseat::HotseatGame::new(players.to_vec(), text) {
+        Ok(game) => game,
+        Err(hotseat::HotseatError::PlayerCount(n)) => {
+            eprintln!("Hot-seat needs {} to {} players, got {}", hotseat::MIN_PLAYERS, hotseat::MAX_PLAYERS, n);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Invalid players: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if gui {
+        #[cfg(feature = "gui")]
+        {
+            macroquad::Window::new("TypeMan hot-seat", gui_hotseat::hotseat_main_async(game));
+            return;
+        }
+        #[cfg(not(feature = "gui"))]
+        {
+            eprintln!("GUI mode is not available in this build.");
+            std::process::exit(1);
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    if let Err(e) = tui_hotseat::run(game) {
+        eprintln!("TUI error: {}", e);
+        std::process::exit(1);
+    }
+    #[cfg(not(feature = "tui"))]
+    {
+        drop(game);
+        eprintln!("Hot-seat games need the TUI or GUI, which are not available in this build.");
+        std::process::exit(1);
+    }
+}
+
+fn print_team_entries(entries: &[team::TeamEntry]) {
+    for (i, e) in entries.iter().enumerate() {
+        let note = e.entry.flag.as_ref().map(|f| format!("  ({})", f)).unwrap_or_default();
+        println!(
+            "{:>3}. {:<16} {:>6.1} wpm {:>5.1}%  {:<5} {}{}",
+            i + 1,
+            e.player,
+            e.entry.wpm,
+            e.entry.accuracy,
+            e.entry.test_mode,
+            e.entry.timestamp.get(..10).unwrap_or(&e.entry.timestamp),
+            note
+        );
+    }
+}
+
+#[cfg(feature = "sync")]
+fn run_sync_command(
+    server: Option<&str>,
+    name: Option<&str>,
+    token: Option<&str>,
+    all: bool,
+    mode: Option<&str>,
+    best_only: bool,
+    limit: usize,
+) {
+    if server.is_some() || name.is_some() || token.is_some() {
+        if let Some(name) = name {
+            if let Err(e) = team::validate_player_name(name) {
+                eprintln!("Invalid player name: {}", e);
+                std::process::exit(1);
+            }
+        }
+        let mut config = config::AppConfig::load();
+        config.server_url = server.map(str::to_string).or(config.server_url);
+        config.player_name = name.map(|n| n.trim().to_string()).or(config.player_name);
+        config.server_token = token.map(str::to_string).or(config.server_token);
+        if let Err(e) = config.save() {
+            eprintln!("Failed to save config: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let (Some((server, player, token)), Ok(dir)) = (sync::configured(), profile::current_data_dir()) else {
+        eprintln!("No server set up, run 'typeman sync --server <URL> --name <NAME>' first");
+        std::process::exit(1);
+    };
+
+    if all {
+        let queued = leaderboard::load_history(None).and_then(|history| sync::enqueue(&dir, &history));
+        if let Err(e) = queued {
+            eprintln!("Failed to queue local results: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    match sync::flush_queue(&dir, &server, &player, token.as_deref()) {
+        Ok(summary) => {
+            let uploaded = summary.uploaded;
+            println!(
+                "Uploaded {} results ({} already on the server, {} conflicts)",
+                uploaded.added, uploaded.duplicates, uploaded.conflicts
+            );
+            if summary.rejected > 0 {
+                eprintln!(
+                    "The server refused {} results, they were moved to {:?}",
+                    summary.rejected,
+                    sync::rejected_path(&dir)
+                );
+            }
+        }
+        Err(sync::SyncError::Unreachable(e)) => {
+            let queued = sync::load_queue(&dir).map(|q| q.len()).unwrap_or(0);
+            eprintln!("Server unreachable ({}), {} results stay queued for the next sync", e, queued);
+            std::process::exit(1);
+        }
+        Err(sync::SyncError::Rejected(401 | 403, _)) => {
+            eprintln!("The server refused the token, set the right one with 'typeman sync --token <TOKEN>'");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Sync failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    match sync::pull(&server, mode, best_only, limit) {
+        Ok(entries) if entries.is_empty() => println!("No results on the server yet"),
+        Ok(entries) => print_team_entries(&entries),
+        Err(e) => {
+            eprintln!("Failed to fetch the ranking: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_team_command(action: &TeamAction) {
+    let result = match action {
+        TeamAction::Join { dir, name } => team::join(dir, name).map(|_| {
+            println!("Joined the team in {:?} as {}", dir, name.trim());
+            println!("Run 'typeman team push' to share the results you already have");
+        }),
+        TeamAction::Leave => team::leave().map(|_| println!("Results are no longer shared")),
+        TeamAction::Push => match team::configured() {
+            Some((dir, player)) => leaderboard::load_history(None)
+                .and_then(|entries| team::publish(&dir, &player, &entries))
+                .map(|summary| {
+                    println!(
+                        "Pushed {} results ({} already there, {} conflicts)",
+                        summary.added, summary.duplicates, summary.conflicts
+                    )
+                }),
+            None => {
+                eprintln!("No team set up, run 'typeman team join <DIR> --name <NAME>' first");
+                std::process::exit(1);
+            }
+        },
+        TeamAction::Show => match team::configured() {
+            Some((dir, _)) => team::load(&dir).map(|entries| print_team_entries(&entries)),
+            None => {
+                eprintln!("No team set up, run 'typeman team join <DIR> --name <NAME>' first");
+                std::process::exit(1);
+            }
+        },
+    };
+
+    if let Err(e) = result {
+        eprintln!("Team command failed: {}", e);
+        std::process::exit(1);
+    }
+}
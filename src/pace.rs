@@ -0,0 +1,154 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::language::Language;
+use crate::leaderboard::{self, LeaderboardEntry, TestType};
+
+/// Speed of the pace caret, a second cursor that runs through the text at a
+/// steady WPM for the user to keep up with
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum Pace {
+    #[default]
+    Off,
+    /// Average of earlier results with the same test settings
+    Average,
+    /// Best earlier result with the same test settings
+    Best,
+    Fixed(u32),
+}
+
+impl Pace {
+    pub fn all() -> Vec<Pace> {
+        let mut all = vec![Pace::Off, Pace::Average, Pace::Best];
+        all.extend([40, 60, 80, 100, 120, 150].map(Pace::Fixed));
+        all
+    }
+
+    pub fn count() -> usize {
+        Self::all().len()
+    }
+
+    /// Target WPM for a test, looked up from the leaderboard history when needed
+    pub fn target_wpm(&self, test_type: &TestType, language: Language) -> Option<f64> {
+        match self {
+            Pace::Off => None,
+            Pace::Fixed(wpm) => Some(*wpm as f64),
+            Pace::Average | Pace::Best => {
+                let history = leaderboard::load_history(None).unwrap_or_default();
+                self.resolve(&history, test_type, language)
+            }
+        }
+    }
+
+    /// Target WPM from the given results; flagged ones don't count
+    pub fn resolve(&self, history: &[LeaderboardEntry], test_type: &TestType, language: Language) -> Option<f64> {
+        let matching: Vec<f64> = history
+            .iter()
+            .filter(|e| e.test_type == *test_type && e.language == language && e.flag.is_none())
+            .map(|e| e.wpm)
+            .collect();
+        match self {
+            Pace::Off => None,
+            Pace::Fixed(wpm) => Some(*wpm as f64),
+            _ if matching.is_empty() => None,
+            Pace::Average => Some(matching.iter().sum::<f64>() / matching.len() as f64),
+            Pace::Best => matching.into_iter().reduce(f64::max),
+        }
+    }
+}
+
+impl Display for Pace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pace::Off => write!(f, "Off"),
+            Pace::Average => write!(f, "My average"),
+            Pace::Best => write!(f, "Personal best"),
+            Pace::Fixed(wpm) => write!(f, "{} WPM", wpm),
+        }
+    }
+}
+
+/// Words the pace caret has covered after `elapsed`
+pub fn words_at(target_wpm: f64, elapsed: Duration) -> f64 {
+    target_wpm * elapsed.as_secs_f64() / 60.0
+}
+
+/// Char index of the pace caret after `words` words of `reference`, moving
+/// evenly through each word and its trailing space. `None` once it's past the end.
+pub fn caret_index(reference: &str, words: f64) -> Option<usize> {
+    if words < 0.0 {
+        return None;
+    }
+    let chars: Vec<char> = reference.chars().collect();
+    let starts: Vec<usize> = (0..chars.len())
+        .filter(|&i| chars[i] != ' ' && (i == 0 || chars[i - 1] == ' '))
+        .collect();
+
+    let word = words.floor() as usize;
+    let start = *starts.get(word)?;
+    let next = starts.get(word + 1).copied().unwrap_or(chars.len());
+    let index = start + ((next - start) as f64 * words.fract()) as usize;
+    (index < chars.len()).then_some(index)
+}
+
+/// Line for the results screen, e.g. "Pace 80 WPM: beaten by 4.5 WPM"
+pub fn verdict(wpm: f64, target_wpm: f64) -> String {
+    if wpm >= target_wpm {
+        format!("Pace {:.0} WPM: beaten by {:.1} WPM", target_wpm, wpm - target_wpm)
+    } else {
+        format!("Pace {:.0} WPM: missed by {:.1} WPM", target_wpm, target_wpm - wpm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anticheat::CheatFlag;
+
+    fn entry(wpm: f64, test_type: TestType) -> LeaderboardEntry {
+        LeaderboardEntry {
+            wpm,
+            accuracy: 95.0,
+            test_type,
+            test_mode: "time".to_string(),
+            word_count: 40,
+            test_duration: 30.0,
+            timestamp: "2025-09-11T10:00:00Z".to_string(),
+            language: Language::English,
+            flag: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_ignores_flagged_and_other_tests() {
+        let mut cheated = entry(250.0, TestType::Time(30));
+        cheated.flag = Some(CheatFlag::PasteBurst { chars: 30 });
+        let history = vec![
+            entry(60.0, TestType::Time(30)),
+            entry(80.0, TestType::Time(30)),
+            entry(120.0, TestType::Time(60)),
+            cheated,
+        ];
+
+        let time30 = TestType::Time(30);
+        assert_eq!(Pace::Average.resolve(&history, &time30, Language::English), Some(70.0));
+        assert_eq!(Pace::Best.resolve(&history, &time30, Language::English), Some(80.0));
+        assert_eq!(Pace::Best.resolve(&history, &TestType::Quote, Language::English), None);
+        assert_eq!(Pace::Fixed(90).resolve(&[], &time30, Language::English), Some(90.0));
+        assert_eq!(Pace::Off.resolve(&history, &time30, Language::English), None);
+    }
+
+    #[test]
+    fn test_caret_index() {
+        let reference = "one two three";
+        assert_eq!(caret_index(reference, 0.0), Some(0));
+        assert_eq!(caret_index(reference, 0.5), Some(2));
+        assert_eq!(caret_index(reference, 1.0), Some(4));
+        assert_eq!(caret_index(reference, 2.5), Some(10));
+        assert_eq!(caret_index(reference, 3.0), None);
+        // 60 WPM covers one word per second
+        assert_eq!(words_at(60.0, Duration::from_millis(2500)), 2.5);
+    }
+}
@@ -0,0 +1,156 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Environment variable holding one directory for both config and data
+pub const HOME_VAR: &str = "TYPEMAN_HOME";
+const APP_DIR: &str = "typeman";
+
+/// Files that only older versions kept next to the config
+const LEGACY_DATA: [&str; 3] = ["leaderboard.json", "typeman.db", "practice_results"];
+
+/// Directory given with --data-dir, which beats `TYPEMAN_HOME`
+static DATA_DIR_OVERRIDE: OnceLock<Mutex<Option<PathBuf>>> = OnceLock::new();
+
+fn data_dir_override() -> &'static Mutex<Option<PathBuf>> {
+    DATA_DIR_OVERRIDE.get_or_init(|| Mutex::new(None))
+}
+
+/// Keeps config and data of this process in `dir` (the --data-dir flag)
+pub fn set_data_dir(dir: PathBuf) {
+    if let Ok(mut data_dir) = data_dir_override().lock() {
+        *data_dir = Some(dir);
+    }
+}
+
+/// One directory for everything, if --data-dir or `TYPEMAN_HOME` asks for it
+fn single_root() -> Option<PathBuf> {
+    if let Some(dir) = data_dir_override().lock().ok().and_then(|dir| dir.clone()) {
+        return Some(dir);
+    }
+    std::env::var_os(HOME_VAR).filter(|dir| !dir.is_empty()).map(PathBuf::from)
+}
+
+fn home_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map_err(|_| "Unable to find home directory")?;
+    Ok(PathBuf::from(home))
+}
+
+/// `$<var>/typeman`, or `~/<fallback>/typeman` when the variable isn't set.
+/// Relative XDG paths are invalid and ignored, as the spec asks.
+fn xdg_dir(var: &str, fallback: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    match std::env::var_os(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => Ok(dir.join(APP_DIR)),
+        _ => Ok(home_dir()?.join(fallback).join(APP_DIR)),
+    }
+}
+
+fn create(dir: PathBuf) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Where the config of all profiles lives: `$XDG_CONFIG_HOME/typeman`
+pub fn config_root() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    match single_root() {
+        Some(dir) => create(dir),
+        None => create(xdg_dir("XDG_CONFIG_HOME", ".config")?),
+    }
+}
+
+/// Where results, practice progress and recorded runs of all profiles live:
+/// `$XDG_DATA_HOME/typeman`. Installs from before the split keep their data
+/// next to the config.
+pub fn data_root() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(dir) = single_root() {
+        return create(dir);
+    }
+    let data_dir = xdg_dir("XDG_DATA_HOME", ".local/share")?;
+    let config_dir = xdg_dir("XDG_CONFIG_HOME", ".config")?;
+    if !data_dir.exists() && has_legacy_data(&config_dir) {
+        return Ok(config_dir);
+    }
+    create(data_dir)
+}
+
+/// `path` with a leading `~` turned into the home directory, as a shell would
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => rest.trim_start_matches(['/', '\\']),
+        _ => return PathBuf::from(path),
+    };
+    match home_dir() {
+        Ok(home) => home.join(rest),
+        Err(_) => PathBuf::from(path),
+    }
+}
+
+fn has_legacy_data(dir: &Path) -> bool {
+    LEGACY_DATA.iter().any(|name| dir.join(name).exists())
+}
+
+/// A fresh directory that config and data go to until it's dropped, then it
+/// is removed and the override cleared. The override is global, so tests
+/// holding one run one at a time.
+#[cfg(test)]
+pub(crate) struct TempDataDir {
+    pub path: PathBuf,
+    _lock: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl TempDataDir {
+    pub fn new(name: &str) -> Self {
+        static LOCK: Mutex<()> = Mutex::new(());
+        // A failed test doesn't make the directory unusable for the next one
+        let lock = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let path = std::env::temp_dir().join(format!("typeman-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        set_data_dir(path.clone());
+        TempDataDir { path, _lock: lock }
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDataDir {
+    fn drop(&mut self) {
+        if let Ok(mut data_dir) = data_dir_override().lock() {
+            *data_dir = None;
+        }
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_data_is_detected() {
+        let dir = std::env::temp_dir().join(format!("typeman-paths-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        assert!(!has_legacy_data(&dir));
+
+        fs::write(dir.join("config.json"), "{}").unwrap();
+        assert!(!has_legacy_data(&dir));
+        fs::write(dir.join("leaderboard.json"), "[]").unwrap();
+        assert!(has_legacy_data(&dir));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_data_dir_holds_everything() {
+        let data_dir = TempDataDir::new("paths-override");
+        assert_eq!(config_root().unwrap(), data_dir.path);
+        assert_eq!(data_root().unwrap(), data_dir.path);
+
+        let path = data_dir.path.clone();
+        drop(data_dir);
+        assert!(!path.exists());
+        assert!(data_dir_override().lock().unwrap().is_none());
+    }
+}
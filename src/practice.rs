@@ -1,10 +1,8 @@
 use rand::prelude::IndexedRandom;
 
-use {
-    std::io::Write,
-    std::path::Path,
-    std::fs,
-};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::backend::{open_configured, Storage};
 
 pub const WPM_MIN: f64 = 35.0;
 
@@ -68,71 +66,51 @@ pub fn create_words(chars: &[char], word_number: usize) -> String {
     reference
 }
 
-pub fn save_results(time: f64, accuracy: f64, wpm: f64, level:usize) {
-    let results_dir = "practice_results";
-    fs::create_dir_all(results_dir).ok();
+/// One finished practice run
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PracticeResult {
+    pub time: f64,
+    pub accuracy: f64,
+    pub wpm: f64,
+}
 
-    let filename = format!("{}/level_{:?}.txt", results_dir, level);
-    let file_path = Path::new(&filename);
+pub fn save_results(time: f64, accuracy: f64, wpm: f64, level:usize) {
+    let result = PracticeResult { time, accuracy, wpm };
+    if let Err(e) = open_configured().and_then(|storage| storage.save_practice_result(level, &result)) {
+        eprintln!("Failed to save practice results: {}", e);
+    }
+}
 
-    let stats = format!(
-        "Time: {:.2}s\nAccuracy: {:.1}%\nWPM: {:.1}\n---\n",
-        time, accuracy, wpm
-    );
-    
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(file_path)
-        .unwrap();
-    file.write_all(stats.as_bytes()).unwrap();
+fn load_results(storage: &dyn Storage, level: usize) -> Vec<PracticeResult> {
+    storage.load_practice_results(level).unwrap_or_default()
 }
 
 pub fn get_prev_best_wpm(level: usize) -> f64 {
-    let results_path = format!("practice_results/level_{}.txt", level);
-    let contents = match fs::read_to_string(&results_path) {
-        Ok(c) if !c.trim().is_empty() => c,
-        _ => return 0.0,
+    let Ok(storage) = open_configured() else {
+        return 0.0;
     };
-    let mut best_wpm = 0.0;
-    for line in contents.lines() {
-        if line.starts_with("WPM:") {
-            if let Some(wpm_str) = line.strip_prefix("WPM:").map(str::trim) {
-                if let Ok(wpm) = wpm_str.parse::<f64>() {
-                    if wpm > best_wpm {
-                        best_wpm = wpm;
-                    }
-                }
-            }
-        }
-    }
-    best_wpm
+    load_results(storage.as_ref(), level)
+        .iter()
+        .map(|r| r.wpm)
+        .fold(0.0, f64::max)
 }
 
-pub fn check_if_completed(results_path: &str) -> bool {
-    if let Ok(contents) = std::fs::read_to_string(results_path) {
-        for line in contents.lines() {
-            if line.starts_with("WPM:") {
-                if let Some(wpm_str) = line.strip_prefix("WPM:").map(str::trim) {
-                    if let Ok(wpm) = wpm_str.parse::<f32>() {
-                        if wpm >= 35.0 {
-                            return true;
-                        }
-                    }
-                }
-            }
-        }
-    }
-    false
+pub fn check_if_completed(level: usize) -> bool {
+    completed_levels().get(level - 1).copied().unwrap_or(false)
+}
+
+/// Completion state of every level, reading the storage once
+pub fn completed_levels() -> Vec<bool> {
+    let storage = open_configured().ok();
+    (1..=TYPING_LEVELS.len())
+        .map(|level| {
+            storage.as_ref().is_some_and(|storage| {
+                load_results(storage.as_ref(), level).iter().any(|r| r.wpm >= WPM_MIN)
+            })
+        })
+        .collect()
 }
 
 pub fn get_first_not_done() -> usize {
-    for i in 0..TYPING_LEVELS.len() {
-        let results_path = format!("practice_results/level_{}.txt", i + 1);
-        let done = check_if_completed(results_path.as_str());
-        if !done {
-            return i;
-        }
-    }
-    1
+    completed_levels().iter().position(|done| !done).unwrap_or(1)
 }
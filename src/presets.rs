@@ -0,0 +1,258 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{AppConfig, Mode};
+use crate::language::Language;
+
+#[derive(Debug)]
+pub enum PresetError {
+    InvalidName(String),
+    /// No preset with that name; carries the names there are
+    NotFound(String, Vec<String>),
+    NoPresets,
+    SaveError(String),
+}
+
+impl Display for PresetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresetError::InvalidName(name) => write!(f, "invalid preset name {:?}", name),
+            PresetError::NotFound(name, names) if names.is_empty() => {
+                write!(f, "unknown preset '{}', there are no presets", name)
+            }
+            PresetError::NotFound(name, names) => {
+                write!(f, "unknown preset '{}', choose one of: {}", name, names.join(", "))
+            }
+            PresetError::NoPresets => write!(f, "there are no presets"),
+            PresetError::SaveError(e) => write!(f, "failed to save the config: {}", e),
+        }
+    }
+}
+
+/// Named test settings, applied all at once. Settings left out keep their
+/// current value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    pub mode: Mode,
+    #[serde(default)]
+    pub test_time: Option<u32>,
+    #[serde(default)]
+    pub word_number: Option<usize>,
+    #[serde(default)]
+    pub top_words: Option<usize>,
+    #[serde(default)]
+    pub punctuation: bool,
+    #[serde(default)]
+    pub numbers: bool,
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+impl Preset {
+    /// The test settings of a config, saved under `name`
+    pub fn from_config(name: &str, config: &AppConfig) -> Self {
+        Preset {
+            name: name.to_string(),
+            mode: config.mode,
+            test_time: (config.mode == Mode::Time).then_some(config.test_time as u32),
+            word_number: (config.mode == Mode::Words).then_some(config.word_number),
+            top_words: matches!(config.mode, Mode::Time | Mode::Words).then_some(config.top_words),
+            punctuation: config.punctuation,
+            numbers: config.numbers,
+            language: Some(config.language),
+        }
+    }
+
+    pub fn apply(&self, config: &mut AppConfig) {
+        config.mode = self.mode;
+        if let Some(secs) = self.test_time {
+            config.test_time = secs as f32;
+        }
+        if let Some(words) = self.word_number {
+            config.word_number = words;
+        }
+        if let Some(words) = self.top_words {
+            config.top_words = words;
+        }
+        config.punctuation = self.punctuation;
+        config.numbers = self.numbers;
+        if let Some(language) = self.language {
+            config.language = language;
+        }
+        config.preset = Some(self.name.clone());
+    }
+
+    /// Whether the config still has the settings of this preset
+    pub fn matches(&self, config: &AppConfig) -> bool {
+        config.mode == self.mode
+            && self.test_time.is_none_or(|secs| config.test_time == secs as f32)
+            && self.word_number.is_none_or(|words| config.word_number == words)
+            && self.top_words.is_none_or(|words| config.top_words == words)
+            && config.punctuation == self.punctuation
+            && config.numbers == self.numbers
+            && self.language.is_none_or(|language| config.language == language)
+    }
+}
+
+/// e.g. "exam: 60s punct+digits ita"
+impl Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.name)?;
+        match (self.mode, self.test_time, self.word_number) {
+            (Mode::Time, Some(secs), _) => write!(f, " {}s", secs)?,
+            (Mode::Words, _, Some(words)) => write!(f, " {}w", words)?,
+            (mode, _, _) => write!(f, " {}", mode)?,
+        }
+        if let Some(words) = self.top_words {
+            write!(f, " top{}", words)?;
+        }
+        match (self.punctuation, self.numbers) {
+            (true, true) => write!(f, " punct+digits")?,
+            (true, false) => write!(f, " punct")?,
+            (false, true) => write!(f, " digits")?,
+            (false, false) => {}
+        }
+        if let Some(language) = self.language {
+            let short: String = language.to_string().to_lowercase().chars().take(3).collect();
+            write!(f, " {}", short)?;
+        }
+        Ok(())
+    }
+}
+
+/// Presets a new config starts with
+pub fn defaults() -> Vec<Preset> {
+    vec![
+        Preset {
+            name: "warmup".to_string(),
+            mode: Mode::Time,
+            test_time: Some(15),
+            word_number: None,
+            top_words: Some(200),
+            punctuation: false,
+            numbers: false,
+            language: None,
+        },
+        Preset {
+            name: "exam".to_string(),
+            mode: Mode::Time,
+            test_time: Some(60),
+            word_number: None,
+            top_words: None,
+            punctuation: true,
+            numbers: true,
+            language: Some(Language::Italian),
+        },
+    ]
+}
+
+/// A preset by name, ignoring case
+pub fn find<'a>(presets: &'a [Preset], name: &str) -> Option<&'a Preset> {
+    presets.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// The preset after `current`, wrapping around; the first one if none was applied
+pub fn next<'a>(presets: &'a [Preset], current: Option<&str>) -> Option<&'a Preset> {
+    let index = current
+        .and_then(|name| presets.iter().position(|p| p.name.eq_ignore_ascii_case(name)))
+        .map_or(0, |i| (i + 1) % presets.len().max(1));
+    presets.get(index)
+}
+
+/// Names are typed on the command line, so they stay short and simple
+pub fn validate_name(name: &str) -> Result<(), PresetError> {
+    crate::profile::validate_name(name).map_err(|_| PresetError::InvalidName(name.to_string()))
+}
+
+fn save(config: &AppConfig) -> Result<(), PresetError> {
+    config.save().map_err(|e| PresetError::SaveError(e.to_string()))
+}
+
+/// Applies a preset of the active profile and saves the config
+pub fn apply_named(name: &str) -> Result<AppConfig, PresetError> {
+    let mut config = AppConfig::load();
+    let preset = find(&config.presets, name)
+        .cloned()
+        .ok_or_else(|| PresetError::NotFound(name.to_string(), config.presets.iter().map(|p| p.name.clone()).collect()))?;
+    preset.apply(&mut config);
+    save(&config)?;
+    Ok(config)
+}
+
+/// Applies the preset after the last applied one and saves the config
+pub fn cycle() -> Result<AppConfig, PresetError> {
+    let mut config = AppConfig::load();
+    let preset = next(&config.presets, config.preset.as_deref()).cloned().ok_or(PresetError::NoPresets)?;
+    preset.apply(&mut config);
+    save(&config)?;
+    Ok(config)
+}
+
+/// Saves the current test settings as a preset, replacing one with the same name
+pub fn save_current(name: &str) -> Result<Preset, PresetError> {
+    validate_name(name)?;
+    let mut config = AppConfig::load();
+    let preset = Preset::from_config(name, &config);
+    match config.presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(name)) {
+        Some(existing) => *existing = preset.clone(),
+        None => config.presets.push(preset.clone()),
+    }
+    config.preset = Some(name.to_string());
+    save(&config)?;
+    Ok(preset)
+}
+
+pub fn remove(name: &str) -> Result<(), PresetError> {
+    let mut config = AppConfig::load();
+    let before = config.presets.len();
+    config.presets.retain(|p| !p.name.eq_ignore_ascii_case(name));
+    if config.presets.len() == before {
+        return Err(PresetError::NotFound(name.to_string(), config.presets.iter().map(|p| p.name.clone()).collect()));
+    }
+    if config.preset.as_deref().is_some_and(|current| current.eq_ignore_ascii_case(name)) {
+        config.preset = None;
+    }
+    save(&config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_and_describe() {
+        let presets = defaults();
+        assert_eq!(presets[0].to_string(), "warmup: 15s top200");
+        assert_eq!(presets[1].to_string(), "exam: 60s punct+digits ita");
+
+        let mut config = AppConfig { mode: Mode::Quote, top_words: 1000, ..AppConfig::default() };
+        find(&presets, "EXAM").unwrap().apply(&mut config);
+        assert_eq!(config.mode, Mode::Time);
+        assert_eq!(config.test_time, 60.0);
+        assert!(config.punctuation && config.numbers);
+        assert_eq!(config.language, Language::Italian);
+        // Left out of the preset, so unchanged
+        assert_eq!(config.top_words, 1000);
+        assert_eq!(config.preset.as_deref(), Some("exam"));
+        assert!(presets[1].matches(&config));
+        config.numbers = false;
+        assert!(!presets[1].matches(&config));
+
+        let words = Preset::from_config("long", &AppConfig { mode: Mode::Words, word_number: 100, ..AppConfig::default() });
+        assert_eq!(words.to_string(), "long: 100w top500 eng");
+        let indonesian = Preset { language: Some(Language::Indonesian), ..words };
+        assert_eq!(indonesian.to_string(), "long: 100w top500 ind");
+    }
+
+    #[test]
+    fn test_next_wraps_around() {
+        let presets = defaults();
+        assert_eq!(next(&presets, None).unwrap().name, "warmup");
+        assert_eq!(next(&presets, Some("warmup")).unwrap().name, "exam");
+        assert_eq!(next(&presets, Some("exam")).unwrap().name, "warmup");
+        assert_eq!(next(&presets, Some("gone")).unwrap().name, "warmup");
+        assert!(next(&[], None).is_none());
+    }
+}
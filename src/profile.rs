@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::AppConfig;
+use crate::leaderboard::LeaderboardEntry;
+use crate::paths;
+use crate::storage::backend::open_storage_in;
+
+/// Profile that lives directly in the config and data roots, so existing data keeps working
+pub const DEFAULT_PROFILE: &str = "default";
+
+const MAX_NAME_LEN: usize = 32;
+
+#[derive(Debug)]
+pub enum ProfileError {
+    InvalidName(String),
+    IoError(std::io::Error),
+    ConfigDirError(String),
+}
+
+impl std::fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileError::InvalidName(name) => write!(
+                f,
+                "invalid profile name {:?}, use up to {} letters, digits, '-' or '_'",
+                name, MAX_NAME_LEN
+            ),
+            ProfileError::IoError(e) => write!(f, "{}", e),
+            ProfileError::ConfigDirError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ProfileError {
+    fn from(error: std::io::Error) -> Self {
+        ProfileError::IoError(error)
+    }
+}
+
+/// Profile chosen for this session (--profile or the switcher), overrides the saved one
+static ACTIVE_PROFILE: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn active_override() -> &'static Mutex<Option<String>> {
+    ACTIVE_PROFILE.get_or_init(|| Mutex::new(None))
+}
+
+fn active_file() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(paths::config_root()?.join("active_profile"))
+}
+
+/// Directory of a profile under one of the roots
+fn dir_in(root: PathBuf, name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = if name == DEFAULT_PROFILE {
+        root
+    } else {
+        root.join("profiles").join(name)
+    };
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Directory holding the config of a profile
+pub fn config_dir(name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    dir_in(paths::config_root()?, name)
+}
+
+/// Directory holding results, practice progress and recorded runs of a profile
+pub fn data_dir(name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    dir_in(paths::data_root()?, name)
+}
+
+/// Config directory of the active profile
+pub fn current_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    config_dir(&active())
+}
+
+/// Data directory of the active profile
+pub fn current_data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    data_dir(&active())
+}
+
+pub fn validate_name(name: &str) -> Result<(), ProfileError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ProfileError::InvalidName(name.to_string()))
+    }
+}
+
+pub fn active() -> String {
+    if let Ok(active) = active_override().lock() {
+        if let Some(name) = active.as_ref() {
+            return name.clone();
+        }
+    }
+
+    active_file()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| validate_name(name).is_ok())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// Uses (and creates if needed) a profile for this session only
+pub fn use_profile(name: &str) -> Result<(), ProfileError> {
+    validate_name(name)?;
+    config_dir(name)
+        .and_then(|_| data_dir(name))
+        .map_err(|e| ProfileError::ConfigDirError(e.to_string()))?;
+    if let Ok(mut active) = active_override().lock() {
+        *active = Some(name.to_string());
+    }
+    Ok(())
+}
+
+/// Switches to a profile and remembers it for the next start
+pub fn set_active(name: &str) -> Result<(), ProfileError> {
+    use_profile(name)?;
+    let path = active_file().map_err(|e| ProfileError::ConfigDirError(e.to_string()))?;
+    fs::write(path, name)?;
+    Ok(())
+}
+
+/// All profiles, the default one first
+pub fn list() -> Vec<String> {
+    let mut names: Vec<String> = [paths::config_root(), paths::data_root()]
+        .into_iter()
+        .filter_map(|root| root.ok())
+        .filter_map(|root| fs::read_dir(root.join("profiles")).ok())
+        .flat_map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| validate_name(name).is_ok() && name != DEFAULT_PROFILE)
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    names
+}
+
+/// Best results of every profile, tagged with the profile name, highest WPM first
+pub fn combined_entries() -> Vec<(String, LeaderboardEntry)> {
+    let mut combined = Vec::new();
+    for name in list() {
+        let (Ok(config_dir), Ok(data_dir)) = (config_dir(&name), data_dir(&name)) else {
+            continue;
+        };
+        let backend = AppConfig::load_from(&config_dir).storage_backend;
+        let entries = open_storage_in(backend, &data_dir)
+            .and_then(|storage| storage.load_entries())
+            .unwrap_or_default();
+        combined.extend(entries.into_iter().map(|entry| (name.clone(), entry)));
+    }
+    combined.sort_by(|a, b| b.1.wpm.partial_cmp(&a.1.wpm).unwrap_or(std::cmp::Ordering::Equal));
+    combined.truncate(100);
+    combined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("alice").is_ok());
+        assert!(validate_name("team-2_b").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name("a b").is_err());
+        assert!(validate_name(&"x".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,741 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::anticheat;
+use crate::language::Language;
+use crate::leaderboard::{self, LeaderboardEntry, TestType};
+
+pub const DEFAULT_PORT: u16 = 7878;
+pub const MAX_PLAYERS: usize = 8;
+/// Time between the host pressing start and the race starting
+pub const COUNTDOWN: Duration = Duration::from_secs(3);
+/// How often a player's progress is sent to the others
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+/// A player whose connection takes longer than this to accept a message is
+/// dropped, so one stalled client can't hold up the race for everyone
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// One line of JSON on the wire. Players send `Join`, `Progress` and
+/// `Finished`; the host answers with everything else.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RaceMessage {
+    Join { name: String },
+    Progress { typed: usize, wpm: f64 },
+    Finished { wpm: f64, accuracy: f64, time: f64 },
+    Welcome { id: usize },
+    Lobby { players: Vec<String> },
+    Start { text: String, countdown_ms: u64 },
+    Update { players: Vec<PlayerProgress> },
+    Standings { standings: Vec<Standing> },
+    Refused { reason: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlayerProgress {
+    pub id: usize,
+    pub name: String,
+    /// Share of the text typed, 0.0 - 1.0
+    pub progress: f64,
+    pub wpm: f64,
+    pub finished: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub id: usize,
+    pub name: String,
+    pub place: usize,
+    pub wpm: f64,
+    pub accuracy: f64,
+    pub time: f64,
+    pub finished: bool,
+}
+
+fn to_line(message: &RaceMessage) -> Vec<u8> {
+    let mut line = serde_json::to_vec(message).unwrap_or_default();
+    line.push(b'\n');
+    line
+}
+
+struct Player {
+    name: String,
+    writer: TcpStream,
+    typed: usize,
+    wpm: f64,
+    /// wpm, accuracy and time once the player is through
+    result: Option<(f64, f64, f64)>,
+    connected: bool,
+}
+
+#[derive(Default)]
+struct HostState {
+    players: Vec<Player>,
+    text_len: usize,
+    started: bool,
+    over: bool,
+}
+
+impl HostState {
+    fn broadcast(&mut self, message: &RaceMessage) {
+        let line = to_line(message);
+        for player in self.players.iter_mut().filter(|p| p.connected) {
+            if player.writer.write_all(&line).is_err() {
+                player.connected = false;
+                // Also ends the player's reader thread
+                let _ = player.writer.shutdown(Shutdown::Both);
+            }
+        }
+    }
+
+    fn lobby(&self) -> RaceMessage {
+        RaceMessage::Lobby {
+            players: self.players.iter().filter(|p| p.connected).map(|p| p.name.clone()).collect(),
+        }
+    }
+
+    fn update(&self) -> RaceMessage {
+        let players = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.connected || p.result.is_some())
+            .map(|(id, p)| PlayerProgress {
+                id,
+                name: p.name.clone(),
+                progress: if self.text_len == 0 { 0.0 } else { p.typed as f64 / self.text_len as f64 },
+                wpm: p.result.map_or(p.wpm, |(wpm, _, _)| wpm),
+                finished: p.result.is_some(),
+            })
+            .collect();
+        RaceMessage::Update { players }
+    }
+
+    /// Finishers by time, then everyone else by how far they got
+    fn standings(&self) -> Vec<Standing> {
+        let mut order: Vec<usize> = (0..self.players.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&self.players[a], &self.players[b]);
+            match (a.result, b.result) {
+                (Some(ra), Some(rb)) => ra.2.partial_cmp(&rb.2).unwrap_or(std::cmp::Ordering::Equal),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => b.typed.cmp(&a.typed),
+            }
+        });
+        order
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let player = &self.players[id];
+                let (wpm, accuracy, time) = player.result.unwrap_or((player.wpm, 0.0, 0.0));
+                Standing {
+                    id,
+                    name: player.name.clone(),
+                    place: i + 1,
+                    wpm,
+                    accuracy,
+                    time,
+                    finished: player.result.is_some(),
+                }
+            })
+            .collect()
+    }
+
+    /// Sends the standings once every player still connected is through
+    fn finish_if_done(&mut self) {
+        if self.started && !self.over && self.players.iter().all(|p| !p.connected || p.result.is_some()) {
+            self.over = true;
+            let standings = RaceMessage::Standings { standings: self.standings() };
+            self.broadcast(&standings);
+        }
+    }
+
+    fn unique_name(&self, name: &str) -> String {
+        let base = name.trim();
+        let base = if base.is_empty() { "player" } else { base };
+        let mut candidate = base.to_string();
+        let mut n = 2;
+        while self.players.iter().any(|p| p.connected && p.name == candidate) {
+            candidate = format!("{} ({})", base, n);
+            n += 1;
+        }
+        candidate
+    }
+}
+
+/// Relays a race between the players connected to it; the host's own player
+/// joins over localhost like everyone else
+pub struct RaceHost {
+    addr: SocketAddr,
+    state: Arc<Mutex<HostState>>,
+}
+
+impl RaceHost {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(HostState::default()));
+
+        let accept_state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = Arc::clone(&accept_state);
+                std::thread::spawn(move || serve_player(stream, state));
+            }
+        });
+
+        Ok(RaceHost { addr, state })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.state.lock().map_or(0, |state| state.players.iter().filter(|p| p.connected).count())
+    }
+
+    /// Sends the text to everyone and starts the countdown
+    pub fn begin(&self, text: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            if state.started {
+                return;
+            }
+            state.started = true;
+            state.text_len = text.chars().count();
+            state.broadcast(&RaceMessage::Start {
+                text: text.to_string(),
+                countdown_ms: COUNTDOWN.as_millis() as u64,
+            });
+        }
+    }
+}
+
+fn serve_player(stream: TcpStream, state: Arc<Mutex<HostState>>) {
+    let Ok(writer) = stream.try_clone() else {
+        return;
+    };
+    if writer.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+        return;
+    }
+    let mut lines = BufReader::new(stream).lines();
+
+    let Some(Ok(RaceMessage::Join { name })) =
+        lines.next().and_then(|line| line.ok()).map(|line| serde_json::from_str(&line))
+    else {
+        return;
+    };
+
+    let id = {
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        let refused = if state.started {
+            Some("the race has already started")
+        } else if state.players.iter().filter(|p| p.connected).count() >= MAX_PLAYERS {
+            Some("the race is full")
+        } else {
+            None
+        };
+        if let Some(reason) = refused {
+            let mut writer = writer;
+            let _ = writer.write_all(&to_line(&RaceMessage::Refused { reason: reason.to_string() }));
+            return;
+        }
+
+        let id = state.players.len();
+        let name = state.unique_name(&name);
+        let mut player = Player { name, writer, typed: 0, wpm: 0.0, result: None, connected: true };
+        player.connected = player.writer.write_all(&to_line(&RaceMessage::Welcome { id })).is_ok();
+        state.players.push(player);
+        let lobby = state.lobby();
+        state.broadcast(&lobby);
+        id
+    };
+
+    for line in lines {
+        let Ok(line) = line else {
+            break;
+        };
+        let Ok(message) = serde_json::from_str::<RaceMessage>(&line) else {
+            continue;
+        };
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        if !state.started || state.over {
+            continue;
+        }
+        let text_len = state.text_len;
+        match message {
+            RaceMessage::Progress { typed, wpm } => {
+                let player = &mut state.players[id];
+                if player.result.is_none() {
+                    player.typed = typed.min(text_len);
+                    player.wpm = wpm;
+                }
+            }
+            RaceMessage::Finished { wpm, accuracy, time } => {
+                let player = &mut state.players[id];
+                if player.result.is_none() {
+                    player.typed = text_len;
+                    player.result = Some((wpm, accuracy, time));
+                }
+            }
+            _ => continue,
+        }
+        let update = state.update();
+        state.broadcast(&update);
+        state.finish_if_done();
+    }
+
+    if let Ok(mut state) = state.lock() {
+        state.players[id].connected = false;
+        if state.started {
+            let update = state.update();
+            state.broadcast(&update);
+            state.finish_if_done();
+        } else {
+            let lobby = state.lobby();
+            state.broadcast(&lobby);
+        }
+    }
+}
+
+/// Connection of one player to a race host
+pub struct RaceClient {
+    writer: TcpStream,
+    incoming: Receiver<RaceMessage>,
+    connected: bool,
+}
+
+impl RaceClient {
+    pub fn connect(addr: impl ToSocketAddrs, name: &str) -> io::Result<Self> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to"))?;
+        let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(5))?;
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+
+        let (sender, incoming) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if let Ok(message) = serde_json::from_str(&line) {
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let mut client = RaceClient { writer: stream, incoming, connected: true };
+        client.send(&RaceMessage::Join { name: name.to_string() })?;
+        Ok(client)
+    }
+
+    pub fn send(&mut self, message: &RaceMessage) -> io::Result<()> {
+        self.writer.write_all(&to_line(message))
+    }
+
+    /// Messages received since the last call
+    pub fn poll(&mut self) -> Vec<RaceMessage> {
+        let mut messages = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+        messages
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<RaceMessage> {
+        self.incoming.recv_timeout(timeout).ok()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+impl Drop for RaceClient {
+    fn drop(&mut self) {
+        // The reader thread holds a clone of the socket, so close it explicitly
+        let _ = self.writer.shutdown(Shutdown::Both);
+    }
+}
+
+/// Typing state of one racer. The clock runs from the start signal, not the
+/// first keystroke, so everybody is timed the same way.
+pub struct RaceTyping {
+    pub reference: Vec<char>,
+    pub typed: Vec<char>,
+    keystrokes: usize,
+    mistakes: usize,
+    started: Instant,
+    finished_after: Option<Duration>,
+    keystroke_times: Vec<f64>,
+}
+
+impl RaceTyping {
+    pub fn new(text: &str, started: Instant) -> Self {
+        RaceTyping {
+            reference: text.chars().collect(),
+            typed: Vec::new(),
+            keystrokes: 0,
+            mistakes: 0,
+            started,
+            finished_after: None,
+            keystroke_times: Vec::new(),
+        }
+    }
+
+    /// Nothing past the end of the text is taken; the race is only over once
+    /// the whole text matches, so mistakes have to be fixed first
+    pub fn type_char(&mut self, c: char) {
+        if self.is_finished() || self.typed.len() >= self.reference.len() {
+            return;
+        }
+        let elapsed = self.started.elapsed();
+        self.keystrokes += 1;
+        self.keystroke_times.push(elapsed.as_secs_f64() * 1000.0);
+        if self.reference.get(self.typed.len()) != Some(&c) {
+            self.mistakes += 1;
+        }
+        self.typed.push(c);
+        if self.typed == self.reference {
+            self.finished_after = Some(elapsed);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if !self.is_finished() {
+            self.typed.pop();
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished_after.is_some()
+    }
+
+    /// Whether the character at `index` was typed correctly, `None` if not typed yet
+    pub fn is_correct(&self, index: usize) -> Option<bool> {
+        self.typed.get(index).map(|c| self.reference.get(index) == Some(c))
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.finished_after.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    fn correct_chars(&self) -> usize {
+        self.typed.iter().zip(&self.reference).filter(|(a, b)| a == b).count()
+    }
+
+    pub fn wpm(&self) -> f64 {
+        let minutes = self.elapsed().as_secs_f64() / 60.0;
+        if minutes <= 0.0 {
+            return 0.0;
+        }
+        (self.correct_chars() as f64 / 5.0) / minutes
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        if self.keystrokes == 0 {
+            return 0.0;
+        }
+        (self.keystrokes - self.mistakes) as f64 / self.keystrokes as f64 * 100.0
+    }
+
+    /// Keystrokes per minute for every second typed so far
+    pub fn speed_per_second(&self) -> Vec<f64> {
+        let seconds = self.elapsed().as_secs_f64().ceil() as usize;
+        let mut speeds = vec![0.0; seconds];
+        for ms in &self.keystroke_times {
+            let second = (*ms / 1000.0) as usize;
+            if let Some(speed) = speeds.get_mut(second.min(seconds.saturating_sub(1))) {
+                *speed += 60.0;
+            }
+        }
+        speeds
+    }
+
+    pub fn progress(&self) -> f64 {
+        if self.reference.is_empty() {
+            return 0.0;
+        }
+        self.typed.len() as f64 / self.reference.len() as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RacePhase {
+    Lobby,
+    /// Counting down to the given start time
+    Countdown(Instant),
+    Racing,
+    /// Through, waiting for the others
+    Waiting,
+    Done,
+    /// Lost the host, or was refused
+    Disconnected(String),
+}
+
+/// Everything a frontend needs to show a race: call `tick` every frame, pass
+/// keys to `type_char`/`backspace`, and draw from the public fields
+pub struct RaceSession {
+    pub host: Option<RaceHost>,
+    pub phase: RacePhase,
+    pub players: Vec<String>,
+    pub progress: Vec<PlayerProgress>,
+    pub standings: Vec<Standing>,
+    pub typing: Option<RaceTyping>,
+    /// Result of saving to the leaderboard, shown with the standings
+    pub message: Option<String>,
+    id: Option<usize>,
+    client: RaceClient,
+    text: String,
+    language: Language,
+    last_progress: Instant,
+}
+
+impl RaceSession {
+    /// Hosts a race on `addr` with the given text and joins it as `name`
+    pub fn host(addr: &str, name: &str, text: String, language: Language) -> io::Result<Self> {
+        let host = RaceHost::bind(addr)?;
+        let local = SocketAddr::from(([127, 0, 0, 1], host.local_addr().port()));
+        let mut session = Self::join(local, name, language)?;
+        session.host = Some(host);
+        session.text = text;
+        Ok(session)
+    }
+
+    pub fn join(addr: impl ToSocketAddrs, name: &str, language: Language) -> io::Result<Self> {
+        Ok(RaceSession {
+            host: None,
+            phase: RacePhase::Lobby,
+            players: Vec::new(),
+            progress: Vec::new(),
+            standings: Vec::new(),
+            typing: None,
+            message: None,
+            id: None,
+            client: RaceClient::connect(addr, name)?,
+            text: String::new(),
+            language,
+            last_progress: Instant::now(),
+        })
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.host.is_some()
+    }
+
+    pub fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    /// Host only: starts the countdown for everyone in the lobby
+    pub fn start(&self) {
+        if let (Some(host), RacePhase::Lobby) = (&self.host, &self.phase) {
+            host.begin(&self.text);
+        }
+    }
+
+    pub fn countdown_left(&self) -> Option<Duration> {
+        match self.phase {
+            RacePhase::Countdown(go) => Some(go.saturating_duration_since(Instant::now())),
+            _ => None,
+        }
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        if self.phase == RacePhase::Racing {
+            if let Some(typing) = self.typing.as_mut() {
+                typing.type_char(c);
+            }
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.phase == RacePhase::Racing {
+            if let Some(typing) = self.typing.as_mut() {
+                typing.backspace();
+            }
+        }
+    }
+
+    /// Handles messages from the host, moves through the phases and reports progress
+    pub fn tick(&mut self) {
+        for message in self.client.poll() {
+            match message {
+                RaceMessage::Welcome { id } => self.id = Some(id),
+                RaceMessage::Lobby { players } => self.players = players,
+                RaceMessage::Start { text, countdown_ms } => {
+                    let go = Instant::now() + Duration::from_millis(countdown_ms);
+                    self.typing = Some(RaceTyping::new(&text, go));
+                    self.phase = RacePhase::Countdown(go);
+                }
+                RaceMessage::Update { players } => self.progress = players,
+                RaceMessage::Standings { standings } => {
+                    self.standings = standings;
+                    self.phase = RacePhase::Done;
+                    self.save_result();
+                }
+                RaceMessage::Refused { reason } => self.phase = RacePhase::Disconnected(reason),
+                _ => {}
+            }
+        }
+
+        if let RacePhase::Countdown(go) = self.phase {
+            if Instant::now() >= go {
+                self.phase = RacePhase::Racing;
+            }
+        }
+
+        if self.phase == RacePhase::Racing {
+            if let Some(typing) = self.typing.as_ref() {
+                let message = if typing.is_finished() {
+                    self.phase = RacePhase::Waiting;
+                    Some(RaceMessage::Finished {
+                        wpm: typing.wpm(),
+                        accuracy: typing.accuracy(),
+                        time: typing.elapsed().as_secs_f64(),
+                    })
+                } else if self.last_progress.elapsed() >= PROGRESS_INTERVAL {
+                    self.last_progress = Instant::now();
+                    Some(RaceMessage::Progress { typed: typing.typed.len(), wpm: typing.wpm() })
+                } else {
+                    None
+                };
+                if let Some(message) = message {
+                    let _ = self.client.send(&message);
+                }
+            }
+        }
+
+        if !self.client.is_connected() && !matches!(self.phase, RacePhase::Done | RacePhase::Disconnected(_)) {
+            self.phase = RacePhase::Disconnected("lost connection to the host".to_string());
+        }
+    }
+
+    /// Saves this player's finish to the local (and team) leaderboard
+    fn save_result(&mut self) {
+        let (Some(id), Some(typing)) = (self.id, self.typing.as_ref()) else {
+            return;
+        };
+        let Some(standing) = self.standings.iter().find(|s| s.id == id && s.finished) else {
+            return;
+        };
+
+        let text: String = typing.reference.iter().collect();
+        let entry = LeaderboardEntry {
+            wpm: standing.wpm,
+            accuracy: standing.accuracy,
+            test_type: TestType::Race(standing.place),
+            test_mode: "race".to_string(),
+            word_count: text.split_whitespace().count(),
+            test_duration: standing.time,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            language: self.language,
+            flag: anticheat::analyze(&typing.keystroke_times),
+        };
+
+        self.message = Some(match leaderboard::save_entry(&entry) {
+            Ok(()) => {
+                let _ = crate::team::publish_configured(&entry);
+                format!("Result saved: place {} of {}", standing.place, self.standings.len())
+            }
+            Err(e) => format!("Failed to save the result: {}", e),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_race_typing() {
+        let mut typing = RaceTyping::new("ab cd", Instant::now() - Duration::from_secs(6));
+        for c in "ax".chars() {
+            typing.type_char(c);
+        }
+        assert_eq!(typing.is_correct(1), Some(false));
+        typing.backspace();
+        for c in "b cd".chars() {
+            typing.type_char(c);
+        }
+        assert!(typing.is_finished());
+        assert_eq!(typing.progress(), 1.0);
+        assert!((typing.accuracy() - 83.3).abs() < 0.1);
+        // 5 correct chars in ~6 seconds
+        assert!((typing.wpm() - 10.0).abs() < 0.5);
+        // all six keystrokes landed in the last second
+        assert_eq!(typing.speed_per_second().last(), Some(&360.0));
+    }
+
+    /// Both ends of a localhost connection, for players in host state tests
+    fn socket() -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        listener.accept().unwrap();
+        stream
+    }
+
+    #[test]
+    fn test_wrong_text_does_not_finish() {
+        let start = Instant::now() - Duration::from_secs(10);
+        let mut masher = RaceTyping::new("ab cd", start);
+        for c in "xxxxxxxx".chars() {
+            masher.type_char(c);
+        }
+        assert!(!masher.is_finished());
+        assert_eq!(masher.typed.len(), 5);
+
+        let mut typist = RaceTyping::new("ab cd", start);
+        for c in "ab cd".chars() {
+            typist.type_char(c);
+        }
+        assert!(typist.is_finished());
+
+        // The masher got to the end first, but only the correct text counts as through
+        let player = |name: &str, typing: &RaceTyping| Player {
+            name: name.to_string(),
+            writer: socket(),
+            typed: typing.typed.len(),
+            wpm: typing.wpm(),
+            result: typing.is_finished().then(|| (typing.wpm(), typing.accuracy(), typing.elapsed().as_secs_f64())),
+            connected: true,
+        };
+        let state = HostState {
+            players: vec![player("masher", &masher), player("typist", &typist)],
+            text_len: 5,
+            started: true,
+            over: false,
+        };
+        let standings = state.standings();
+        assert_eq!((standings[0].name.as_str(), standings[0].finished), ("typist", true));
+        assert_eq!((standings[1].name.as_str(), standings[1].finished), ("masher", false));
+    }
+
+    #[test]
+    fn test_message_format() {
+        let line = to_line(&RaceMessage::Progress { typed: 12, wpm: 80.5 });
+        assert_eq!(String::from_utf8(line).unwrap(), "{\"type\":\"progress\",\"typed\":12,\"wpm\":80.5}\n");
+    }
+}
@@ -0,0 +1,205 @@
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::leaderboard::LeaderboardError;
+use crate::team::{self, Submission};
+
+/// Uploads bigger than this are refused (a full history is well below it)
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+const DEFAULT_LIMIT: usize = 100;
+/// Environment variable `typeman serve` reads the shared token from
+pub const TOKEN_VAR: &str = "TYPEMAN_SERVER_TOKEN";
+
+#[derive(Debug)]
+pub enum ServerError {
+    BindError(String),
+    IoError(std::io::Error),
+}
+
+impl std::fmt::Display for ServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerError::BindError(e) => write!(f, "cannot listen there: {}", e),
+            ServerError::IoError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ServerError {
+    fn from(error: std::io::Error) -> Self {
+        ServerError::IoError(error)
+    }
+}
+
+/// Self-hosted leaderboard: accepts result uploads and serves rankings as JSON.
+///
+/// Results are stored the same way as a synced team folder, so uploads get the
+/// same locking, duplicate and conflict handling.
+///
+/// - `GET  /api/health`
+/// - `GET  /api/leaderboard?limit=N&mode=time&best=true`
+/// - `POST /api/results` with a `Submission` body
+///
+/// With a token set, uploads must send it as `Authorization: Bearer <token>`;
+/// the leaderboard stays readable without it.
+pub struct LeaderboardServer {
+    server: Server,
+    data_dir: PathBuf,
+    token: Option<String>,
+}
+
+impl LeaderboardServer {
+    pub fn bind(addr: &str, data_dir: &Path) -> Result<Self, ServerError> {
+        std::fs::create_dir_all(data_dir)?;
+        let server = Server::http(addr).map_err(|e| ServerError::BindError(e.to_string()))?;
+        Ok(LeaderboardServer { server, data_dir: data_dir.to_path_buf(), token: None })
+    }
+
+    /// Only accepts uploads that carry this token
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token.filter(|token| !token.is_empty());
+        self
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Serves requests until the process exits
+    pub fn run(&self) {
+        for mut request in self.server.incoming_requests() {
+            let (status, body) = self.handle(&mut request);
+            let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+            let response = Response::from_string(body).with_status_code(status).with_header(header);
+            let _ = request.respond(response);
+        }
+    }
+
+    fn handle(&self, request: &mut Request) -> (u16, String) {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+        match (request.method(), path) {
+            (Method::Get, "/api/health") => (200, r#"{"status":"ok"}"#.to_string()),
+            (Method::Get, "/api/leaderboard") => {
+                let param = |name: &str| {
+                    query
+                        .split('&')
+                        .filter_map(|pair| pair.split_once('='))
+                        .find(|(key, _)| percent_decode(key) == name)
+                        .map(|(_, value)| percent_decode(value))
+                };
+                let limit = param("limit").and_then(|l| l.parse().ok()).unwrap_or(DEFAULT_LIMIT);
+                let best_only = param("best").map_or(false, |b| b == "true" || b == "1");
+                let mode = param("mode");
+
+                match team::load(&self.data_dir) {
+                    Ok(entries) => {
+                        let ranked = team::ranked(entries, mode.as_deref(), best_only, limit);
+                        json_response(200, &ranked)
+                    }
+                    Err(e) => error_response(500, &e.to_string()),
+                }
+            }
+            (Method::Post, "/api/results") => {
+                if !self.authorized(request) {
+                    return error_response(401, "missing or wrong token");
+                }
+                let mut body = String::new();
+                let read = request.as_reader().take(MAX_BODY_BYTES + 1).read_to_string(&mut body);
+                if read.is_err() || body.len() as u64 > MAX_BODY_BYTES {
+                    return error_response(413, "request body too large or unreadable");
+                }
+                let submission: Submission = match serde_json::from_str(&body) {
+                    Ok(submission) => submission,
+                    Err(e) => return error_response(400, &e.to_string()),
+                };
+
+                match team::publish(&self.data_dir, &submission.player, &submission.entries) {
+                    Ok(summary) => json_response(200, &summary),
+                    Err(LeaderboardError::ValidationError(e)) => error_response(400, &e.to_string()),
+                    Err(e) => error_response(500, &e.to_string()),
+                }
+            }
+            _ => error_response(404, "not found"),
+        }
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Authorization"))
+            .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+            .is_some_and(|sent| same_secret(sent, token))
+    }
+}
+
+/// Decodes a query string part: `%XX` escapes and `+` for a space. Broken
+/// escapes are kept as they are.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit));
+        match (bytes[i], hex) {
+            (b'%', Some(hex)) => {
+                let hex = std::str::from_utf8(hex).unwrap_or_default();
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+                i += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Compares a sent token without giving away through timing how much of it matched
+fn same_secret(sent: &str, token: &str) -> bool {
+    sent.len() == token.len() && sent.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn json_response<T: serde::Serialize>(status: u16, value: &T) -> (u16, String) {
+    match serde_json::to_string(value) {
+        Ok(body) => (status, body),
+        Err(e) => error_response(500, &e.to_string()),
+    }
+}
+
+fn error_response(status: u16, message: &str) -> (u16, String) {
+    (status, serde_json::json!({ "error": message }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("ana%20b"), "ana b");
+        assert_eq!(percent_decode("ana+b"), "ana b");
+        assert_eq!(percent_decode("time%3A30"), "time:30");
+        assert_eq!(percent_decode("J%C3%BCrgen"), "Jürgen");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert_eq!(percent_decode("%+1"), "% 1");
+    }
+
+    #[test]
+    fn test_same_secret() {
+        assert!(same_secret("s3cret", "s3cret"));
+        assert!(!same_secret("s3creT", "s3cret"));
+        assert!(!same_secret("s3cre", "s3cret"));
+        assert!(!same_secret("", "s3cret"));
+    }
+}
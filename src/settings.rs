@@ -7,6 +7,12 @@ pub enum Settings {
     Language,
     BatchSize,
     TopWords,
+    Profile,
+    Pace,
+    Preset,
+    Font,
+    TextSize,
+    LineSpacing,
 }
 
 impl Settings {
@@ -16,6 +22,25 @@ impl Settings {
             Settings::Language,
             Settings::BatchSize,
             Settings::TopWords,
+            Settings::Profile,
+            Settings::Pace,
+            Settings::Preset,
+        ]
+    }
+
+    /// `all` and the settings only the GUI has
+    pub fn gui() -> &'static [Settings] {
+        &[
+            Settings::ColorScheme,
+            Settings::Language,
+            Settings::BatchSize,
+            Settings::TopWords,
+            Settings::Profile,
+            Settings::Pace,
+            Settings::Preset,
+            Settings::Font,
+            Settings::TextSize,
+            Settings::LineSpacing,
         ]
     }
 
@@ -31,6 +56,12 @@ impl Display for Settings {
             Settings::Language => write!(f, "Language"),
             Settings::BatchSize => write!(f, "Batch Size"),
             Settings::TopWords => write!(f, "Top Words"),
+            Settings::Profile => write!(f, "Profile"),
+            Settings::Pace => write!(f, "Pace Caret"),
+            Settings::Preset => write!(f, "Preset"),
+            Settings::Font => write!(f, "Font"),
+            Settings::TextSize => write!(f, "Text Size"),
+            Settings::LineSpacing => write!(f, "Line Spacing"),
         }
     }
 }
@@ -0,0 +1,138 @@
+use std::fmt::Display;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::leaderboard::{LeaderboardEntry, LeaderboardError};
+use crate::practice::{PracticeResult, TYPING_LEVELS};
+use crate::storage::json::JsonStorage;
+
+/// Persistence for test results, full history and practice progress
+pub trait Storage {
+    /// Best results, highest WPM first
+    fn load_entries(&self) -> Result<Vec<LeaderboardEntry>, LeaderboardError>;
+
+    /// Every stored result, oldest first, optionally starting at an RFC3339 timestamp
+    fn load_history(&self, since: Option<&str>) -> Result<Vec<LeaderboardEntry>, LeaderboardError>;
+
+    fn save_entries(&self, entries: &[LeaderboardEntry]) -> Result<(), LeaderboardError>;
+
+    fn save_practice_result(&self, level: usize, result: &PracticeResult) -> Result<(), LeaderboardError>;
+
+    fn load_practice_results(&self, level: usize) -> Result<Vec<PracticeResult>, LeaderboardError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum StorageBackend {
+    Json,
+    Sqlite,
+}
+
+impl StorageBackend {
+    pub fn all() -> &'static [StorageBackend] {
+        &[StorageBackend::Json, StorageBackend::Sqlite]
+    }
+
+    pub fn is_available(&self) -> bool {
+        match self {
+            StorageBackend::Json => true,
+            StorageBackend::Sqlite => cfg!(feature = "sqlite"),
+        }
+    }
+}
+
+impl Default for StorageBackend {
+    fn default() -> Self {
+        StorageBackend::Json
+    }
+}
+
+impl Display for StorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageBackend::Json => write!(f, "json"),
+            StorageBackend::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+/// Opens a backend in the active profile's directory
+pub fn open_storage(backend: StorageBackend) -> Result<Box<dyn Storage>, LeaderboardError> {
+    let dir = crate::profile::current_data_dir().map_err(|e| LeaderboardError::IoError(
+        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+    ))?;
+    open_storage_in(backend, &dir)
+}
+
+/// Opens a backend in a given profile directory
+pub fn open_storage_in(backend: StorageBackend, dir: &Path) -> Result<Box<dyn Storage>, LeaderboardError> {
+    match backend {
+        StorageBackend::Json => Ok(Box::new(JsonStorage::in_dir(dir))),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => Ok(Box::new(crate::storage::sqlite::SqliteStorage::in_dir(dir)?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => {
+            eprintln!("SQLite storage is not available in this build, falling back to JSON");
+            Ok(Box::new(JsonStorage::in_dir(dir)))
+        }
+    }
+}
+
+/// Opens the backend selected in the user's config
+pub fn open_configured() -> Result<Box<dyn Storage>, LeaderboardError> {
+    open_storage(AppConfig::load().storage_backend)
+}
+
+/// Moves everything from the configured backend to `to` and makes it the
+/// configured one. Returns the number of results copied.
+pub fn switch_backend(to: StorageBackend) -> Result<usize, LeaderboardError> {
+    if !to.is_available() {
+        return Err(LeaderboardError::DatabaseError(format!(
+            "{} storage is not available in this build",
+            to
+        )));
+    }
+
+    let mut config = AppConfig::load();
+    if config.storage_backend == to {
+        return Ok(0);
+    }
+
+    let from = open_storage(config.storage_backend)?;
+    let target = open_storage(to)?;
+    let copied = migrate(from.as_ref(), target.as_ref(), TYPING_LEVELS.len())?;
+
+    config.storage_backend = to;
+    config
+        .save()
+        .map_err(|e| LeaderboardError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+    Ok(copied)
+}
+
+/// Copies results and practice progress from one backend into another.
+/// Returns the number of results copied.
+pub fn migrate(from: &dyn Storage, to: &dyn Storage, levels: usize) -> Result<usize, LeaderboardError> {
+    let existing: std::collections::HashSet<String> = to
+        .load_history(None)?
+        .into_iter()
+        .map(|e| e.timestamp)
+        .collect();
+    let entries: Vec<LeaderboardEntry> = from
+        .load_history(None)?
+        .into_iter()
+        .filter(|e| !existing.contains(&e.timestamp))
+        .collect();
+    if !entries.is_empty() {
+        to.save_entries(&entries)?;
+    }
+
+    for level in 1..=levels {
+        let already_there = to.load_practice_results(level)?;
+        for result in from.load_practice_results(level)? {
+            if !already_there.contains(&result) {
+                to.save_practice_result(level, &result)?;
+            }
+        }
+    }
+    Ok(entries.len())
+}
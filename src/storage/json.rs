@@ -0,0 +1,534 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::leaderboard::{FileLockGuard, LeaderboardEntry, LeaderboardError};
+use crate::practice::PracticeResult;
+use crate::storage::backend::Storage;
+
+/// Results kept in leaderboard.json; older/slower runs are dropped on save
+const MAX_ENTRIES: usize = 100;
+/// Every saved result, one JSON object per line, only ever appended to
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// Cached leaderboard data with timestamp for invalidation
+#[derive(Debug, Clone)]
+struct LeaderboardCache {
+    path: PathBuf,
+    entries: Vec<LeaderboardEntry>,
+    last_modified: SystemTime,
+    cached_at: Instant,
+}
+
+impl LeaderboardCache {
+    /// Check if cache is still valid (less than 30 seconds old and file hasn't changed)
+    fn is_valid(&self, file_path: &PathBuf) -> bool {
+        if self.path != *file_path {
+            return false;
+        }
+
+        // Cache expires after 30 seconds
+        if self.cached_at.elapsed() > Duration::from_secs(30) {
+            return false;
+        }
+
+        // Check if file has been modified
+        if let Ok(metadata) = fs::metadata(file_path) {
+            if let Ok(modified) = metadata.modified() {
+                return modified <= self.last_modified;
+            }
+        }
+
+        false
+    }
+}
+
+/// Global cache for leaderboard data
+static LEADERBOARD_CACHE: OnceLock<Mutex<Option<LeaderboardCache>>> = OnceLock::new();
+
+/// Initialize the global cache
+fn get_cache() -> &'static Mutex<Option<LeaderboardCache>> {
+    LEADERBOARD_CACHE.get_or_init(|| Mutex::new(None))
+}
+
+/// Clear the cache (called after successful save operations)
+fn invalidate_cache() {
+    if let Ok(mut cache) = get_cache().lock() {
+        *cache = None;
+    }
+}
+
+/// Creates a backup of the leaderboard file before modifications with rotation
+/// Maintains up to 3 backup files: .json.bak, .json.bak2, .json.bak3
+pub fn create_backup(leaderboard_path: &PathBuf) -> Result<PathBuf, std::io::Error> {
+    if !leaderboard_path.exists() {
+        return Ok(leaderboard_path.with_extension("json.bak"));
+    }
+
+    // Rotate existing backups: .bak2 -> .bak3, .bak -> .bak2
+    let backup3_path = leaderboard_path.with_extension("json.bak3");
+    let backup2_path = leaderboard_path.with_extension("json.bak2");
+    let backup1_path = leaderboard_path.with_extension("json.bak");
+
+    // Remove oldest backup if it exists
+    if backup3_path.exists() {
+        fs::remove_file(&backup3_path).ok(); // Ignore errors
+    }
+
+    // Rotate backup2 -> backup3
+    if backup2_path.exists() {
+        fs::rename(&backup2_path, &backup3_path).ok(); // Ignore errors
+    }
+
+    // Rotate backup1 -> backup2
+    if backup1_path.exists() {
+        fs::rename(&backup1_path, &backup2_path).ok(); // Ignore errors
+    }
+
+    // Create new backup from current file
+    fs::copy(leaderboard_path, &backup1_path)?;
+
+    Ok(backup1_path)
+}
+
+/// Performs atomic write by writing to a temporary file first, then moving
+pub fn atomic_write<T: Serialize + DeserializeOwned>(path: &PathBuf, entries: &[T]) -> Result<(), LeaderboardError> {
+    let temp_path = path.with_extension("json.tmp");
+
+    // Write to temporary file first
+    let json = serde_json::to_string_pretty(entries)?;
+    {
+        let mut temp_file = fs::File::create(&temp_path)?;
+        temp_file.write_all(json.as_bytes())?;
+        temp_file.sync_all()?; // Ensure data is written to disk
+    }
+
+    // Verify the written data is valid JSON
+    if let Err(_) = validate_json_file::<T>(&temp_path) {
+        fs::remove_file(&temp_path).ok(); // Clean up temp file
+        return Err(LeaderboardError::IoError(
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Written data failed validation"
+            )
+        ));
+    }
+
+    // Atomically move temp file to final location
+    fs::rename(&temp_path, path)?;
+
+    Ok(())
+}
+
+/// Validates that a JSON file contains valid leaderboard data
+fn validate_json_file<T: DeserializeOwned>(path: &PathBuf) -> Result<(), LeaderboardError> {
+    if !path.exists() {
+        return Ok(()); // Non-existent file is valid (empty leaderboard)
+    }
+
+    let content = fs::read_to_string(path)?;
+    if content.trim().is_empty() {
+        return Ok(()); // Empty file is valid
+    }
+
+    // Try to parse as leaderboard entries
+    let _entries: Vec<T> = serde_json::from_str(&content)?;
+    Ok(())
+}
+
+/// Attempts to recover from backup files if main file is corrupted
+/// Tries backup files in order: .bak, .bak2, .bak3
+fn recover_from_backup<T: DeserializeOwned>(leaderboard_path: &PathBuf) -> Result<Vec<T>, LeaderboardError> {
+    let backup_files = [
+        leaderboard_path.with_extension("json.bak"),
+        leaderboard_path.with_extension("json.bak2"),
+        leaderboard_path.with_extension("json.bak3"),
+    ];
+
+    for (i, backup_path) in backup_files.iter().enumerate() {
+        if !backup_path.exists() {
+            continue;
+        }
+
+        eprintln!("Attempting to recover leaderboard from backup {}...", i + 1);
+
+        if let Ok(()) = validate_json_file::<T>(backup_path) {
+            let content = fs::read_to_string(backup_path)?;
+            if content.trim().is_empty() {
+                eprintln!("Backup {} is empty, trying next backup...", i + 1);
+                continue;
+            }
+
+            match serde_json::from_str::<Vec<T>>(&content) {
+                Ok(entries) => {
+                    // Restore this backup as main file
+                    fs::copy(backup_path, leaderboard_path)?;
+                    eprintln!("Successfully recovered leaderboard from backup {}", i + 1);
+                    return Ok(entries);
+                }
+                Err(_) => {
+                    eprintln!("Backup {} is corrupted, trying next backup...", i + 1);
+                    continue;
+                }
+            }
+        } else {
+            eprintln!("Backup {} failed validation, trying next backup...", i + 1);
+        }
+    }
+
+    eprintln!("No valid backup found, starting with empty leaderboard");
+    Ok(Vec::new())
+}
+
+/// Retry helper for operations that might fail temporarily
+pub fn retry_operation<F, T, E>(mut operation: F, max_retries: usize, delay: Duration) -> Result<T, E>
+where
+    F: FnMut() -> Result<T, E>,
+    E: std::fmt::Debug,
+{
+    let mut last_error = None;
+
+    for attempt in 0..=max_retries {
+        match operation() {
+            Ok(result) => return Ok(result),
+            Err(error) => {
+                if attempt == max_retries {
+                    return Err(error);
+                }
+
+                eprintln!("Operation failed (attempt {}), retrying in {:?}: {:?}",
+                         attempt + 1, delay, error);
+                last_error = Some(error);
+                std::thread::sleep(delay);
+            }
+        }
+    }
+
+    // This should never be reached due to the logic above
+    Err(last_error.unwrap())
+}
+
+/// Load entries directly from file without caching
+pub fn load_entries_from_file<T: DeserializeOwned>(leaderboard_path: &PathBuf) -> Result<Vec<T>, LeaderboardError> {
+    // First try to validate and load the main file
+    if let Ok(()) = validate_json_file::<T>(leaderboard_path) {
+        let content = fs::read_to_string(leaderboard_path)?;
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        match serde_json::from_str(&content) {
+            Ok(entries) => return Ok(entries),
+            Err(_) => {
+                eprintln!("Main leaderboard file appears corrupted, attempting recovery...");
+            }
+        }
+    } else {
+        eprintln!("Main leaderboard file validation failed, attempting recovery...");
+    }
+
+    // If main file is corrupted, try to recover from backup
+    recover_from_backup(leaderboard_path)
+}
+
+/// Older versions kept practice progress in ./practice_results relative to where
+/// typeman was started; copy it into the default profile the first time
+fn adopt_legacy_practice_dir(dir: &Path, practice_dir: &Path) {
+    let legacy = Path::new("practice_results");
+    let is_default_profile = crate::paths::data_root().map_or(false, |root| root == dir);
+    if !is_default_profile || practice_dir.exists() || !legacy.is_dir() {
+        return;
+    }
+
+    if fs::create_dir_all(practice_dir).is_err() {
+        return;
+    }
+    if let Ok(entries) = fs::read_dir(legacy) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            fs::copy(entry.path(), practice_dir.join(entry.file_name())).ok();
+        }
+    }
+}
+
+/// Reads history.jsonl, skipping lines that don't parse (a write cut short)
+fn read_history(path: &Path) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The original storage: leaderboard.json of the best 100 results, the full
+/// history next to it and plain-text practice logs
+pub struct JsonStorage {
+    dir: PathBuf,
+    practice_dir: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(dir: PathBuf, practice_dir: PathBuf) -> Self {
+        Self { dir, practice_dir }
+    }
+
+    /// Storage inside a profile directory, practice logs in its practice_results folder
+    pub fn in_dir(dir: &Path) -> Self {
+        let practice_dir = dir.join("practice_results");
+        adopt_legacy_practice_dir(dir, &practice_dir);
+        Self::new(dir.to_path_buf(), practice_dir)
+    }
+
+    fn leaderboard_path(&self) -> PathBuf {
+        self.dir.join("leaderboard.json")
+    }
+
+    fn history_path(&self) -> PathBuf {
+        self.dir.join(HISTORY_FILE)
+    }
+
+    /// Appends the results whose timestamps aren't in the history yet. The
+    /// first time, the results already on the leaderboard go in before them.
+    fn append_history(&self, new_entries: &[LeaderboardEntry]) -> Result<(), LeaderboardError> {
+        let history_path = self.history_path();
+        let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut to_write = Vec::new();
+        if history_path.exists() {
+            seen.extend(read_history(&history_path)?.into_iter().map(|e| e.timestamp));
+        } else {
+            to_write.extend(self.load_entries().unwrap_or_default());
+        }
+        to_write.extend_from_slice(new_entries);
+
+        let mut lines = String::new();
+        for entry in to_write {
+            if seen.insert(entry.timestamp.clone()) {
+                lines.push_str(&serde_json::to_string(&entry)?);
+                lines.push('\n');
+            }
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&history_path)?;
+        file.write_all(lines.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        self.dir.join("leaderboard.lock")
+    }
+
+    fn practice_path(&self, level: usize) -> PathBuf {
+        self.practice_dir.join(format!("level_{}.txt", level))
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load_entries(&self) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+        let leaderboard_path = self.leaderboard_path();
+
+        // Return empty vec if file doesn't exist
+        if !leaderboard_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        // Check cache first
+        if let Ok(cache_guard) = get_cache().lock() {
+            if let Some(ref cache) = *cache_guard {
+                if cache.is_valid(&leaderboard_path) {
+                    return Ok(cache.entries.clone());
+                }
+            }
+        }
+
+        // Cache miss or invalid, load from file
+        let entries = load_entries_from_file(&leaderboard_path)?;
+
+        // Update cache
+        if let Ok(metadata) = fs::metadata(&leaderboard_path) {
+            if let Ok(modified) = metadata.modified() {
+                if let Ok(mut cache_guard) = get_cache().lock() {
+                    *cache_guard = Some(LeaderboardCache {
+                        path: leaderboard_path.clone(),
+                        entries: entries.clone(),
+                        last_modified: modified,
+                        cached_at: Instant::now(),
+                    });
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn load_history(&self, since: Option<&str>) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+        let since = since.and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok());
+        // Leaderboards from before the history file was kept only have the top 100
+        let history_path = self.history_path();
+        let stored = if history_path.exists() {
+            read_history(&history_path)?
+        } else {
+            self.load_entries()?
+        };
+        let mut entries: Vec<(chrono::DateTime<chrono::FixedOffset>, LeaderboardEntry)> = stored
+            .into_iter()
+            .filter_map(|e| chrono::DateTime::parse_from_rfc3339(&e.timestamp).ok().map(|t| (t, e)))
+            .filter(|(t, _)| since.map_or(true, |since| *t >= since))
+            .collect();
+        entries.sort_by_key(|(t, _)| *t);
+        Ok(entries.into_iter().map(|(_, e)| e).collect())
+    }
+
+    fn save_entries(&self, new_entries: &[LeaderboardEntry]) -> Result<(), LeaderboardError> {
+        let leaderboard_path = self.leaderboard_path();
+        let lock_path = self.lock_path();
+
+        // Retry the entire save operation up to 3 times for temporary failures
+        retry_operation(|| -> Result<(), LeaderboardError> {
+            // Acquire file lock with 5-second timeout
+            let _lock_guard = FileLockGuard::acquire(&lock_path, Duration::from_secs(5))?;
+
+            self.append_history(new_entries)?;
+
+            // Create backup before making changes
+            create_backup(&leaderboard_path)?;
+
+            // Load existing entries
+            let mut entries = self.load_entries().unwrap_or_default();
+
+            // Add new entries, skipping ones already on it (same as the history)
+            for entry in new_entries {
+                if !entries.iter().any(|e| e.timestamp == entry.timestamp) {
+                    entries.push(entry.clone());
+                }
+            }
+
+            // Sort by WPM (highest first)
+            entries.sort_by(|a, b| b.wpm.partial_cmp(&a.wpm).unwrap_or(std::cmp::Ordering::Equal));
+
+            // Limit to top 100 entries
+            entries.truncate(MAX_ENTRIES);
+
+            // Save to file using atomic write
+            atomic_write(&leaderboard_path, &entries)?;
+
+            Ok(())
+        }, 2, Duration::from_millis(100))?; // Retry up to 2 times with 100ms delay
+
+        // Invalidate cache after successful save
+        invalidate_cache();
+
+        Ok(())
+    }
+
+    fn save_practice_result(&self, level: usize, result: &PracticeResult) -> Result<(), LeaderboardError> {
+        fs::create_dir_all(&self.practice_dir)?;
+
+        let stats = format!(
+            "Time: {:.2}s\nAccuracy: {:.1}%\nWPM: {:.1}\n---\n",
+            result.time, result.accuracy, result.wpm
+        );
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.practice_path(level))?;
+        file.write_all(stats.as_bytes())?;
+        Ok(())
+    }
+
+    fn load_practice_results(&self, level: usize) -> Result<Vec<PracticeResult>, LeaderboardError> {
+        let contents = match fs::read_to_string(self.practice_path(level)) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut results = Vec::new();
+        let mut current = PracticeResult::default();
+        let mut has_wpm = false;
+        for line in contents.lines() {
+            let value = |prefix: &str, suffix: &str| {
+                line.strip_prefix(prefix)
+                    .map(|v| v.trim().trim_end_matches(suffix))
+                    .and_then(|v| v.parse::<f64>().ok())
+            };
+            if let Some(time) = value("Time:", "s") {
+                current.time = time;
+            } else if let Some(accuracy) = value("Accuracy:", "%") {
+                current.accuracy = accuracy;
+            } else if let Some(wpm) = value("WPM:", "") {
+                current.wpm = wpm;
+                has_wpm = true;
+            } else if line.starts_with("---") && has_wpm {
+                results.push(std::mem::take(&mut current));
+                has_wpm = false;
+            }
+        }
+        if has_wpm {
+            results.push(current);
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    use crate::language::Language;
+    use crate::leaderboard::TestType;
+
+    fn entry(day: usize, wpm: f64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            wpm,
+            accuracy: 97.0,
+            test_type: TestType::Time(30),
+            test_mode: "time".to_string(),
+            word_count: 40,
+            test_duration: 30.0,
+            timestamp: format!("2025-{:02}-{:02}T08:00:00Z", day / 28 + 1, day % 28 + 1),
+            language: Language::English,
+            flag: None,
+        }
+    }
+
+    #[test]
+    fn test_history_keeps_more_than_the_leaderboard() {
+        let dir = std::env::temp_dir().join(format!("typeman-json-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let storage = JsonStorage::new(dir.clone(), dir.join("practice_results"));
+
+        // Slowest last, so the newest results fall off the top 100
+        let entries: Vec<LeaderboardEntry> = (0..130).map(|day| entry(day, 200.0 - day as f64)).collect();
+        for chunk in entries.chunks(40) {
+            storage.save_entries(chunk).unwrap();
+        }
+        // Saving a result again doesn't repeat it
+        storage.save_entries(&entries[..5]).unwrap();
+
+        let leaderboard = storage.load_entries().unwrap();
+        assert_eq!(leaderboard.len(), MAX_ENTRIES);
+        let timestamps: HashSet<&str> = leaderboard.iter().map(|e| e.timestamp.as_str()).collect();
+        assert_eq!(timestamps.len(), MAX_ENTRIES);
+        assert_eq!(leaderboard, entries[..MAX_ENTRIES]);
+        assert_eq!(storage.load_history(None).unwrap(), entries);
+        assert_eq!(storage.load_history(Some(&entries[120].timestamp)).unwrap(), entries[120..]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,299 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::{params, Connection};
+
+use crate::leaderboard::{LeaderboardEntry, LeaderboardError};
+use crate::practice::PracticeResult;
+use crate::storage::backend::Storage;
+
+/// Same cap as the JSON leaderboard, but only applied when reading
+const LEADERBOARD_SIZE: i64 = 100;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS results (
+        id INTEGER PRIMARY KEY,
+        timestamp TEXT NOT NULL UNIQUE,
+        time INTEGER,
+        wpm REAL NOT NULL,
+        test_mode TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_results_wpm ON results (wpm DESC);
+    CREATE TABLE IF NOT EXISTS practice_results (
+        id INTEGER PRIMARY KEY,
+        level INTEGER NOT NULL,
+        time REAL NOT NULL,
+        accuracy REAL NOT NULL,
+        wpm REAL NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_practice_level ON practice_results (level);
+";
+
+/// Run after the columns added since the first schema exist
+const INDEXES: &str = "
+    CREATE INDEX IF NOT EXISTS idx_results_time ON results (time);
+    DROP INDEX IF EXISTS idx_results_timestamp;
+";
+
+/// When a result was recorded, in UTC milliseconds. Timestamps keep the
+/// local offset they were taken in, so they can't be compared as text.
+fn utc_millis(timestamp: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.timestamp_millis())
+}
+
+impl From<rusqlite::Error> for LeaderboardError {
+    fn from(error: rusqlite::Error) -> Self {
+        LeaderboardError::DatabaseError(error.to_string())
+    }
+}
+
+/// Keeps every result in a single SQLite database, so history isn't capped
+/// and queries stay fast as it grows
+pub struct SqliteStorage {
+    conn: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> Result<Self, LeaderboardError> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(SCHEMA)?;
+        Self::add_time_column(&conn)?;
+        conn.execute_batch(INDEXES)?;
+        Ok(Self { conn })
+    }
+
+    /// Databases from before the `time` column get it filled in once
+    fn add_time_column(conn: &Connection) -> Result<(), LeaderboardError> {
+        let has_time: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('results') WHERE name = 'time'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_time {
+            return Ok(());
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("ALTER TABLE results ADD COLUMN time INTEGER", [])?;
+        let rows: Vec<(i64, String)> = {
+            let mut stmt = tx.prepare("SELECT id, timestamp FROM results")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<_, _>>()?
+        };
+        {
+            let mut stmt = tx.prepare("UPDATE results SET time = ?1 WHERE id = ?2")?;
+            for (id, timestamp) in rows {
+                stmt.execute(params![utc_millis(&timestamp), id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn in_dir(dir: &Path) -> Result<Self, LeaderboardError> {
+        Self::open(&Self::database_path(dir))
+    }
+
+    pub fn database_path(dir: &Path) -> PathBuf {
+        dir.join("typeman.db")
+    }
+
+    fn query_entries(&self, sql: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(args, |row| row.get::<_, String>(0))?;
+
+        let mut entries = Vec::new();
+        for data in rows {
+            entries.push(serde_json::from_str(&data?)?);
+        }
+        Ok(entries)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_entries(&self) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+        self.query_entries(
+            "SELECT data FROM results ORDER BY wpm DESC LIMIT ?1",
+            &[&LEADERBOARD_SIZE],
+        )
+    }
+
+    fn load_history(&self, since: Option<&str>) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+        // Like the JSON backend, results without a readable time are left out
+        match since.and_then(utc_millis) {
+            Some(since) => self.query_entries(
+                "SELECT data FROM results WHERE time >= ?1 ORDER BY time, id",
+                &[&since],
+            ),
+            None => self.query_entries("SELECT data FROM results WHERE time IS NOT NULL ORDER BY time, id", &[]),
+        }
+    }
+
+    fn save_entries(&self, entries: &[LeaderboardEntry]) -> Result<(), LeaderboardError> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO results (timestamp, time, wpm, test_mode, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for entry in entries {
+                let data = serde_json::to_string(entry)?;
+                stmt.execute(params![entry.timestamp, utc_millis(&entry.timestamp), entry.wpm, entry.test_mode, data])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn save_practice_result(&self, level: usize, result: &PracticeResult) -> Result<(), LeaderboardError> {
+        self.conn.execute(
+            "INSERT INTO practice_results (level, time, accuracy, wpm) VALUES (?1, ?2, ?3, ?4)",
+            params![level as i64, result.time, result.accuracy, result.wpm],
+        )?;
+        Ok(())
+    }
+
+    fn load_practice_results(&self, level: usize) -> Result<Vec<PracticeResult>, LeaderboardError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT time, accuracy, wpm FROM practice_results WHERE level = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map([level as i64], |row| {
+            Ok(PracticeResult {
+                time: row.get(0)?,
+                accuracy: row.get(1)?,
+                wpm: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+    use crate::leaderboard::TestType;
+    use crate::storage::backend::migrate;
+    use crate::storage::json::JsonStorage;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("typeman-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn entry(timestamp: &str, wpm: f64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            wpm,
+            accuracy: 97.0,
+            test_type: TestType::Time(30),
+            test_mode: "time".to_string(),
+            word_count: 40,
+            test_duration: 30.0,
+            timestamp: timestamp.to_string(),
+            language: Language::English,
+            flag: None,
+        }
+    }
+
+    #[test]
+    fn test_sqlite_round_trip() {
+        let dir = temp_dir("sqlite-round-trip");
+        let storage = SqliteStorage::open(&SqliteStorage::database_path(&dir)).unwrap();
+
+        let entries = vec![
+            entry("2025-09-11T08:00:00Z", 60.0),
+            entry("2025-09-10T08:00:00Z", 90.0),
+        ];
+        storage.save_entries(&entries).unwrap();
+        // Saving the same timestamp twice keeps a single row
+        storage.save_entries(&entries[..1]).unwrap();
+
+        let best = storage.load_entries().unwrap();
+        assert_eq!(best.len(), 2);
+        assert_eq!(best[0].wpm, 90.0);
+
+        let history = storage.load_history(Some("2025-09-11T00:00:00Z")).unwrap();
+        assert_eq!(history, vec![entries[0].clone()]);
+
+        let result = PracticeResult { time: 12.5, accuracy: 99.0, wpm: 41.0 };
+        storage.save_practice_result(3, &result).unwrap();
+        assert_eq!(storage.load_practice_results(3).unwrap(), vec![result]);
+        assert!(storage.load_practice_results(4).unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_history_compares_times_across_offsets() {
+        let dir = temp_dir("sqlite-offsets");
+        let sqlite = SqliteStorage::in_dir(&dir).unwrap();
+        let json = JsonStorage::new(dir.clone(), dir.join("practice_results"));
+        // 23:00 UTC, so earlier than the second one although it sorts after it as text
+        let entries = [entry("2025-09-11T01:00:00+02:00", 60.0), entry("2025-09-10T23:30:00Z", 70.0)];
+        sqlite.save_entries(&entries).unwrap();
+        json.save_entries(&entries).unwrap();
+
+        for storage in [&sqlite as &dyn Storage, &json] {
+            let history = storage.load_history(None).unwrap();
+            assert_eq!(history, [entries[0].clone(), entries[1].clone()]);
+            let since = storage.load_history(Some("2025-09-10T23:15:00Z")).unwrap();
+            assert_eq!(since, [entries[1].clone()]);
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_old_databases_get_the_time_column() {
+        let dir = temp_dir("sqlite-old-schema");
+        let path = SqliteStorage::database_path(&dir);
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE results (id INTEGER PRIMARY KEY, timestamp TEXT NOT NULL UNIQUE, wpm REAL NOT NULL, test_mode TEXT NOT NULL, data TEXT NOT NULL);
+                 CREATE INDEX idx_results_timestamp ON results (timestamp);",
+            )
+            .unwrap();
+            let old = entry("2025-09-11T01:00:00+02:00", 60.0);
+            conn.execute(
+                "INSERT INTO results (timestamp, wpm, test_mode, data) VALUES (?1, ?2, ?3, ?4)",
+                params![old.timestamp, old.wpm, old.test_mode, serde_json::to_string(&old).unwrap()],
+            )
+            .unwrap();
+        }
+
+        let storage = SqliteStorage::open(&path).unwrap();
+        storage.save_entries(&[entry("2025-09-10T23:30:00Z", 70.0)]).unwrap();
+        let history = storage.load_history(Some("2025-09-10T22:00:00Z")).unwrap();
+        let times: Vec<&str> = history.iter().map(|e| e.timestamp.as_str()).collect();
+        assert_eq!(times, ["2025-09-11T01:00:00+02:00", "2025-09-10T23:30:00Z"]);
+        // Opening it again doesn't try to add the column twice
+        drop(storage);
+        assert!(SqliteStorage::open(&path).is_ok());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_migrate_json_to_sqlite() {
+        let dir = temp_dir("migrate");
+        let json = JsonStorage::new(dir.clone(), dir.join("practice_results"));
+        json.save_entries(&[entry("2025-09-11T08:00:00Z", 60.0), entry("2025-09-12T08:00:00Z", 70.0)])
+            .unwrap();
+        json.save_practice_result(1, &PracticeResult { time: 20.0, accuracy: 95.5, wpm: 38.2 })
+            .unwrap();
+
+        let sqlite = SqliteStorage::open(&SqliteStorage::database_path(&dir)).unwrap();
+        assert_eq!(migrate(&json, &sqlite, 2).unwrap(), 2);
+        // Running it again copies nothing new
+        assert_eq!(migrate(&json, &sqlite, 2).unwrap(), 0);
+
+        assert_eq!(sqlite.load_history(None).unwrap(), json.load_history(None).unwrap());
+        assert_eq!(sqlite.load_practice_results(1).unwrap(), json.load_practice_results(1).unwrap());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
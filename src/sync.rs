@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::AppConfig;
+use crate::leaderboard::{FileLockGuard, LeaderboardEntry, LeaderboardError};
+use crate::storage::json::{atomic_write, load_entries_from_file};
+use crate::team::{MergeSummary, Submission, TeamEntry};
+
+/// Results waiting to be uploaded, kept in the profile dir
+pub const QUEUE_FILE: &str = "sync_queue.json";
+/// Results the server refused, set aside so they don't hold up the queue
+pub const REJECTED_FILE: &str = "sync_rejected.json";
+const QUEUE_LOCK: &str = "sync_queue.lock";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum SyncError {
+    NotConfigured,
+    /// The server couldn't be reached; queued results stay queued
+    Unreachable(String),
+    /// The server answered with an error status
+    Rejected(u16, String),
+    LeaderboardError(LeaderboardError),
+}
+
+impl std::fmt::Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncError::NotConfigured => write!(f, "no server set up"),
+            SyncError::Unreachable(e) => write!(f, "server unreachable ({})", e),
+            SyncError::Rejected(status, body) => {
+                // The server explains itself as {"error": "..."}
+                let reason = serde_json::from_str::<serde_json::Value>(body)
+                    .ok()
+                    .and_then(|value| value.get("error").and_then(|e| e.as_str()).map(str::to_string))
+                    .unwrap_or_else(|| body.clone());
+                write!(f, "the server answered {}: {}", status, reason)
+            }
+            SyncError::LeaderboardError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<LeaderboardError> for SyncError {
+    fn from(error: LeaderboardError) -> Self {
+        SyncError::LeaderboardError(error)
+    }
+}
+
+impl From<ureq::Error> for SyncError {
+    fn from(error: ureq::Error) -> Self {
+        match error {
+            ureq::Error::Status(code, response) => {
+                SyncError::Rejected(code, response.into_string().unwrap_or_default())
+            }
+            ureq::Error::Transport(transport) => SyncError::Unreachable(transport.to_string()),
+        }
+    }
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(TIMEOUT).build()
+}
+
+fn endpoint(server: &str, path: &str) -> String {
+    format!("{}{}", server.trim_end_matches('/'), path)
+}
+
+/// What flushing the queue did: the server's merge summary, plus how many
+/// results it refused and were moved to the rejected file
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FlushSummary {
+    pub uploaded: MergeSummary,
+    pub rejected: usize,
+}
+
+/// Server URL, player name and token (if the server wants one) from the config
+pub fn configured() -> Option<(String, String, Option<String>)> {
+    let config = AppConfig::load();
+    match (config.server_url, config.player_name) {
+        (Some(server), Some(player)) => Some((server, player, config.server_token)),
+        _ => None,
+    }
+}
+
+/// Uploads results; the server skips ones it already has
+pub fn push(server: &str, player: &str, token: Option<&str>, entries: &[LeaderboardEntry]) -> Result<MergeSummary, SyncError> {
+    let submission = Submission { player: player.to_string(), entries: entries.to_vec() };
+    let mut request = agent().post(&endpoint(server, "/api/results"));
+    if let Some(token) = token {
+        request = request.set("Authorization", &format!("Bearer {}", token));
+    }
+    let summary = request
+        .send_json(&submission)?
+        .into_json()
+        .map_err(|e| SyncError::Unreachable(e.to_string()))?;
+    Ok(summary)
+}
+
+/// Fetches the server's ranking, optionally the best result per player only
+pub fn pull(server: &str, mode: Option<&str>, best_only: bool, limit: usize) -> Result<Vec<TeamEntry>, SyncError> {
+    let mut request = agent()
+        .get(&endpoint(server, "/api/leaderboard"))
+        .query("limit", &limit.to_string())
+        .query("best", if best_only { "true" } else { "false" });
+    if let Some(mode) = mode {
+        request = request.query("mode", mode);
+    }
+    let entries = request
+        .call()?
+        .into_json()
+        .map_err(|e| SyncError::Unreachable(e.to_string()))?;
+    Ok(entries)
+}
+
+pub fn queue_path(dir: &Path) -> PathBuf {
+    dir.join(QUEUE_FILE)
+}
+
+pub fn rejected_path(dir: &Path) -> PathBuf {
+    dir.join(REJECTED_FILE)
+}
+
+fn load_list(path: &Path) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    load_entries_from_file(&path.to_path_buf())
+}
+
+/// Adds entries to a list file, skipping ones it already holds
+fn append_unique(path: &Path, entries: &[LeaderboardEntry]) -> Result<(), LeaderboardError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut list = load_list(path)?;
+    for entry in entries {
+        if !list.iter().any(|listed| listed.timestamp == entry.timestamp) {
+            list.push(entry.clone());
+        }
+    }
+    atomic_write(&path.to_path_buf(), &list)
+}
+
+pub fn load_queue(dir: &Path) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+    load_list(&queue_path(dir))
+}
+
+/// Results the server refused to take, kept for the user to look at
+pub fn load_rejected(dir: &Path) -> Result<Vec<LeaderboardEntry>, LeaderboardError> {
+    load_list(&rejected_path(dir))
+}
+
+/// Adds results to the upload queue
+pub fn enqueue(dir: &Path, entries: &[LeaderboardEntry]) -> Result<(), LeaderboardError> {
+    let _lock_guard = FileLockGuard::acquire(&dir.join(QUEUE_LOCK), TIMEOUT)?;
+    append_unique(&queue_path(dir), entries)
+}
+
+/// Whether a status means the server won't ever take these results. A bad
+/// token, a timeout or rate limiting can pass, so those results stay queued.
+fn is_refusal(status: u16) -> bool {
+    (400..500).contains(&status) && !matches!(status, 401 | 403 | 408 | 429)
+}
+
+/// Uploads everything queued in `dir` and empties the queue once the server
+/// has accepted it. When the server is unreachable the queue is left as is.
+///
+/// If the server refuses the upload, the results are sent one by one and the
+/// ones it still refuses are moved to the rejected file, so a single bad
+/// result can't block the queue.
+pub fn flush_queue(dir: &Path, server: &str, player: &str, token: Option<&str>) -> Result<FlushSummary, SyncError> {
+    let _lock_guard = FileLockGuard::acquire(&dir.join(QUEUE_LOCK), TIMEOUT)?;
+    let queue = load_queue(dir)?;
+    if queue.is_empty() {
+        return Ok(FlushSummary::default());
+    }
+
+    let summary = match push(server, player, token, &queue) {
+        Ok(uploaded) => FlushSummary { uploaded, rejected: 0 },
+        Err(SyncError::Rejected(status, _)) if is_refusal(status) => {
+            let mut summary = FlushSummary::default();
+            let mut refused = Vec::new();
+            for (i, entry) in queue.iter().enumerate() {
+                match push(server, player, token, std::slice::from_ref(entry)) {
+                    Ok(uploaded) => {
+                        summary.uploaded.added += uploaded.added;
+                        summary.uploaded.duplicates += uploaded.duplicates;
+                        summary.uploaded.conflicts += uploaded.conflicts;
+                    }
+                    Err(SyncError::Rejected(status, _)) if is_refusal(status) => refused.push(entry.clone()),
+                    Err(e) => {
+                        append_unique(&rejected_path(dir), &refused)?;
+                        atomic_write(&queue_path(dir), &queue[i..])?;
+                        return Err(e);
+                    }
+                }
+            }
+            append_unique(&rejected_path(dir), &refused)?;
+            summary.rejected = refused.len();
+            summary
+        }
+        Err(e) => return Err(e),
+    };
+    atomic_write::<LeaderboardEntry>(&queue_path(dir), &[])?;
+    Ok(summary)
+}
+
+/// Queues a new result and, if a server is configured, uploads the queue in the
+/// background so a slow or missing server never holds up the results screen
+pub fn submit_configured(entry: &LeaderboardEntry) -> Result<(), SyncError> {
+    let Some((server, player, token)) = configured() else {
+        return Ok(());
+    };
+    let dir = crate::profile::current_data_dir()
+        .map_err(|e| LeaderboardError::IoError(std::io::Error::other(e.to_string())))?;
+    enqueue(&dir, std::slice::from_ref(entry))?;
+
+    std::thread::spawn(move || {
+        let _ = flush_queue(&dir, &server, &player, token.as_deref());
+    });
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::leaderboard::{FileLockGuard, LeaderboardEntry, LeaderboardError, ValidationError};
+use crate::storage::json::{atomic_write, create_backup, load_entries_from_file, retry_operation};
+
+/// File every team member merges into, kept in the shared directory
+pub const TEAM_FILE: &str = "team_leaderboard.json";
+const TEAM_LOCK: &str = "team_leaderboard.lock";
+/// Best results kept per player, so nobody can crowd out the rest of the team
+const MAX_ENTRIES_PER_PLAYER: usize = 50;
+const MAX_PLAYER_LEN: usize = 32;
+
+/// A result on the team leaderboard, tagged with whoever typed it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TeamEntry {
+    pub player: String,
+    #[serde(flatten)]
+    pub entry: LeaderboardEntry,
+}
+
+/// Body of a result upload to a leaderboard server
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Submission {
+    pub player: String,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// What a merge did with the incoming entries
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub duplicates: usize,
+    pub conflicts: usize,
+}
+
+pub fn validate_player_name(name: &str) -> Result<(), ValidationError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_PLAYER_LEN || name.chars().any(char::is_control) {
+        return Err(ValidationError::InvalidPlayerName(name.to_string()));
+    }
+    Ok(())
+}
+
+pub fn team_path(dir: &Path) -> PathBuf {
+    dir.join(TEAM_FILE)
+}
+
+/// Shared directory and player name from the config, if the user joined a team
+pub fn configured() -> Option<(PathBuf, String)> {
+    let config = AppConfig::load();
+    match (config.team_dir, config.player_name) {
+        (Some(dir), Some(player)) => Some((dir, player)),
+        _ => None,
+    }
+}
+
+/// Remembers the shared directory and player name, creating the directory if needed
+pub fn join(dir: &Path, player: &str) -> Result<(), LeaderboardError> {
+    validate_player_name(player)?;
+    std::fs::create_dir_all(dir)?;
+
+    let mut config = AppConfig::load();
+    config.team_dir = Some(dir.canonicalize()?);
+    config.player_name = Some(player.trim().to_string());
+    config.save().map_err(|e| LeaderboardError::IoError(std::io::Error::other(e.to_string())))
+}
+
+/// Stops sharing results; the team file itself is left alone
+pub fn leave() -> Result<(), LeaderboardError> {
+    let mut config = AppConfig::load();
+    config.team_dir = None;
+    config.player_name = None;
+    config.save().map_err(|e| LeaderboardError::IoError(std::io::Error::other(e.to_string())))
+}
+
+/// Merges incoming entries into the existing ones.
+///
+/// Entries are identified by player and timestamp. An exact copy is a duplicate
+/// (someone pushed twice); a different entry under the same key is a conflict,
+/// settled in favour of an unflagged result, otherwise the one already stored.
+pub fn merge(existing: Vec<TeamEntry>, incoming: Vec<TeamEntry>) -> (Vec<TeamEntry>, MergeSummary) {
+    let mut summary = MergeSummary::default();
+    let mut merged = existing;
+    let mut index: HashMap<(String, String), usize> = merged
+        .iter()
+        .enumerate()
+        .map(|(i, e)| ((e.player.clone(), e.entry.timestamp.clone()), i))
+        .collect();
+
+    for new in incoming {
+        let key = (new.player.clone(), new.entry.timestamp.clone());
+        match index.get(&key) {
+            Some(&i) if merged[i] == new => summary.duplicates += 1,
+            Some(&i) => {
+                summary.conflicts += 1;
+                if merged[i].entry.flag.is_some() && new.entry.flag.is_none() {
+                    merged[i] = new;
+                }
+            }
+            None => {
+                index.insert(key, merged.len());
+                merged.push(new);
+                summary.added += 1;
+            }
+        }
+    }
+
+    merged.sort_by(|a, b| b.entry.wpm.partial_cmp(&a.entry.wpm).unwrap_or(std::cmp::Ordering::Equal));
+    let mut per_player: HashMap<String, usize> = HashMap::new();
+    merged.retain(|e| {
+        let count = per_player.entry(e.player.clone()).or_insert(0);
+        *count += 1;
+        *count <= MAX_ENTRIES_PER_PLAYER
+    });
+
+    (merged, summary)
+}
+
+/// Ranked view of a team leaderboard: optionally one test mode only and/or
+/// just the best result of each player
+pub fn ranked(entries: Vec<TeamEntry>, mode: Option<&str>, best_only: bool, limit: usize) -> Vec<TeamEntry> {
+    let mut seen = std::collections::HashSet::new();
+    let mut ranked: Vec<TeamEntry> = entries
+        .into_iter()
+        .filter(|e| mode.map_or(true, |mode| e.entry.test_mode == mode))
+        .collect();
+    ranked.sort_by(|a, b| b.entry.wpm.partial_cmp(&a.entry.wpm).unwrap_or(std::cmp::Ordering::Equal));
+    if best_only {
+        ranked.retain(|e| seen.insert(e.player.clone()));
+    }
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Team leaderboard in a shared directory, highest WPM first
+pub fn load(dir: &Path) -> Result<Vec<TeamEntry>, LeaderboardError> {
+    let path = team_path(dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    load_entries_from_file(&path)
+}
+
+/// Adds a player's results to the team leaderboard in `dir`.
+///
+/// The shared file is locked for the whole read-merge-write, so two members
+/// finishing a test at once can't drop each other's results.
+pub fn publish(dir: &Path, player: &str, entries: &[LeaderboardEntry]) -> Result<MergeSummary, LeaderboardError> {
+    validate_player_name(player)?;
+    for entry in entries {
+        entry.validate()?;
+    }
+
+    let path = team_path(dir);
+    let lock_path = dir.join(TEAM_LOCK);
+    let incoming: Vec<TeamEntry> = entries
+        .iter()
+        .map(|entry| TeamEntry { player: player.trim().to_string(), entry: entry.clone() })
+        .collect();
+
+    retry_operation(|| -> Result<MergeSummary, LeaderboardError> {
+        let _lock_guard = FileLockGuard::acquire(&lock_path, Duration::from_secs(5))?;
+        create_backup(&path)?;
+
+        let existing = load(dir)?;
+        let (merged, summary) = merge(existing, incoming.clone());
+        if summary.added > 0 || summary.conflicts > 0 {
+            atomic_write(&path, &merged)?;
+        }
+        Ok(summary)
+    }, 2, Duration::from_millis(100))
+}
+
+/// Pushes a new result to the configured team, if any
+pub fn publish_configured(entry: &LeaderboardEntry) -> Result<(), LeaderboardError> {
+    if let Some((dir, player)) = configured() {
+        publish(&dir, &player, std::slice::from_ref(entry))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anticheat::CheatFlag;
+    use crate::language::Language;
+    use crate::leaderboard::TestType;
+
+    fn entry(wpm: f64, timestamp: &str) -> LeaderboardEntry {
+        LeaderboardEntry {
+            wpm,
+            accuracy: 97.0,
+            test_type: TestType::Time(30),
+            test_mode: "time".to_string(),
+            word_count: 50,
+            test_duration: 30.0,
+            timestamp: timestamp.to_string(),
+            language: Language::English,
+            flag: None,
+        }
+    }
+
+    fn team(player: &str, entry: LeaderboardEntry) -> TeamEntry {
+        TeamEntry { player: player.to_string(), entry }
+    }
+
+    #[test]
+    fn test_merge_duplicates_and_conflicts() {
+        let existing = vec![team("ana", entry(80.0, "2025-09-11T10:00:00Z"))];
+
+        let mut flagged = entry(180.0, "2025-09-11T10:00:00Z");
+        flagged.flag = Some(CheatFlag::PasteBurst { chars: 20 });
+        let incoming = vec![
+            team("ana", entry(80.0, "2025-09-11T10:00:00Z")),
+            team("ana", flagged),
+            team("bo", entry(80.0, "2025-09-11T10:00:00Z")),
+        ];
+
+        let (merged, summary) = merge(existing, incoming);
+        assert_eq!(summary, MergeSummary { added: 1, duplicates: 1, conflicts: 1 });
+        assert_eq!(merged.len(), 2);
+        assert!(merged.iter().all(|e| e.entry.wpm == 80.0 && e.entry.flag.is_none()));
+    }
+
+    #[test]
+    fn test_publish_to_shared_dir() {
+        let dir = std::env::temp_dir().join(format!("typeman-team-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        publish(&dir, "ana", &[entry(70.0, "2025-09-11T10:00:00Z")]).unwrap();
+        publish(&dir, "bo", &[entry(90.0, "2025-09-11T11:00:00Z")]).unwrap();
+        let again = publish(&dir, "ana", &[entry(70.0, "2025-09-11T10:00:00Z")]).unwrap();
+        assert_eq!(again.duplicates, 1);
+        assert!(publish(&dir, "", &[entry(70.0, "2025-09-11T12:00:00Z")]).is_err());
+
+        let entries = load(&dir).unwrap();
+        let players: Vec<&str> = entries.iter().map(|e| e.player.as_str()).collect();
+        assert_eq!(players, ["bo", "ana"]);
+
+        publish(&dir, "bo", &[entry(60.0, "2025-09-11T12:00:00Z")]).unwrap();
+        let best: Vec<f64> = ranked(load(&dir).unwrap(), Some("time"), true, 10).iter().map(|e| e.entry.wpm).collect();
+        assert_eq!(best, [90.0, 70.0]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::custom_colors::MyColor;
+
+/// How many colors the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorDepth {
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+    /// No colors at all, e.g. with `NO_COLOR` set
+    #[serde(rename = "mono")]
+    Mono,
+}
+
+impl Display for ColorDepth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorDepth::TrueColor => write!(f, "truecolor"),
+            ColorDepth::Ansi256 => write!(f, "256 colors"),
+            ColorDepth::Ansi16 => write!(f, "16 colors"),
+            ColorDepth::Mono => write!(f, "no colors"),
+        }
+    }
+}
+
+/// A color the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermColor {
+    /// The terminal's own foreground or background
+    Reset,
+    /// 0-15 are the basic ANSI colors, 16-231 the color cube and 232-255 grays
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// The basic colors as xterm shows them
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel values of the 6x6x6 color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Index of the max_colors number in the terminfo format
+const TERMINFO_MAX_COLORS: usize = 13;
+
+/// Weighted by how sensitive the eye is to each channel
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    2 * d(a.0, b.0) + 4 * d(a.1, b.1) + 3 * d(a.2, b.2)
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> u8 {
+    (0..16).min_by_key(|&i| distance(rgb, ANSI_16[i])).unwrap_or(7) as u8
+}
+
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |v: u8| (0..6).min_by_key(|&i| (CUBE_LEVELS[i] as i32 - v as i32).abs()).unwrap_or(0);
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_step = (average.saturating_sub(8) / 10).min(23) as u8;
+    let gray_value = 8 + gray_step * 10;
+
+    if distance(rgb, (gray_value, gray_value, gray_value)) < distance(rgb, cube) {
+        232 + gray_step
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+impl ColorDepth {
+    /// The closest color this depth has to `color`
+    pub fn nearest(&self, color: MyColor) -> TermColor {
+        let rgb = (color.r, color.g, color.b);
+        match self {
+            ColorDepth::TrueColor => TermColor::Rgb(color.r, color.g, color.b),
+            ColorDepth::Ansi256 => TermColor::Indexed(nearest_256(rgb)),
+            ColorDepth::Ansi16 => TermColor::Indexed(nearest_16(rgb)),
+            ColorDepth::Mono => TermColor::Reset,
+        }
+    }
+
+    /// Depth of the terminal from the environment and its terminfo entry
+    pub fn detect() -> ColorDepth {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        if !var("NO_COLOR").is_empty() {
+            return ColorDepth::Mono;
+        }
+        let colorterm = var("COLORTERM").to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::TrueColor;
+        }
+        let term = var("TERM");
+        if term == "dumb" {
+            return ColorDepth::Mono;
+        }
+        if term.ends_with("-direct") || term.contains("truecolor") {
+            return ColorDepth::TrueColor;
+        }
+        let truecolor_programs = ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"];
+        if truecolor_programs.contains(&var("TERM_PROGRAM").as_str()) || !var("WT_SESSION").is_empty() {
+            return ColorDepth::TrueColor;
+        }
+        if term.is_empty() {
+            // Windows consoles don't set TERM and have had truecolor since Windows 10
+            return if cfg!(windows) { ColorDepth::TrueColor } else { ColorDepth::Mono };
+        }
+        match terminfo_max_colors(&term) {
+            Some(colors) if colors >= 1 << 24 => ColorDepth::TrueColor,
+            Some(colors) if colors >= 256 => ColorDepth::Ansi256,
+            Some(colors) if colors >= 8 => ColorDepth::Ansi16,
+            Some(_) => ColorDepth::Mono,
+            None if term.contains("256color") => ColorDepth::Ansi256,
+            None => ColorDepth::Ansi16,
+        }
+    }
+
+    /// The depth in the config if there is one, else the detected one.
+    /// Looked up once, on first use.
+    pub fn current() -> ColorDepth {
+        static CURRENT: OnceLock<ColorDepth> = OnceLock::new();
+        *CURRENT.get_or_init(|| crate::config::AppConfig::load().color_depth.unwrap_or_else(ColorDepth::detect))
+    }
+}
+
+/// Where ncurses looks for compiled terminfo entries
+fn terminfo_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = std::env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+    if let Ok(list) = std::env::var("TERMINFO_DIRS") {
+        dirs.extend(list.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+    }
+    dirs.extend(["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo", "/usr/lib/terminfo"].map(PathBuf::from));
+    dirs
+}
+
+fn terminfo_max_colors(term: &str) -> Option<u32> {
+    let first = term.chars().next()?;
+    terminfo_dirs().into_iter().find_map(|dir| {
+        // Letters as directory names on Linux, their hex code on macOS
+        [dir.join(first.to_string()).join(term), dir.join(format!("{:x}", first as u32)).join(term)]
+            .into_iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .and_then(|data| parse_max_colors(&data))
+    })
+}
+
+/// max_colors from a compiled terminfo entry, in the legacy (16-bit numbers)
+/// or extended (32-bit numbers) format
+fn parse_max_colors(data: &[u8]) -> Option<u32> {
+    let word = |i: usize| -> Option<usize> { Some(u16::from_le_bytes([*data.get(2 * i)?, *data.get(2 * i + 1)?]) as usize) };
+    let number_size = match word(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let (names, bools, numbers) = (word(1)?, word(2)?, word(3)?);
+    if numbers <= TERMINFO_MAX_COLORS {
+        return None;
+    }
+    // Numbers start on an even offset after the 12-byte header, names and booleans
+    let start = (12 + names + bools + 1) & !1;
+    let at = start + TERMINFO_MAX_COLORS * number_size;
+    let value = match number_size {
+        2 => i16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?) as i32,
+        _ => i32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?),
+    };
+    (value >= 0).then_some(value as u32)
+}
+
+#[cfg(any(feature = "cli", feature = "tui"))]
+impl From<TermColor> for ratatui::style::Color {
+    fn from(color: TermColor) -> Self {
+        use ratatui::style::Color;
+        const NAMED: [Color; 16] = [
+            Color::Black,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::Gray,
+            Color::DarkGray,
+            Color::LightRed,
+            Color::LightGreen,
+            Color::LightYellow,
+            Color::LightBlue,
+            Color::LightMagenta,
+            Color::LightCyan,
+            Color::White,
+        ];
+        match color {
+            TermColor::Reset => Color::Reset,
+            // Named, so 16-color terminals get the codes they know
+            TermColor::Indexed(i) if i < 16 => NAMED[i as usize],
+            TermColor::Indexed(i) => Color::Indexed(i),
+            TermColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl From<TermColor> for crossterm::style::Color {
+    fn from(color: TermColor) -> Self {
+        use crossterm::style::Color;
+        const NAMED: [Color; 16] = [
+            Color::Black,
+            Color::DarkRed,
+            Color::DarkGreen,
+            Color::DarkYellow,
+            Color::DarkBlue,
+            Color::DarkMagenta,
+            Color::DarkCyan,
+            Color::Grey,
+            Color::DarkGrey,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::White,
+        ];
+        match color {
+            TermColor::Reset => Color::Reset,
+            TermColor::Indexed(i) if i < 16 => NAMED[i as usize],
+            TermColor::Indexed(i) => Color::AnsiValue(i),
+            TermColor::Rgb(r, g, b) => Color::Rgb { r, g, b },
+        }
+    }
+}
+
+/// A color of the scheme for crossterm, as the terminal can show it
+#[cfg(feature = "cli")]
+impl From<MyColor> for crossterm::style::Color {
+    fn from(c: MyColor) -> Self {
+        ColorDepth::current().nearest(c).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_colors() {
+        let orange = MyColor::new(255, 155, 0, 255);
+        assert_eq!(ColorDepth::TrueColor.nearest(orange), TermColor::Rgb(255, 155, 0));
+        // 5 red, 2 green, 0 blue in the cube
+        assert_eq!(ColorDepth::Ansi256.nearest(orange), TermColor::Indexed(16 + 36 * 5 + 6 * 2));
+        assert_eq!(ColorDepth::Ansi256.nearest(MyColor::new(100, 100, 100, 255)), TermColor::Indexed(241));
+        assert_eq!(ColorDepth::Ansi16.nearest(MyColor::new(200, 30, 30, 255)), TermColor::Indexed(1));
+        assert_eq!(ColorDepth::Ansi16.nearest(MyColor::new(10, 10, 10, 255)), TermColor::Indexed(0));
+        assert_eq!(ColorDepth::Mono.nearest(orange), TermColor::Reset);
+    }
+
+    #[test]
+    fn test_terminfo_max_colors() {
+        // Header, "xterm|x\0" (8 bytes), 1 boolean and a pad byte, then 14 numbers
+        let mut entry: Vec<u8> = [0o432u16, 8, 1, 14, 0, 0].iter().flat_map(|w| w.to_le_bytes()).collect();
+        entry.extend(b"xterm|x\0");
+        entry.extend([1, 0]);
+        for i in 0..14u16 {
+            let value: u16 = if i as usize == TERMINFO_MAX_COLORS { 256 } else { 0xffff };
+            entry.extend(value.to_le_bytes());
+        }
+        assert_eq!(parse_max_colors(&entry), Some(256));
+        assert_eq!(parse_max_colors(&entry[..30]), None);
+        entry[0] = 0;
+        assert_eq!(parse_max_colors(&entry), None);
+    }
+
+    #[test]
+    fn test_depth_in_config() {
+        assert_eq!(serde_json::to_string(&ColorDepth::Ansi256).unwrap(), r#""256""#);
+        assert_eq!(serde_json::from_str::<ColorDepth>(r#""mono""#).unwrap(), ColorDepth::Mono);
+    }
+}
@@ -0,0 +1,236 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::custom_colors::MyColor;
+use crate::themes::{self, Format, Theme, ThemeError};
+
+/// Formats other tools keep their themes in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// A Base16 scheme: `scheme`/`name` and `base00`..`base0F`, flat or under `palette`
+    Base16,
+    /// A monkeytype theme: bg, main, caret, sub, subAlt, text and error colors,
+    /// as an object or as the 10-color array of a custom theme
+    Monkeytype,
+    /// Already a typeman theme
+    Typeman,
+}
+
+/// Reads a theme of any supported format, telling them apart by extension and content
+pub fn read(path: &Path) -> Result<(Theme, Source), ThemeError> {
+    let content = fs::read_to_string(path).map_err(|e| ThemeError::Io(e.to_string()))?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("theme");
+    let (mut theme, source) = match ext.as_str() {
+        "yaml" | "yml" => (from_base16(&content)?, Source::Base16),
+        "toml" => (Theme::parse(&content, Format::Toml)?, Source::Typeman),
+        "json" => match Theme::parse(&content, Format::Json) {
+            Ok(theme) => (theme, Source::Typeman),
+            Err(_) => {
+                let value: Value = serde_json::from_str(&content).map_err(|e| ThemeError::Parse(e.to_string()))?;
+                (from_monkeytype(&value)?, Source::Monkeytype)
+            }
+        },
+        _ => return Err(ThemeError::UnsupportedFormat(ext)),
+    };
+    if theme.name.trim().is_empty() {
+        theme.name = stem.to_string();
+    }
+    Ok((theme, source))
+}
+
+/// Reads `path` and saves it as a typeman theme in the themes directory.
+/// Returns the theme and where it was saved.
+pub fn import(path: &Path, name: Option<&str>, force: bool) -> Result<(Theme, PathBuf), ThemeError> {
+    let (mut theme, _) = read(path)?;
+    if let Some(name) = name {
+        theme.name = name.to_string();
+    }
+    if themes::is_builtin_name(&theme.name) {
+        return Err(ThemeError::NameTaken(theme.name));
+    }
+    let dir = themes::themes_dir().map_err(|e| ThemeError::Io(e.to_string()))?;
+    fs::create_dir_all(&dir).map_err(|e| ThemeError::Io(e.to_string()))?;
+    let target = dir.join(format!("{}.toml", file_name(&theme.name)));
+    let taken = themes::names().iter().any(|n| n.eq_ignore_ascii_case(&theme.name));
+    if (target.exists() || taken) && !force {
+        return Err(ThemeError::NameTaken(theme.name));
+    }
+    let content = theme.to_string_as(Format::Toml)?;
+    fs::write(&target, content).map_err(|e| ThemeError::Io(e.to_string()))?;
+    Ok((theme, target))
+}
+
+/// "Gruvbox dark, hard" -> "gruvbox-dark-hard"
+fn file_name(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let parts: Vec<&str> = lower.split(|c: char| !c.is_ascii_alphanumeric()).filter(|p| !p.is_empty()).collect();
+    if parts.is_empty() { "theme".to_string() } else { parts.join("-") }
+}
+
+fn color(value: Option<&str>, key: &str) -> Result<MyColor, ThemeError> {
+    let value = value.ok_or_else(|| ThemeError::Parse(format!("missing {}", key)))?;
+    MyColor::from_hex(value).ok_or_else(|| ThemeError::Parse(format!("invalid color {:?} for {}", value, key)))
+}
+
+/// Base16 files are flat YAML, so keys and values are read line by line
+/// instead of pulling in a YAML parser
+pub fn from_base16(content: &str) -> Result<Theme, ThemeError> {
+    let mut name = String::new();
+    let mut colors: Vec<(String, String)> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.split(" #").next().unwrap_or_default().trim().trim_matches(|c| c == '"' || c == '\'');
+        match key.trim() {
+            "scheme" | "name" => name = value.to_string(),
+            key if key.len() == 6 && key.starts_with("base") => colors.push((key.to_lowercase(), value.to_string())),
+            _ => {}
+        }
+    }
+    let base = |n: &str| {
+        let key = format!("base{}", n.to_lowercase());
+        color(colors.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str()), &key)
+    };
+    // Roles as the Base16 styling guide uses them: 00 background, 02 selection,
+    // 03 comments, 05 foreground, 08 red, 09 orange, 0A yellow, 0D blue
+    let bg = base("00")?;
+    let main = base("0A")?;
+    Ok(Theme {
+        name,
+        bg_color: bg,
+        main_color: main,
+        text_color: base("05")?,
+        ref_color: base("03")?,
+        incorrect_color: base("08")?,
+        border_color: Some(base("02")?),
+        dimmer_main: Some(main.mix(bg, 0.3)),
+        chart_color: Some(base("0D")?),
+        correct_color: Some(base("05")?),
+        corrected_color: Some(base("09")?),
+    })
+}
+
+/// Order of the colors in a monkeytype custom theme
+const MONKEYTYPE_ORDER: [&str; 8] = ["bg", "main", "caret", "sub", "subalt", "text", "error", "errorextra"];
+
+/// "--sub-alt-color", "subAltColor" and "sub_alt" are all "subalt"
+fn monkeytype_key(key: &str) -> String {
+    let key: String = key.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase();
+    key.strip_suffix("color").map(str::to_string).unwrap_or(key)
+}
+
+pub fn from_monkeytype(value: &Value) -> Result<Theme, ThemeError> {
+    let mut name = String::new();
+    let mut colors: Vec<(String, String)> = Vec::new();
+    let array = match value {
+        Value::Array(array) => Some(array),
+        Value::Object(map) => map.get("colors").or(map.get("customThemeColors")).and_then(Value::as_array),
+        _ => None,
+    };
+    if let Some(array) = array {
+        for (key, color) in MONKEYTYPE_ORDER.iter().zip(array) {
+            if let Some(color) = color.as_str() {
+                colors.push((key.to_string(), color.to_string()));
+            }
+        }
+    }
+    if let Value::Object(map) = value {
+        for (key, value) in map {
+            match (key.as_str(), value.as_str()) {
+                ("name", Some(value)) => name = value.to_string(),
+                (key, Some(value)) => colors.push((monkeytype_key(key), value.to_string())),
+                _ => {}
+            }
+        }
+    }
+    let get = |key: &str| colors.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    let bg = color(get("bg"), "bg")?;
+    let main = color(get("main"), "main")?;
+    let text = color(get("text"), "text")?;
+    let sub = color(get("sub"), "sub")?;
+    let error = color(get("error"), "error")?;
+    let optional = |key: &str| get(key).and_then(MyColor::from_hex);
+    Ok(Theme {
+        name,
+        bg_color: bg,
+        main_color: main,
+        text_color: text,
+        ref_color: sub,
+        incorrect_color: error,
+        border_color: Some(optional("subalt").unwrap_or(sub)),
+        dimmer_main: Some(main.mix(bg, 0.3)),
+        chart_color: Some(optional("caret").unwrap_or(main)),
+        correct_color: Some(text),
+        corrected_color: Some(optional("errorextra").unwrap_or(main)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base16_scheme() {
+        let yaml = r#"
+scheme: "Gruvbox dark, hard"
+author: "Dawid Kurek"
+base00: "1d2021" # background
+base01: "3c3836"
+base02: "504945"
+base03: "665c54"
+base04: "bdae93"
+base05: "d5c4a1"
+base06: "ebdbb2"
+base07: "fbf1c7"
+base08: "fb4934"
+base09: "fe8019"
+base0A: "fabd2f"
+base0B: "b8bb26"
+base0C: "8ec07c"
+base0D: "83a598"
+base0E: "d3869b"
+base0F: "d65d0e"
+"#;
+        let theme = from_base16(yaml).unwrap();
+        assert_eq!(theme.name, "Gruvbox dark, hard");
+        assert_eq!(theme.bg_color, MyColor::new(0x1d, 0x20, 0x21, 255));
+        assert_eq!(theme.main_color, MyColor::new(0xfa, 0xbd, 0x2f, 255));
+        assert_eq!(theme.corrected_color, MyColor::from_hex("fe8019"));
+        assert_eq!(file_name(&theme.name), "gruvbox-dark-hard");
+
+        // The newer format nests the colors under `palette`
+        let nested = yaml.replace("scheme:", "name:").replace("\nbase", "\n  base").replace("\n  base00", "\npalette:\n  base00");
+        assert_eq!(from_base16(&nested).unwrap(), theme);
+        assert!(from_base16(&yaml.replace("base08: \"fb4934\"", "")).is_err());
+    }
+
+    #[test]
+    fn test_monkeytype_theme() {
+        let object: Value = serde_json::from_str(
+            r##"{"name": "serika dark", "--bg-color": "#323437", "--main-color": "#e2b714", "--caret-color": "#e2b714",
+                "--sub-color": "#646669", "--sub-alt-color": "#2c2e31", "--text-color": "#d1d0c5",
+                "--error-color": "#ca4754", "--error-extra-color": "#7e2a33"}"##,
+        )
+        .unwrap();
+        let theme = from_monkeytype(&object).unwrap();
+        assert_eq!(theme.name, "serika dark");
+        assert_eq!(theme.ref_color, MyColor::new(0x64, 0x66, 0x69, 255));
+        assert_eq!(theme.border_color, MyColor::from_hex("#2c2e31"));
+        assert_eq!(theme.corrected_color, MyColor::from_hex("#7e2a33"));
+
+        let array: Value = serde_json::from_str(
+            r##"["#323437", "#e2b714", "#e2b714", "#646669", "#2c2e31", "#d1d0c5", "#ca4754", "#7e2a33", "#ca4754", "#7e2a33"]"##,
+        )
+        .unwrap();
+        let from_array = from_monkeytype(&array).unwrap();
+        assert_eq!(Theme { name: theme.name.clone(), ..from_array }, theme);
+    }
+}
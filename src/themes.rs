@@ -0,0 +1,305 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::color_scheme::ColorScheme;
+use crate::custom_colors::MyColor;
+
+/// User themes live here, in the config directory shared by all profiles
+pub const THEMES_DIR: &str = "themes";
+/// How often the themes directory is checked for edits
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(String),
+    Parse(String),
+    /// Only .toml and .json files are themes
+    UnsupportedFormat(String),
+    /// A built-in scheme or an earlier file already has the name
+    NameTaken(String),
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeError::Io(e) => write!(f, "{}", e),
+            ThemeError::Parse(e) => write!(f, "{}", e),
+            ThemeError::UnsupportedFormat(ext) => write!(f, "unsupported format {:?}, use .toml or .json", ext),
+            ThemeError::NameTaken(name) => write!(f, "there already is a theme called {:?}", name),
+        }
+    }
+}
+
+/// A color scheme defined in a file, with the roles of `ColorScheme`.
+/// The roles that may be left out are derived from the others.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// The file name when left out
+    #[serde(default)]
+    pub name: String,
+    pub bg_color: MyColor,
+    pub main_color: MyColor,
+    pub text_color: MyColor,
+    pub ref_color: MyColor,
+    pub incorrect_color: MyColor,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_color: Option<MyColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimmer_main: Option<MyColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart_color: Option<MyColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correct_color: Option<MyColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrected_color: Option<MyColor>,
+}
+
+/// One of the colors a scheme has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Border,
+    Ref,
+    Bg,
+    Main,
+    DimmerMain,
+    Text,
+    Chart,
+    Correct,
+    Corrected,
+    Incorrect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Result<Format, ThemeError> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+        match ext.as_str() {
+            "toml" => Ok(Format::Toml),
+            "json" => Ok(Format::Json),
+            _ => Err(ThemeError::UnsupportedFormat(ext)),
+        }
+    }
+}
+
+impl Theme {
+    pub fn color(&self, role: Role) -> MyColor {
+        match role {
+            Role::Bg => self.bg_color,
+            Role::Main => self.main_color,
+            Role::Text => self.text_color,
+            Role::Ref => self.ref_color,
+            Role::Incorrect => self.incorrect_color,
+            Role::Border => self.border_color.unwrap_or(self.ref_color),
+            Role::DimmerMain => self.dimmer_main.unwrap_or(self.main_color.mix(self.bg_color, 0.3)),
+            Role::Chart => self.chart_color.unwrap_or(self.color(Role::DimmerMain)),
+            Role::Correct => self.correct_color.unwrap_or(self.text_color),
+            Role::Corrected => self.corrected_color.unwrap_or(self.main_color),
+        }
+    }
+
+    pub fn parse(content: &str, format: Format) -> Result<Theme, ThemeError> {
+        match format {
+            Format::Toml => toml::from_str(content).map_err(|e| ThemeError::Parse(e.message().to_string())),
+            Format::Json => serde_json::from_str(content).map_err(|e| ThemeError::Parse(e.to_string())),
+        }
+    }
+
+    pub fn to_string_as(&self, format: Format) -> Result<String, ThemeError> {
+        match format {
+            Format::Toml => toml::to_string(self).map_err(|e| ThemeError::Parse(e.to_string())),
+            Format::Json => serde_json::to_string_pretty(self).map_err(|e| ThemeError::Parse(e.to_string())),
+        }
+    }
+}
+
+/// Reads a theme file, named after the file unless it has a name
+pub fn load_file(path: &Path) -> Result<Theme, ThemeError> {
+    let format = Format::from_path(path)?;
+    let content = fs::read_to_string(path).map_err(|e| ThemeError::Io(e.to_string()))?;
+    let mut theme = Theme::parse(&content, format)?;
+    if theme.name.trim().is_empty() {
+        theme.name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("theme").to_string();
+    }
+    Ok(theme)
+}
+
+pub fn themes_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(crate::paths::config_root()?.join(THEMES_DIR))
+}
+
+/// Whether a built-in scheme is called `name`, ignoring case and spaces
+pub fn is_builtin_name(name: &str) -> bool {
+    let squash = |s: &str| s.replace(' ', "").to_lowercase();
+    ColorScheme::builtin().iter().any(|s| squash(s.name()) == squash(name))
+}
+
+/// Theme names live as long as the program, so `ColorScheme` can stay `Copy`
+pub fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES.get_or_init(|| Mutex::new(HashSet::new())).lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = names.get(name) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+    names.insert(interned);
+    interned
+}
+
+/// Theme files and when they were last modified, to notice edits
+type Files = Vec<(PathBuf, Option<SystemTime>)>;
+
+struct Registry {
+    themes: Vec<Theme>,
+    errors: Vec<(PathBuf, ThemeError)>,
+    files: Files,
+    checked: Instant,
+}
+
+impl Registry {
+    fn load() -> Self {
+        let dir = themes_dir().ok();
+        if let Some(dir) = &dir {
+            let _ = fs::create_dir_all(dir);
+        }
+        let files = dir.as_deref().map(scan).unwrap_or_default();
+        let mut themes: Vec<Theme> = Vec::new();
+        let mut errors = Vec::new();
+        for (path, _) in &files {
+            match load_file(path) {
+                Ok(theme) if is_builtin_name(&theme.name) || find_in(&themes, &theme.name).is_some() => {
+                    errors.push((path.clone(), ThemeError::NameTaken(theme.name)))
+                }
+                Ok(theme) => themes.push(theme),
+                Err(e) => errors.push((path.clone(), e)),
+            }
+        }
+        Registry { themes, errors, files, checked: Instant::now() }
+    }
+}
+
+fn scan(dir: &Path) -> Files {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Files = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_file() && Format::from_path(path).is_ok())
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn find_in<'a>(themes: &'a [Theme], name: &str) -> Option<&'a Theme> {
+    themes.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+}
+
+fn registry() -> &'static RwLock<Registry> {
+    static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Registry::load()))
+}
+
+/// Reloads the themes when a file was added, edited or removed since the last
+/// check. Cheap enough to call every frame; the directory is only looked at
+/// once a second. Returns whether anything was reloaded.
+pub fn reload_if_changed() -> bool {
+    let lock = registry();
+    let changed = match lock.read() {
+        Ok(registry) if registry.checked.elapsed() >= RELOAD_INTERVAL => {
+            themes_dir().map(|dir| scan(&dir)).unwrap_or_default() != registry.files
+        }
+        _ => return false,
+    };
+    let Ok(mut registry) = lock.write() else {
+        return false;
+    };
+    if changed {
+        *registry = Registry::load();
+    } else {
+        registry.checked = Instant::now();
+    }
+    changed
+}
+
+/// Names of the user themes, in file name order
+pub fn names() -> Vec<&'static str> {
+    match registry().read() {
+        Ok(registry) => registry.themes.iter().map(|t| intern(&t.name)).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// A color of a user theme, `None` if there is no such theme (anymore)
+pub fn color(name: &str, role: Role) -> Option<MyColor> {
+    let registry = registry().read().ok()?;
+    find_in(&registry.themes, name).map(|theme| theme.color(role))
+}
+
+/// Theme files that couldn't be used, and why
+pub fn errors() -> Vec<String> {
+    match registry().read() {
+        Ok(registry) => registry.errors.iter().map(|(path, e)| format!("{}: {}", path.display(), e)).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_derive_roles() {
+        let toml = r##"
+            name = "Gruvbox"
+            bg_color = "#282828"
+            main_color = "#fabd2f"
+            text_color = "#ebdbb2"
+            ref_color = "#928374"
+            incorrect_color = "#fb4934"
+            correct_color = "#b8bb26"
+        "##;
+        let theme = Theme::parse(toml, Format::Toml).unwrap();
+        assert_eq!(theme.name, "Gruvbox");
+        assert_eq!(theme.color(Role::Correct), MyColor::new(0xb8, 0xbb, 0x26, 255));
+        // Left out, so derived
+        assert_eq!(theme.color(Role::Border), theme.ref_color);
+        assert_eq!(theme.color(Role::Corrected), theme.main_color);
+        assert_eq!(theme.color(Role::DimmerMain), MyColor::new(0xbb, 0x90, 0x2d, 255));
+
+        let json = theme.to_string_as(Format::Json).unwrap();
+        assert_eq!(Theme::parse(&json, Format::Json).unwrap(), theme);
+        assert!(matches!(
+            Theme::parse(&toml.replace("#fb4934", "red"), Format::Toml),
+            Err(ThemeError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_scheme_names() {
+        assert!(is_builtin_name("ocean dark"));
+        assert!(is_builtin_name("OceanDark"));
+        assert!(!is_builtin_name("Gruvbox"));
+        assert!(std::ptr::eq(intern("Gruvbox"), intern(&String::from("Gruvbox"))));
+
+        // Built-ins are written as before, user themes by name
+        assert_eq!(serde_json::to_string(&ColorScheme::OceanDark).unwrap(), r#""OceanDark""#);
+        let user: ColorScheme = serde_json::from_str(r#"{"User":"Gruvbox"}"#).unwrap();
+        assert_eq!(user, ColorScheme::User("Gruvbox"));
+        assert_eq!(serde_json::to_string(&user).unwrap(), r#"{"User":"Gruvbox"}"#);
+    }
+}
@@ -0,0 +1,173 @@
+use crossterm::{queue, style::Print};
+use std::collections::VecDeque;
+use std::io::{stdout, Write};
+use std::time::Instant;
+
+use crate::accessibility::{word_feedback, word_marks, word_prompt};
+use crate::config::AppConfig;
+use crate::keymap::{Action, KeyReader};
+use crate::practice;
+use crate::ui::cli::main::{poll_input, Input, RawModeGuard};
+use crate::ui::cli::results::{self, Run};
+
+/// Writes a line on its own; the terminal is in raw mode, so it needs the \r
+fn say(line: &str) {
+    let mut stdout = stdout();
+    queue!(stdout, Print(line), Print("\r\n")).unwrap();
+    stdout.flush().unwrap();
+}
+
+/// Character ranges of the words of `reference`
+fn word_spans(reference: &[char]) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in reference.iter().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, reference.len()));
+    }
+    spans
+}
+
+/// `type_loop` for screen readers: no cursor movement or colors, just lines
+/// announcing the next word, whether the last one was right and the results.
+/// Words are typed one at a time and finished with Space or Enter.
+pub fn type_loop(
+    reference: &str,
+    time_limit: Option<u64>,
+    run: &mut Run,
+    practice: Option<usize>,
+    is_correct: &mut VecDeque<i32>,
+    mode: &str,
+    config: &AppConfig,
+) -> i32 {
+    let ref_chars: Vec<char> = reference.chars().collect();
+    let spans = word_spans(&ref_chars);
+    let word = |i: usize| ref_chars[spans[i].0..spans[i].1].iter().collect::<String>();
+    if spans.is_empty() {
+        return 0;
+    }
+
+    let _raw_guard = RawModeGuard::new();
+    let mut keys = KeyReader::default();
+    let mut stdout = stdout();
+
+    if run.start_time.is_none() {
+        match (mode, time_limit) {
+            ("time", Some(limit)) => say(&format!("Type for {} seconds. The time starts with your first key.", limit)),
+            _ => say(&format!("Type {} words. The time starts with your first key.", spans.len())),
+        }
+        say("Finish each word with space. Press Escape to stop.");
+    }
+    let mut current = 0;
+    let mut typed = String::new();
+    let mut typed_chars = 0;
+    say(&word_prompt(&word(current), spans.len() == 1));
+
+    loop {
+        run.tick();
+        if time_limit.is_some_and(|limit| run.start_time.is_some_and(|start| start.elapsed().as_secs() >= limit)) {
+            say("");
+            say("Time is up.");
+            break;
+        }
+
+        let byte = match poll_input(&mut keys, &config.keymap) {
+            Some(Input::Byte(byte)) => byte,
+            Some(Input::Action(Action::Quit)) => {
+                say("");
+                return 1;
+            }
+            Some(Input::Action(_)) | None => continue,
+        };
+        if byte == 0x03 || byte == 0x04 {
+            say("");
+            return 1;
+        }
+        if run.start_time.is_none() {
+            run.start_time = Some(Instant::now());
+        }
+        run.keystroke();
+
+        let expected = word(current);
+        match byte {
+            8 | 127 => {
+                if typed.pop().is_some() {
+                    queue!(stdout, Print("\u{8} \u{8}")).unwrap();
+                }
+            }
+            // A space before anything was typed isn't meant to skip the word
+            b' ' | b'\n' if typed.is_empty() => continue,
+            b' ' | b'\n' => {}
+            _ => {
+                typed.push(byte as char);
+                queue!(stdout, Print(byte as char)).unwrap();
+            }
+        }
+        stdout.flush().unwrap();
+
+        let last = current + 1 == spans.len();
+        let finished = matches!(byte, b' ' | b'\n') || last && typed.chars().count() >= expected.chars().count();
+        if !finished {
+            continue;
+        }
+
+        let (start, end) = spans[current];
+        for (i, mark) in word_marks(&typed, &expected).into_iter().enumerate() {
+            is_correct[start + i] = mark;
+        }
+        // Letters typed past the end of the word are errors too
+        if end < ref_chars.len() {
+            is_correct[end] = if typed.chars().count() > expected.chars().count() { -1 } else { 2 };
+        }
+        typed_chars += typed.chars().count() + 1;
+        say("");
+        say(&word_feedback(&typed, &expected));
+        typed.clear();
+        current += 1;
+        if current == spans.len() {
+            break;
+        }
+        say(&word_prompt(&word(current), current + 1 == spans.len()));
+    }
+
+    if let (Some(practice_level), Some(start_time)) = (practice, run.start_time.as_ref()) {
+        let elapsed = start_time.elapsed().as_secs_f64();
+        let error_count = is_correct.iter().filter(|&&mark| mark == -1).count();
+        let accuracy = 100.0 - (error_count as f64 / ref_chars.len() as f64 * 100.0);
+        let wpm = (typed_chars as f64 / 5.0) / (elapsed / 60.0);
+        let prev_best_wpm = practice::get_prev_best_wpm(practice_level);
+        practice::save_results(elapsed, accuracy, wpm, practice_level);
+
+        if wpm >= practice::WPM_MIN {
+            say("Level passed!");
+        } else {
+            say(&format!("Reach {:.0} words per minute to pass this level.", practice::WPM_MIN));
+        }
+        if prev_best_wpm < wpm {
+            say("New highscore for this level!");
+        }
+    }
+
+    run.finish_batch(reference, is_correct);
+    let test_over = time_limit.is_none_or(|limit| run.start_time.is_some_and(|start| start.elapsed().as_secs() >= limit));
+    if test_over && run.start_time.is_some() {
+        let results = run.results();
+        say("Results.");
+        for line in results::read_out(&results, run.personal_best()) {
+            say(&line);
+        }
+        if config.save_cli_results {
+            run.save(&results, mode);
+        }
+    }
+    0
+}
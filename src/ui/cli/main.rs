@@ -5,16 +5,21 @@ use crossterm::{
 };
 use std::io::{stdout, Write};
 use std::time::Instant;
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::event::{self, Event, KeyCode};
 use std::collections::VecDeque;
-use crate::utils;
-use crate::practice;
+use crate::{accessibility, practice};
+use crate::color_scheme::ColorScheme;
+use crate::config::AppConfig;
+use crate::keymap::{Action, Dispatch, KeyPress, KeyReader, Keymap};
+use crate::terminal_colors::ColorDepth;
+use crate::ui::cli::accessible;
+use crate::ui::cli::results::{self, Run};
 
 
-struct RawModeGuard;
+pub(crate) struct RawModeGuard;
 
 impl RawModeGuard {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         crossterm::terminal::enable_raw_mode().unwrap();
         RawModeGuard
     }
@@ -26,33 +31,36 @@ impl Drop for RawModeGuard {
     }
 }
 
-fn display_results(elapsed: f64, accuracy: f64, wpm: f64, raw: f64) {
-    println!("\n\nTime: {:.0}s | Accuracy: {:.0}% | WPM: {:.0} | Raw WPM: {:.0}",
-        elapsed,
-        accuracy,
-        wpm,
-        raw
-    );
+/// Text still to type is dimmed when the terminal has no colors to set it apart
+fn ref_attribute() -> Attribute {
+    if ColorDepth::current() == ColorDepth::Mono { Attribute::Dim } else { Attribute::NormalIntensity }
 }
 
-fn initial_display(reference: &str, timer_pos: (u16, u16)) {
+fn initial_display(reference: &str, timer_pos: (u16, u16), color_scheme: ColorScheme) {
     let mut stdout = stdout();
 
     queue!(
         stdout,
         Clear(ClearType::All),
         cursor::MoveTo(0, 2),
-        SetAttribute(Attribute::Dim),
+        SetForegroundColor(color_scheme.ref_color()),
+        SetAttribute(ref_attribute()),
         Print(reference),
         SetAttribute(Attribute::Reset),
+        SetForegroundColor(color_scheme.main_color()),
         cursor::MoveTo(timer_pos.0, timer_pos.1),
         Print("Time: 00:00"),
+        SetForegroundColor(Color::Reset),
         cursor::MoveTo(0, 2)
     ).unwrap();
     stdout.flush().unwrap();
 }
 
-pub fn type_loop(reference: &str, time_limit: Option<u64>, start_time: &mut Option<Instant>, practice: Option<usize>, is_correct: &mut VecDeque<i32>, mode: &str) -> i32 {
+pub fn type_loop(reference: &str, time_limit: Option<u64>, run: &mut Run, practice: Option<usize>, is_correct: &mut VecDeque<i32>, mode: &str) -> i32 {
+    let config = AppConfig::load();
+    if accessibility::enabled(&config) {
+        return accessible::type_loop(reference, time_limit, run, practice, is_correct, mode, &config);
+    }
     let ref_chars: Vec<char> = reference.chars().collect();
     let mut stdout = stdout();
     let _raw_guard = RawModeGuard::new();
@@ -60,7 +68,9 @@ pub fn type_loop(reference: &str, time_limit: Option<u64>, start_time: &mut Opti
     let (width, _height) = crossterm::terminal::size().unwrap();
     let timer_pos = (width.saturating_sub(15), 0);
 
-    initial_display(reference, timer_pos);
+    let color_scheme = config.color_scheme;
+    let mut keys = KeyReader::default();
+    initial_display(reference, timer_pos, color_scheme);
 
     let mut user_input = String::new();
     let mut position = 0;
@@ -71,23 +81,30 @@ pub fn type_loop(reference: &str, time_limit: Option<u64>, start_time: &mut Opti
     let mut words_done = 0;
 
     loop {
+        run.tick();
         if mode == "time" {
-            update_timer(&mut stdout, timer_pos,*start_time, &mut last_update, width, position, time_limit);
+            update_timer(&mut stdout, timer_pos, run.start_time, &mut last_update, width, position, time_limit, color_scheme);
         } else {
-            update_word_count(&mut stdout, timer_pos, words_done, width, position, all_words);
+            update_word_count(&mut stdout, timer_pos, words_done, width, position, all_words, color_scheme);
         }
 
-        let byte_opt = poll_input();
-        if byte_opt.is_none() {
-            let limit = time_limit.unwrap_or(30);
-            if start_time.is_some() && start_time.unwrap().elapsed().as_secs() >= limit {
-                break;
+        let byte = match poll_input(&mut keys, &config.keymap) {
+            Some(Input::Byte(byte)) => byte,
+            // A single test has nothing else to do
+            Some(Input::Action(Action::Quit)) => {
+                clear_on_quit(&mut stdout);
+                return 1;
             }
-            std::thread::sleep(std::time::Duration::from_millis(10));
-            continue;
-        }
-
-        let byte = byte_opt.unwrap();
+            Some(Input::Action(_)) => continue,
+            None => {
+                let limit = time_limit.unwrap_or(30);
+                if run.start_time.is_some_and(|start| start.elapsed().as_secs() >= limit) {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                continue;
+            }
+        };
 
         if handle_control_keys(byte, &mut stdout) {
             return 1;
@@ -103,8 +120,10 @@ pub fn type_loop(reference: &str, time_limit: Option<u64>, start_time: &mut Opti
             is_correct,
             practice.is_some(),
             &mut words_done,
-            start_time
+            &mut run.start_time,
+            color_scheme
         );
+        run.keystroke();
 
         stdout.flush().unwrap();
 
@@ -112,7 +131,7 @@ pub fn type_loop(reference: &str, time_limit: Option<u64>, start_time: &mut Opti
             break;
         }
     }
-    if let (Some(practice_level), Some(start_time_val)) = (practice, start_time.as_ref()) {
+    if let (Some(practice_level), Some(start_time_val)) = (practice, run.start_time.as_ref()) {
         let elapsed = start_time_val.elapsed().as_secs_f64();
         let error_count = error_positions.iter().filter(|&&e| e).count();
         let accuracy = 100.0 - (error_count as f64 / reference.len() as f64 * 100.0);
@@ -145,7 +164,12 @@ pub fn type_loop(reference: &str, time_limit: Option<u64>, start_time: &mut Opti
             println!("\nNew highscore for this level!");
         }
     }
-    show_final_results(reference, start_time.expect("No start time"), is_correct);
+    run.finish_batch(reference, is_correct);
+    // A time test goes on with the next batch until the time is up
+    let test_over = time_limit.is_none_or(|limit| run.start_time.is_some_and(|start| start.elapsed().as_secs() >= limit));
+    if test_over && run.start_time.is_some() {
+        show_final_results(reference, run, mode, &config);
+    }
 
     0
 }
@@ -158,6 +182,7 @@ fn update_timer(
     width: u16,
     position: usize,
     time_limit: Option<u64>,
+    color_scheme: ColorScheme,
 ) {
     if last_update.elapsed().as_millis() > 100 || start_time.is_none(){
         let elapsed_secs = if let Some(start_time) = start_time {
@@ -178,7 +203,9 @@ fn update_timer(
             stdout,
             cursor::MoveTo(timer_pos.0, timer_pos.1),
             Clear(ClearType::UntilNewLine),
+            SetForegroundColor(color_scheme.main_color()),
             Print(format!("Time: {:02}:{:02}", display_mins, display_secs)),
+            SetForegroundColor(Color::Reset),
             cursor::MoveTo(position as u16 % width, position as u16 / width + 2)
         )
         .unwrap();
@@ -195,12 +222,15 @@ fn update_word_count(
     width: u16,
     position: usize,
     all_words: usize,
+    color_scheme: ColorScheme,
 ) {
     queue!(
         stdout,
         cursor::MoveTo(pos.0, pos.1),
         Clear(ClearType::UntilNewLine),
+        SetForegroundColor(color_scheme.main_color()),
         Print(format!("{}\\{}", words_done, all_words)),
+        SetForegroundColor(Color::Reset),
         cursor::MoveTo(position as u16 % width, position as u16 / width + 2)
     )
     .unwrap();
@@ -208,20 +238,34 @@ fn update_word_count(
     stdout.flush().unwrap();
 }
 
-fn poll_input() -> Option<u8> {
+/// A key typed into the test, or an action of the keymap
+pub(crate) enum Input {
+    Byte(u8),
+    Action(Action),
+}
+
+pub(crate) fn poll_input(keys: &mut KeyReader, keymap: &Keymap) -> Option<Input> {
     if event::poll(std::time::Duration::from_millis(10)).unwrap() {
-        if let Event::Key(KeyEvent { code, modifiers, kind: _kind, .. }) = event::read().unwrap() {
+        if let Event::Key(key_event) = event::read().unwrap() {
             #[cfg(windows)]
-            if _kind != event::KeyEventKind::Press {
+            if key_event.kind != event::KeyEventKind::Press {
                 return None;
             }
-            match (code, modifiers) {
-                (KeyCode::Char('c'), event::KeyModifiers::CONTROL) => Some(0x03), // Ctrl+C
-                (KeyCode::Char('d'), event::KeyModifiers::CONTROL) => Some(0x04), // Ctrl+D
-                (KeyCode::Char(c), _) => Some(c as u8),
-                (KeyCode::Backspace, _) => Some(8),
-                (KeyCode::Esc, _) => Some(0x1B),
-                (KeyCode::Enter, _) => Some(b'\n'),
+            // Ctrl+C and Ctrl+D always stop the test, whatever the keymap says
+            match (key_event.code, key_event.modifiers) {
+                (KeyCode::Char('c'), event::KeyModifiers::CONTROL) => return Some(Input::Byte(0x03)),
+                (KeyCode::Char('d'), event::KeyModifiers::CONTROL) => return Some(Input::Byte(0x04)),
+                _ => {}
+            }
+            match KeyPress::from_crossterm(&key_event).map(|press| keys.read(keymap, press)) {
+                Some(Dispatch::Action(action)) => return Some(Input::Action(action)),
+                Some(Dispatch::Leader) => return None,
+                Some(Dispatch::Key(_)) | None => {}
+            }
+            match key_event.code {
+                KeyCode::Char(c) => Some(Input::Byte(c as u8)),
+                KeyCode::Backspace => Some(Input::Byte(8)),
+                KeyCode::Enter => Some(Input::Byte(b'\n')),
                 _ => None,
             }
         } else {
@@ -235,19 +279,24 @@ fn poll_input() -> Option<u8> {
 fn handle_control_keys(byte: u8, stdout: &mut std::io::Stdout) -> bool {
     match byte {
         0x03 | 0x04 => {
-            queue!(
-                stdout,
-                Clear(ClearType::All),
-                cursor::MoveTo(0, 0)
-            )
-            .unwrap();
+            clear_on_quit(stdout);
             true
         }
-        0x1B => true, // ESC
         _ => false,
     }
 }
 
+/// Leaves an empty screen behind when a test is stopped, however it was quit
+fn clear_on_quit(stdout: &mut std::io::Stdout) {
+    queue!(
+        stdout,
+        Clear(ClearType::All),
+        cursor::MoveTo(0, 0)
+    )
+    .unwrap();
+    stdout.flush().unwrap();
+}
+
 #[allow(clippy::too_many_arguments)]
 fn handle_typing(
     byte: u8,
@@ -259,7 +308,8 @@ fn handle_typing(
     is_correct: &mut VecDeque<i32>,
     practice_mode: bool,
     words_done: &mut usize,
-    start_time: &mut Option<Instant>
+    start_time: &mut Option<Instant>,
+    color_scheme: ColorScheme
 ) {
     match byte {
         // backspace
@@ -274,7 +324,8 @@ fn handle_typing(
             queue!(
                 stdout,
                 cursor::MoveLeft(1),
-                SetAttribute(Attribute::Dim),
+                SetForegroundColor(color_scheme.ref_color()),
+                SetAttribute(ref_attribute()),
                 Print(ref_chars[*position]),
                 SetAttribute(Attribute::Reset),
                 cursor::MoveLeft(1),
@@ -295,7 +346,7 @@ fn handle_typing(
                 }
                 if error_positions[*position] {
                     is_correct[*position] = 1;
-                    // Corrected an error: yellow
+                    // Corrected an error
                     let char_display = if practice_mode && c == ' ' {
                         '_'
                     } else {
@@ -303,17 +354,19 @@ fn handle_typing(
                     };
                     queue!(
                         stdout,
-                        SetForegroundColor(Color::Yellow),
+                        SetForegroundColor(color_scheme.corrected_color()),
+                        SetAttribute(Attribute::Underlined),
                         Print(char_display),
+                        SetAttribute(Attribute::Reset),
                         SetForegroundColor(Color::Reset)
                     )
                     .unwrap();
                 } else {
-                    // Correct on first try: green
+                    // Correct on first try
                     is_correct[*position] = 2;
                     queue!(
                         stdout,
-                        SetForegroundColor(Color::White),
+                        SetForegroundColor(color_scheme.correct_color()),
                         Print(c),
                         SetForegroundColor(Color::Reset)
                     )
@@ -333,16 +386,20 @@ fn handle_typing(
                 if ref_char == ' ' {
                     queue!(
                         stdout,
-                        SetForegroundColor(Color::Red),
+                        SetForegroundColor(color_scheme.incorrect_color()),
+                        SetAttribute(Attribute::CrossedOut),
                         Print('_'),
+                        SetAttribute(Attribute::Reset),
                         SetForegroundColor(Color::Reset)
                     )
                 .unwrap();
                 } else {
                     queue!(
                         stdout,
-                        SetForegroundColor(Color::Red),
+                        SetForegroundColor(color_scheme.incorrect_color()),
+                        SetAttribute(Attribute::CrossedOut),
                         Print(ref_char),
+                        SetAttribute(Attribute::Reset),
                         SetForegroundColor(Color::Reset)
                     )
                     .unwrap();
@@ -355,16 +412,7 @@ fn handle_typing(
     }
 }
 
-fn show_final_results(
-    reference: &str,
-    start_time: Instant,
-    is_correct: &VecDeque<i32>,
-) {
-    let (_corrected_words, correct_words, all_words) = utils::count_correct_words(reference, is_correct);
-    let elapsed = start_time.elapsed().as_secs_f64();
-    let wpm = correct_words as f64 / (elapsed / 60.0);
-    let raw = all_words as f64 / (elapsed / 60.0);
-
+fn show_final_results(reference: &str, run: &Run, mode: &str, config: &AppConfig) {
     let term_width = crossterm::terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
     let lines = reference.len().div_ceil(term_width);
 
@@ -375,19 +423,11 @@ fn show_final_results(
     )
     .unwrap();
     stdout.flush().unwrap();
-    
-    let correct_count = is_correct.iter().filter(|&&v| v == 1 || v == 2).count();
-    let all_pressed_count = is_correct.iter().filter(|&&v| v != 0).count();
-    let accuracy = if correct_count > 0 {
-        (correct_count as f64 / all_pressed_count as f64) * 100.0
-    } else {
-        0.0
-    };
-    display_results(elapsed, accuracy, wpm, raw);
-    
-    queue!(
-        stdout,
-        cursor::MoveToNextLine(1)
-    ).unwrap();
-    stdout.flush().unwrap();
-}
\ No newline at end of file
+
+    let results = run.results();
+    let personal_best = run.personal_best();
+    results::display(&results, personal_best, config.color_scheme);
+    if config.save_cli_results {
+        run.save(&results, mode);
+    }
+}
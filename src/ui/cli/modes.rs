@@ -1,9 +1,10 @@
 use std::path::PathBuf;
 use std::fs;
-use std::time::Instant;
 use std::collections::VecDeque;
 
 use crate::ui::cli;
+use crate::ui::cli::results::Run;
+use crate::leaderboard::TestType;
 use crate::Cli;
 use crate::utils;
 use crate::practice;
@@ -20,10 +21,10 @@ pub fn wiki_mode() {
     println!("Starting Wikipedia mode test");
     
     let reference = utils::get_wiki_summary();
-    let mut start_time: Option<Instant> = None;
+    let mut run = Run::new(Some(TestType::Wiki), Language::default());
     let mut is_correct: VecDeque<i32> = VecDeque::from(vec![0; reference.len()]);
 
-    cli::main::type_loop(&reference, None, &mut start_time, None, &mut is_correct, "wiki");
+    cli::main::type_loop(&reference, None, &mut run, None, &mut is_correct, "wiki");
 }
 
 pub fn word_mode(args: &Cli) {
@@ -51,10 +52,10 @@ pub fn word_mode(args: &Cli) {
     let word_list = utils::read_first_n_words(top_words, language);
 
     let reference = utils::get_reference(punctuation, digits, &word_list, word_number);
-    let mut start_time: Option<Instant> = None;
+    let mut run = Run::new(Some(TestType::Word(word_number)), language);
     let mut is_correct: VecDeque<i32> = VecDeque::from(vec![0; reference.len()]);
 
-    cli::main::type_loop(&reference, None, &mut start_time, None, &mut is_correct, "word");
+    cli::main::type_loop(&reference, None, &mut run, None, &mut is_correct, "word");
 }
 
 pub fn time_mode(args: &Cli) {
@@ -77,16 +78,16 @@ pub fn time_mode(args: &Cli) {
     let word_list = utils::read_first_n_words(top_words, language);
 
     let batch_size = 20;
-    let mut start_time: Option<Instant> = None;
+    let mut run = Run::new(Some(TestType::Time(time_limit as u32)), language);
 
     let punctuation = args.punctuation;
     let digits = args.digits;
 
-    'outer: while start_time.is_none() || start_time.unwrap().elapsed().as_secs() < time_limit {
+    'outer: while run.start_time.is_none_or(|start| start.elapsed().as_secs() < time_limit) {
         let reference = utils::get_reference(punctuation, digits, &word_list, batch_size) + " ";
         let mut is_correct: VecDeque<i32> = VecDeque::from(vec![0; reference.len()]);
         
-        if let Some(start_time_val) = start_time {
+        if let Some(start_time_val) = run.start_time {
             let elapsed = start_time_val.elapsed().as_secs();
             let remaining_time = if time_limit > elapsed {
                 Some(time_limit - elapsed)
@@ -99,13 +100,13 @@ pub fn time_mode(args: &Cli) {
             }
         }
 
-        let res = cli::main::type_loop(&reference, Some(time_limit), &mut start_time, None, &mut is_correct, "time");
+        let res = cli::main::type_loop(&reference, Some(time_limit), &mut run, None, &mut is_correct, "time");
         if res != 0 {
             println!("Test interrupted by user.");
             break;
         }
 
-        if start_time.unwrap().elapsed().as_secs() >= time_limit {
+        if run.start_time.is_some_and(|start| start.elapsed().as_secs() >= time_limit) {
             break 'outer;
         }
     }
@@ -125,26 +126,27 @@ pub fn custom_text(path: &PathBuf) {
         }
     };
     let mut is_correct: VecDeque<i32> = VecDeque::from(vec![0; reference.len()]);
-    let mut start_time: Option<Instant> = None;
-    cli::main::type_loop(reference.as_str(), None, &mut start_time, None, &mut is_correct, "custom");
+    // Custom texts differ every time, so they aren't saved or compared
+    let mut run = Run::new(None, Language::default());
+    cli::main::type_loop(reference.as_str(), None, &mut run, None, &mut is_correct, "custom");
 }
 
 pub fn quotes() {
     println!("Starting random quote test");
     let reference = utils::get_random_quote();
-    let mut start_time: Option<Instant> = None;
+    let mut run = Run::new(Some(TestType::Quote), Language::default());
     let mut is_correct: VecDeque<i32> = VecDeque::from(vec![0; reference.len()]);
 
-    cli::main::type_loop(&reference, None, &mut start_time, None, &mut is_correct, "quote");
+    cli::main::type_loop(&reference, None, &mut run, None, &mut is_correct, "quote");
 }
 
 pub fn practice(args: &Cli) {
     let level = args.level.unwrap();
     if level.is_none() || level.unwrap() < 1 || level.unwrap() > practice::TYPING_LEVELS.len() {
         eprintln!("Please choose a level between 1 and {}.", practice::TYPING_LEVELS.len());
+        let completed = practice::completed_levels();
         for i in 0..practice::TYPING_LEVELS.len() {
-            let results_path = format!("practice_results/level_{}.txt", i + 1);
-            if practice::check_if_completed(results_path.as_str()) {
+            if completed[i] {
                 println!("✔ Level {}: {}", i + 1, practice::TYPING_LEVELS[i].0);
             } else {
                 println!("  Level {}: {}", i + 1, practice::TYPING_LEVELS[i].0);
@@ -158,8 +160,8 @@ pub fn practice(args: &Cli) {
     
     let reference = practice::create_words(chars, args.word_number.unwrap_or(Some(50)).unwrap_or(50));
     let mut is_correct: VecDeque<i32> = VecDeque::from(vec![0; reference.len()]);
-    let mut start_time: Option<Instant> = None;
-    let res = cli::main::type_loop(&reference, None, &mut start_time, Some(curr_level), &mut is_correct, "practice");
+    let mut run = Run::new(Some(TestType::Practice(curr_level + 1)), get_language_from_args(args));
+    let res = cli::main::type_loop(&reference, None, &mut run, Some(curr_level), &mut is_correct, "practice");
     if res == 1 {
         println!("Exiting practice mode.");
     }
@@ -0,0 +1,217 @@
+use crossterm::{
+    cursor, queue,
+    style::{Print, SetForegroundColor, Color},
+};
+use std::collections::VecDeque;
+use std::io::{stdout, Write};
+use std::time::Instant;
+
+use crate::anticheat;
+use crate::color_scheme::ColorScheme;
+use crate::language::Language;
+use crate::leaderboard::{self, LeaderboardEntry, TestType};
+use crate::utils;
+
+/// Widest the speed sparkline gets
+const SPARKLINE_WIDTH: usize = 60;
+
+/// A test in progress, kept across the batches of a time test
+pub struct Run {
+    pub start_time: Option<Instant>,
+    /// Where the result is saved and compared; custom texts have none
+    pub test_type: Option<TestType>,
+    pub language: Language,
+    /// Characters per minute of each second of the test
+    pub speed_per_second: Vec<f64>,
+    /// Milliseconds since the start of each keystroke
+    keystroke_times: Vec<f64>,
+    chars_this_second: usize,
+    correct_words: usize,
+    all_words: usize,
+    correct_chars: usize,
+    pressed_chars: usize,
+}
+
+impl Run {
+    pub fn new(test_type: Option<TestType>, language: Language) -> Self {
+        Run {
+            start_time: None,
+            test_type,
+            language,
+            speed_per_second: Vec::new(),
+            keystroke_times: Vec::new(),
+            chars_this_second: 0,
+            correct_words: 0,
+            all_words: 0,
+            correct_chars: 0,
+            pressed_chars: 0,
+        }
+    }
+
+    pub fn keystroke(&mut self) {
+        if let Some(start_time) = self.start_time {
+            self.keystroke_times.push(start_time.elapsed().as_secs_f64() * 1000.0);
+            self.chars_this_second += 1;
+        }
+    }
+
+    /// Records the speed of every second that passed since the last call
+    pub fn tick(&mut self) {
+        let Some(start_time) = self.start_time else {
+            return;
+        };
+        while start_time.elapsed().as_secs() as usize > self.speed_per_second.len() {
+            self.speed_per_second.push(self.chars_this_second as f64 * 60.0);
+            self.chars_this_second = 0;
+        }
+    }
+
+    /// Adds up a typed batch of text
+    pub fn finish_batch(&mut self, reference: &str, is_correct: &VecDeque<i32>) {
+        let (_corrected_words, correct_words, all_words) = utils::count_correct_words(reference, is_correct);
+        self.correct_words += correct_words;
+        self.all_words += all_words;
+        self.correct_chars += is_correct.iter().filter(|&&v| v == 1 || v == 2).count();
+        self.pressed_chars += is_correct.iter().filter(|&&v| v != 0).count();
+    }
+
+    pub fn results(&self) -> Results {
+        let elapsed = self.start_time.map(|start| start.elapsed().as_secs_f64()).unwrap_or_default();
+        let minutes = (elapsed / 60.0).max(f64::EPSILON);
+        let wpm = self.correct_words as f64 / minutes;
+        let mut speed_per_second = self.speed_per_second.clone();
+        if self.chars_this_second > 0 {
+            speed_per_second.push(self.chars_this_second as f64 * 60.0);
+        }
+        Results {
+            elapsed,
+            wpm,
+            raw: self.all_words as f64 / minutes,
+            accuracy: if self.correct_chars > 0 {
+                self.correct_chars as f64 / self.pressed_chars as f64 * 100.0
+            } else {
+                0.0
+            },
+            consistency: utils::consistency(&speed_per_second, 6.0, wpm),
+            speed_per_second,
+        }
+    }
+
+    /// Best earlier result of the same test and language
+    pub fn personal_best(&self) -> Option<f64> {
+        let test_type = self.test_type.as_ref()?;
+        leaderboard::load_history(None)
+            .ok()?
+            .into_iter()
+            .filter(|e| e.test_type == *test_type && e.language == self.language && e.flag.is_none())
+            .map(|e| e.wpm)
+            .fold(None, |best: Option<f64>, wpm| Some(best.map_or(wpm, |best| best.max(wpm))))
+    }
+
+    /// Saves the result to the leaderboard, like the TUI and GUI do. Only
+    /// called with `save_cli_results` on.
+    pub fn save(&self, results: &Results, test_mode: &str) {
+        let Some(test_type) = self.test_type.clone() else {
+            return;
+        };
+        let entry = LeaderboardEntry {
+            wpm: results.wpm,
+            accuracy: results.accuracy,
+            test_type,
+            test_mode: test_mode.to_string(),
+            word_count: self.all_words,
+            test_duration: results.elapsed,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            language: self.language,
+            flag: anticheat::analyze(&self.keystroke_times),
+        };
+        if let Err(e) = leaderboard::save_entry(&entry) {
+            eprintln!("Result not saved: {}", e);
+        }
+    }
+}
+
+pub struct Results {
+    pub elapsed: f64,
+    pub wpm: f64,
+    pub raw: f64,
+    pub accuracy: f64,
+    pub consistency: f64,
+    pub speed_per_second: Vec<f64>,
+}
+
+/// Prints the results block below the typed text, in the colors of the scheme:
+///
+/// ```text
+/// wpm 82   raw 90   acc 96%   cons 78%   time 30s
+/// ▂▃▅▆▇▇▆▅▆▇█▇▆
+/// new personal best, +4 over 78
+/// ```
+pub fn display(results: &Results, personal_best: Option<f64>, color_scheme: ColorScheme) {
+    let label: Color = color_scheme.ref_color();
+    let value: Color = color_scheme.main_color();
+    let mut stdout = stdout();
+
+    queue!(stdout, cursor::MoveToNextLine(2)).unwrap();
+    let stats = [
+        ("wpm", format!("{:.0}", results.wpm)),
+        ("raw", format!("{:.0}", results.raw)),
+        ("acc", format!("{:.0}%", results.accuracy)),
+        ("cons", format!("{:.0}%", results.consistency)),
+        ("time", format!("{:.0}s", results.elapsed)),
+    ];
+    for (name, stat) in stats {
+        queue!(
+            stdout,
+            SetForegroundColor(label),
+            Print(format!("{} ", name)),
+            SetForegroundColor(value),
+            Print(format!("{:<6}", stat)),
+        )
+        .unwrap();
+    }
+
+    let width = crossterm::terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
+    let sparkline = utils::sparkline(&results.speed_per_second, SPARKLINE_WIDTH.min(width));
+    if !sparkline.is_empty() {
+        queue!(
+            stdout,
+            cursor::MoveToNextLine(1),
+            SetForegroundColor(color_scheme.chart_color()),
+            Print(sparkline),
+        )
+        .unwrap();
+    }
+
+    let comparison = match personal_best {
+        Some(best) if results.wpm > best => {
+            Some((color_scheme.correct_color(), format!("new personal best, +{:.0} over {:.0}", results.wpm - best, best)))
+        }
+        Some(best) => Some((label, format!("personal best {:.0}, {:.0} to go", best, best - results.wpm))),
+        None => None,
+    };
+    if let Some((color, line)) = comparison {
+        queue!(stdout, cursor::MoveToNextLine(1), SetForegroundColor(color), Print(line)).unwrap();
+    }
+    queue!(stdout, SetForegroundColor(Color::Reset), cursor::MoveToNextLine(1)).unwrap();
+    stdout.flush().unwrap();
+}
+
+/// The results as sentences a screen reader can read out, one per line
+pub fn read_out(results: &Results, personal_best: Option<f64>) -> Vec<String> {
+    let mut lines = vec![
+        format!("Speed: {:.0} words per minute", results.wpm),
+        format!("Raw speed: {:.0} words per minute", results.raw),
+        format!("Accuracy: {:.0} percent", results.accuracy),
+        format!("Consistency: {:.0} percent", results.consistency),
+        format!("Time: {:.0} seconds", results.elapsed),
+    ];
+    match personal_best {
+        Some(best) if results.wpm > best => {
+            lines.push(format!("New personal best, {:.0} more than {:.0}", results.wpm - best, best))
+        }
+        Some(best) => lines.push(format!("Personal best: {:.0}, {:.0} to go", best, best - results.wpm)),
+        None => {}
+    }
+    lines
+}
@@ -0,0 +1,84 @@
+use macroquad::prelude::*;
+
+use crate::achievements::{self, AchievementsData};
+use crate::color_scheme::ColorScheme;
+use crate::keymap::{Action, Keymap};
+
+/// Arrow keys scroll, Esc closes
+pub fn handle_achievements_keys(data: &mut AchievementsData) {
+    // Keep typed characters from leaking into the test once the screen closes
+    while get_char_pressed().is_some() {}
+
+    if is_key_pressed(KeyCode::Escape) {
+        data.open = false;
+    } else if is_key_pressed(KeyCode::Up) {
+        data.scroll = data.scroll.saturating_sub(1);
+    } else if is_key_pressed(KeyCode::Down) {
+        data.scroll = (data.scroll + 1).min(achievements::all().len().saturating_sub(1));
+    }
+}
+
+/// Every achievement, the unlocked ones first with when they were unlocked
+pub fn draw_achievements(font: Option<&Font>, font_size: f32, data: &AchievementsData, color_scheme: &ColorScheme, keymap: &Keymap) {
+    let size = font_size as u16;
+    let small = f32::max(font_size / 1.7, 11.0) as u16;
+    let line_h = measure_text("Gy", font, size, 1.0).height * 1.8;
+    let x = screen_width() * 0.1;
+    let mut y = screen_height() * 0.12;
+
+    let text = |s: &str, x: f32, y: f32, font_size: u16, color: Color| {
+        draw_text_ex(
+            s,
+            x,
+            y,
+            TextParams {
+                font,
+                font_size,
+                color,
+                ..Default::default()
+            },
+        );
+    };
+
+    let list = achievements::list(&data.unlocks);
+    let unlocked = list.iter().filter(|(_, when)| when.is_some()).count();
+    text(
+        &format!("Achievements {}/{} ({})", unlocked, list.len(), crate::profile::active()),
+        x,
+        y,
+        size,
+        color_scheme.main_color(),
+    );
+    y += line_h * 1.5;
+
+    let max_rows = ((screen_height() * 0.85 - y) / line_h).max(1.0) as usize;
+    let rows = list
+        .iter()
+        .filter(|(_, when)| when.is_some())
+        .chain(list.iter().filter(|(_, when)| when.is_none()))
+        .skip(data.scroll)
+        .take(max_rows);
+    for (achievement, when) in rows {
+        let (mark, color) = match when {
+            Some(_) => ("\u{2713}", color_scheme.main_color()),
+            None => (" ", color_scheme.ref_color()),
+        };
+        let row = format!(
+            "{} {:<18}  {:<48}  {}",
+            mark,
+            achievement.name,
+            achievement.description,
+            when.as_deref().unwrap_or("")
+        );
+        text(&row, x, y, small, color);
+        y += line_h;
+    }
+
+    text(
+        &format!("↑ or ↓ to scroll, {} or Esc to close", keymap.hint(Action::Achievements)),
+        x,
+        screen_height() - screen_height() / 7.5,
+        small,
+        color_scheme.ref_color(),
+    );
+}
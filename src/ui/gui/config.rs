@@ -5,20 +5,18 @@ use std::time::{Duration, Instant};
 
 use crate::color_scheme::ColorScheme;
 use crate::language::Language;
+use crate::fonts::{self, FontChoice};
+use crate::pace::Pace;
 use crate::ui::gui::main;
 use crate::ui::gui::popup::{PopupContent, PopupStates};
-use crate::{practice, utils};
-use crate::config::AppConfig;
+use crate::{practice, presets, profile, utils};
+use crate::config::{AppConfig, Mode};
 
-fn save_config(punctuation: bool, numbers: bool, time_mode: bool, word_mode: bool, quote: bool, test_time: f32, batch_size: usize, practice_mode: bool, wiki_mode: bool, language: Language, color_scheme: ColorScheme, word_number: usize, top_words: usize, selected_practice_level: Option<usize>) {
+fn save_config(punctuation: bool, numbers: bool, mode: Mode, test_time: f32, batch_size: usize, language: Language, color_scheme: ColorScheme, word_number: usize, top_words: usize, selected_practice_level: Option<usize>) {
     let app_config = AppConfig {
         punctuation: punctuation,
         numbers: numbers,
-        time_mode: time_mode,
-        word_mode: word_mode,
-        quote: quote,
-        practice_mode: practice_mode,
-        wiki_mode: wiki_mode,
+        mode: mode,
         batch_size: batch_size,
         test_time: test_time,
         selected_level: selected_practice_level.unwrap_or(1),
@@ -26,11 +24,57 @@ fn save_config(punctuation: bool, numbers: bool, time_mode: bool, word_mode: boo
         color_scheme: color_scheme,
         word_number: word_number,
         top_words: top_words,
+        ..AppConfig::load()
     };
 
     let _ = app_config.save();
 }
 
+/// Takes over the test settings of `config`, after a profile switch or a
+/// preset, with a new reference for the mode it has
+pub fn load_settings(
+    config: &AppConfig,
+    punctuation: &mut bool,
+    numbers: &mut bool,
+    mode: &mut Mode,
+    batch_size: &mut usize,
+    test_time: &mut f32,
+    language: &mut Language,
+    color_scheme: &mut ColorScheme,
+    word_number: &mut usize,
+    top_words: &mut usize,
+    selected_practice_level: &mut Option<usize>,
+    reference: &mut String,
+    is_correct: &mut VecDeque<i32>,
+    error_positions: &mut Vec<bool>,
+    pressed_vec: &mut Vec<char>,
+    pos1: &mut usize,
+    words_done: &mut usize,
+) {
+    *punctuation = config.punctuation;
+    *numbers = config.numbers;
+    *mode = config.mode;
+    *batch_size = config.batch_size;
+    *test_time = config.test_time;
+    *language = config.language;
+    *color_scheme = config.color_scheme;
+    *word_number = config.word_number;
+    *top_words = config.top_words;
+    *selected_practice_level = Some(config.selected_level);
+    *reference = match config.mode {
+        Mode::Practice => practice::create_words(practice::TYPING_LEVELS[config.selected_level.min(practice::TYPING_LEVELS.len() - 1)].1, 50),
+        Mode::Quote => utils::get_random_quote(),
+        Mode::Wiki => utils::get_wiki_summary(),
+        Mode::Words => utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), usize::min(*batch_size, 100)),
+        Mode::Time => utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), *batch_size),
+    };
+    *is_correct = VecDeque::from(vec![0; reference.chars().count()]);
+    *error_positions = vec![false; reference.chars().count()];
+    pressed_vec.clear();
+    *pos1 = 0;
+    *words_done = 0;
+}
+
 fn draw_toggle_button(
     x: f32,
     y: f32,
@@ -107,13 +151,10 @@ pub fn update_game_state(
     game_started: &mut bool,
     game_over: &mut bool,
     test_time: f32,
-    time_mode: bool,
+    mode: Mode,
     words_done: &mut usize,
     errors_this_second: &mut f64,
-    practice_mode: &mut bool,
     practice_menu: bool,
-    wiki_mode: bool,
-    quote: bool,
     word_number: usize,
 ) {
     if !*game_started
@@ -126,7 +167,7 @@ pub fn update_game_state(
             errors_this_second,
             &mut false,
             &mut vec![false; reference.chars().count()],
-            *practice_mode,
+            mode == Mode::Practice,
             practice_menu,
             *game_over,
         )
@@ -137,7 +178,7 @@ pub fn update_game_state(
 
     if *game_started && !*game_over {
         *timer = start_time.elapsed();
-        if (timer.as_secs_f32() >= test_time && time_mode) || (*pos1 >= reference.chars().count() && (wiki_mode || quote)) || (*words_done >= word_number && !wiki_mode && !quote)  {
+        if (timer.as_secs_f32() >= test_time && mode == Mode::Time) || (*pos1 >= reference.chars().count() && matches!(mode, Mode::Wiki | Mode::Quote)) || (*words_done >= word_number && !matches!(mode, Mode::Wiki | Mode::Quote)) {
             *game_over = true;
         }
     }
@@ -179,9 +220,7 @@ pub fn handle_settings_buttons(
     _word_list: &[String],
     punctuation: &mut bool,
     numbers: &mut bool,
-    quote: &mut bool,
-    time_mode: &mut bool,
-    word_mode: &mut bool,
+    mode: &mut Mode,
     pressed_vec: &mut Vec<char>,
     is_correct: &mut VecDeque<i32>,
     pos1: &mut usize,
@@ -202,16 +241,17 @@ pub fn handle_settings_buttons(
     selected_config: &mut String,
     practice_menu: &mut bool,
     selected_practice_level: &mut Option<usize>,
-    practice_mode: &mut bool,
     saved_results: &mut bool,
     error_positions: &mut Vec<bool>,
     language: &mut Language,
     color_scheme: &mut crate::color_scheme::ColorScheme,
-    wiki_mode: &mut bool,
     menu_buttons_times: &mut std::collections::HashMap<String, Instant>,
     popup_states: &mut PopupStates,
     top_words: &mut usize,
     word_number: &mut usize,
+    font_choice: &mut FontChoice,
+    text_scale: &mut f32,
+    line_spacing: &mut f32,
 ) -> bool {
 
     let btn_y = screen_height() / 5.0;
@@ -238,7 +278,7 @@ pub fn handle_settings_buttons(
             "|",
             "|",
             divider,
-            !*quote && !*practice_mode && !*wiki_mode,
+            !matches!(*mode, Mode::Quote | Mode::Practice | Mode::Wiki),
         ),
         (
             "punctuation",
@@ -248,7 +288,7 @@ pub fn handle_settings_buttons(
                 "! punct"
             },
             *punctuation,
-            !*quote && !*practice_mode && !*wiki_mode,
+            !matches!(*mode, Mode::Quote | Mode::Practice | Mode::Wiki),
         ),
         (
             "numbers",
@@ -258,13 +298,13 @@ pub fn handle_settings_buttons(
                 "# num"
             },
             *numbers,
-            !*quote && !*practice_mode && !*wiki_mode,
+            !matches!(*mode, Mode::Quote | Mode::Practice | Mode::Wiki),
         ),
         ("|", "|", divider, true),
-        ("time", "+ time", *time_mode, true),
-        ("words", "+ words", *word_mode, true),
-        ("quote", "quote", *quote, true),
-        ("practice", "practice", *practice_mode, true),
+        ("time", "+ time", *mode == Mode::Time, true),
+        ("words", "+ words", *mode == Mode::Words, true),
+        ("quote", "quote", *mode == Mode::Quote, true),
+        ("practice", "practice", *mode == Mode::Practice, true),
         (
             "wikipedia",
             if screen_width() > screen_height() && screen_width() > 1500.0 {
@@ -272,12 +312,12 @@ pub fn handle_settings_buttons(
             } else {
                 "wiki"
             },
-            *wiki_mode,
+            *mode == Mode::Wiki,
             true,
         ),
     ];
 
-    let popup_opened = popup_states.language.visible || popup_states.color_scheme.visible || popup_states.time_selection.visible || popup_states.word_number_selection.visible || popup_states.settings.visible || popup_states.batch_size_selection.visible || popup_states.top_words_selection.visible;
+    let popup_opened = popup_states.language.visible || popup_states.color_scheme.visible || popup_states.time_selection.visible || popup_states.word_number_selection.visible || popup_states.settings.visible || popup_states.batch_size_selection.visible || popup_states.top_words_selection.visible || popup_states.profile.visible || popup_states.pace.visible || popup_states.preset.visible || popup_states.font.visible || popup_states.text_size.visible || popup_states.line_spacing.visible;
 
     if is_key_pressed(KeyCode::Up) {
         if !popup_opened {
@@ -358,10 +398,10 @@ pub fn handle_settings_buttons(
             };
             popup_states.language.visible = false;
             popup_states.language.hide();
-            if *word_mode || *time_mode {
-                if *time_mode {
+            if matches!(*mode, Mode::Time | Mode::Words) {
+                if *mode == Mode::Time {
                     *reference = utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), *batch_size);
-                } else if *word_mode {
+                } else if *mode == Mode::Words {
                     *reference = utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), usize::min(*batch_size, *batch_size));
                 }
                 *is_correct = VecDeque::from(vec![0; reference.chars().count()]);
@@ -372,18 +412,7 @@ pub fn handle_settings_buttons(
             }
             return false;
         } else if popup_states.color_scheme.visible {
-            *color_scheme = match popup_states.color_scheme.selected {
-                0 => ColorScheme::Default,
-                1 => ColorScheme::Dark,
-                2 => ColorScheme::Light,
-                3 => ColorScheme::Monochrome,
-                4 => ColorScheme::Ocean,
-                5 => ColorScheme::OceanDark,
-                6 => ColorScheme::Forest,
-                7 => ColorScheme::ForestDark,
-                8 => ColorScheme::Pink,
-                _ => ColorScheme::Default,
-            };
+            *color_scheme = ColorScheme::all().get(popup_states.color_scheme.selected).copied().unwrap_or_default();
             popup_states.color_scheme.visible = false;
             popup_states.color_scheme.hide();
             return false;
@@ -400,7 +429,7 @@ pub fn handle_settings_buttons(
             *reference = utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), *batch_size);
             popup_states.time_selection.visible = false;
             popup_states.time_selection.hide();
-            save_config(*punctuation, *numbers, *time_mode, *word_mode, *quote, *test_time, *batch_size, *practice_mode, *wiki_mode, *language, *color_scheme, *word_number, *top_words, *selected_practice_level);
+            save_config(*punctuation, *numbers, *mode, *test_time, *batch_size, *language, *color_scheme, *word_number, *top_words, *selected_practice_level);
             return false;
         } else if popup_states.word_number_selection.visible {
             *word_number = match popup_states.word_number_selection.selected {
@@ -414,7 +443,7 @@ pub fn handle_settings_buttons(
             *reference = utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), usize::min(*batch_size, 100));
             popup_states.word_number_selection.visible = false;
             popup_states.word_number_selection.hide();
-            save_config(*punctuation, *numbers, *time_mode, *word_mode, *quote, *test_time, *batch_size, *practice_mode, *wiki_mode, *language, *color_scheme, *word_number, *top_words, *selected_practice_level);
+            save_config(*punctuation, *numbers, *mode, *test_time, *batch_size, *language, *color_scheme, *word_number, *top_words, *selected_practice_level);
             return false;
         } else if popup_states.batch_size_selection.visible {
             *batch_size = match popup_states.batch_size_selection.selected {
@@ -424,9 +453,9 @@ pub fn handle_settings_buttons(
                 3 => 100,
                 _ => 50,
             };
-            if *time_mode {
+            if *mode == Mode::Time {
                 *reference = utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), *batch_size);
-            } else if *word_mode {
+            } else if *mode == Mode::Words {
                 *reference = utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), usize::min(*batch_size, 100));
             }
             popup_states.batch_size_selection.visible = false;
@@ -440,14 +469,108 @@ pub fn handle_settings_buttons(
                 3 => 1000,
                 _ => 500,
             };
-            if *time_mode {
+            if *mode == Mode::Time {
                 *reference = utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), *batch_size);
-            } else if *word_mode {
+            } else if *mode == Mode::Words {
                 *reference = utils::get_reference(*punctuation, *numbers, &utils::read_first_n_words(*top_words, *language), usize::min(*batch_size, 100));
             }
             popup_states.top_words_selection.visible = false;
             popup_states.top_words_selection.hide();
             return false;
+        } else if popup_states.profile.visible {
+            popup_states.profile.visible = false;
+            popup_states.profile.hide();
+            let profiles = profile::list();
+            if let Some(name) = profiles.get(popup_states.profile.selected) {
+                if *name != profile::active() {
+                    save_config(*punctuation, *numbers, *mode, *test_time, *batch_size, *language, *color_scheme, *word_number, *top_words, *selected_practice_level);
+                    match profile::set_active(name) {
+                        Ok(()) => load_settings(
+                            &AppConfig::load(),
+                            punctuation,
+                            numbers,
+                            mode,
+                            batch_size,
+                            test_time,
+                            language,
+                            color_scheme,
+                            word_number,
+                            top_words,
+                            selected_practice_level,
+                            reference,
+                            is_correct,
+                            error_positions,
+                            pressed_vec,
+                            pos1,
+                            words_done,
+                        ),
+                        Err(e) => eprintln!("Failed to switch profile: {}", e),
+                    }
+                }
+            }
+            return false;
+        } else if popup_states.preset.visible {
+            popup_states.preset.visible = false;
+            popup_states.preset.hide();
+            let names: Vec<String> = AppConfig::load().presets.into_iter().map(|p| p.name).collect();
+            if let Some(name) = names.get(popup_states.preset.selected) {
+                save_config(*punctuation, *numbers, *mode, *test_time, *batch_size, *language, *color_scheme, *word_number, *top_words, *selected_practice_level);
+                match presets::apply_named(name) {
+                    Ok(config) => load_settings(
+                        &config,
+                        punctuation,
+                        numbers,
+                        mode,
+                        batch_size,
+                        test_time,
+                        language,
+                        color_scheme,
+                        word_number,
+                        top_words,
+                        selected_practice_level,
+                        reference,
+                        is_correct,
+                        error_positions,
+                        pressed_vec,
+                        pos1,
+                        words_done,
+                    ),
+                    Err(e) => eprintln!("Failed to apply preset: {}", e),
+                }
+            }
+            return false;
+        } else if popup_states.pace.visible {
+            popup_states.pace.visible = false;
+            popup_states.pace.hide();
+            if let Some(&pace) = Pace::all().get(popup_states.pace.selected) {
+                let app_config = AppConfig { pace, ..AppConfig::load() };
+                let _ = app_config.save();
+            }
+            return false;
+        } else if popup_states.font.visible {
+            popup_states.font.visible = false;
+            popup_states.font.hide();
+            if let Some(font) = FontChoice::all().into_iter().nth(popup_states.font.selected) {
+                *font_choice = font.clone();
+                let _ = AppConfig { font, ..AppConfig::load() }.save();
+            }
+            return false;
+        } else if popup_states.text_size.visible {
+            popup_states.text_size.visible = false;
+            popup_states.text_size.hide();
+            if let Some(&scale) = fonts::TEXT_SCALES.get(popup_states.text_size.selected) {
+                *text_scale = scale;
+                let _ = AppConfig { text_scale: scale, ..AppConfig::load() }.save();
+            }
+            return false;
+        } else if popup_states.line_spacing.visible {
+            popup_states.line_spacing.visible = false;
+            popup_states.line_spacing.hide();
+            if let Some(&spacing) = fonts::LINE_SPACINGS.get(popup_states.line_spacing.selected) {
+                *line_spacing = spacing;
+                let _ = AppConfig { line_spacing: spacing, ..AppConfig::load() }.save();
+            }
+            return false;
         } else if popup_states.settings.visible {
             if popup_states.settings.selected == 0 {
                 popup_states.color_scheme.visible = true;
@@ -464,6 +587,28 @@ pub fn handle_settings_buttons(
                 popup_states.batch_size_selection.visible = true;
             } else if popup_states.settings.selected == 3 {
                 popup_states.top_words_selection.visible = true;
+            } else if popup_states.settings.selected == 4 {
+                popup_states.profile.visible = true;
+                let active = profile::active();
+                popup_states.profile.selected = profile::list().iter().position(|p| *p == active).unwrap_or(0);
+            } else if popup_states.settings.selected == 5 {
+                popup_states.pace.visible = true;
+                let pace = AppConfig::load().pace;
+                popup_states.pace.selected = Pace::all().iter().position(|&p| p == pace).unwrap_or(0);
+            } else if popup_states.settings.selected == 6 {
+                popup_states.preset.visible = true;
+                let config = AppConfig::load();
+                let current = config.preset.unwrap_or_default();
+                popup_states.preset.selected = config.presets.iter().position(|p| p.name == current).unwrap_or(0);
+            } else if popup_states.settings.selected == 7 {
+                popup_states.font.visible = true;
+                popup_states.font.selected = FontChoice::all().iter().position(|f| f == font_choice).unwrap_or(0);
+            } else if popup_states.settings.selected == 8 {
+                popup_states.text_size.visible = true;
+                popup_states.text_size.selected = nearest(&fonts::TEXT_SCALES, *text_scale);
+            } else if popup_states.settings.selected == 9 {
+                popup_states.line_spacing.visible = true;
+                popup_states.line_spacing.selected = nearest(&fonts::LINE_SPACINGS, *line_spacing);
             }
         }
 
@@ -472,24 +617,20 @@ pub fn handle_settings_buttons(
                 &selected_config,
                 punctuation,
                 numbers,
-                time_mode,
-                word_mode,
-                quote,
+                mode,
                 practice_menu,
                 selected_practice_level,
-                practice_mode,
                 language,
                 popup_states,
-                wiki_mode,
             );
         }
 
-        save_config(*punctuation, *numbers, *time_mode, *word_mode, *quote, *test_time, *batch_size, *practice_mode, *wiki_mode, *language, *color_scheme, *word_number, *top_words, *selected_practice_level);
+        save_config(*punctuation, *numbers, *mode, *test_time, *batch_size, *language, *color_scheme, *word_number, *top_words, *selected_practice_level);
 
         if !popup_states.settings.visible {
-            if *quote {
+            if *mode == Mode::Quote {
                 *reference = utils::get_random_quote();
-            } else if *practice_mode {
+            } else if *mode == Mode::Practice {
                 *reference = practice::create_words(
                     practice::TYPING_LEVELS[selected_practice_level.unwrap_or(0)].1,
                     *batch_size,
@@ -497,7 +638,7 @@ pub fn handle_settings_buttons(
                 if let Some(time) = menu_buttons_times.get_mut("practice") {
                     *time = Instant::now();
                 }
-            } else if *wiki_mode {
+            } else if *mode == Mode::Wiki {
                 *reference = utils::get_wiki_summary();
                 if let Some(time) = menu_buttons_times.get_mut("wiki") {
                     *time = Instant::now();
@@ -597,17 +738,13 @@ pub fn handle_settings_buttons(
                 label,
                 punctuation,
                 numbers,
-                time_mode,
-                word_mode,
-                quote,
+                mode,
                 practice_menu,
                 selected_practice_level,
-                practice_mode,
                 language,
                 popup_states,
-                wiki_mode,
             );
-            if *quote {
+            if *mode == Mode::Quote {
                 *reference = utils::get_random_quote();
                 *is_correct = VecDeque::from(vec![0; reference.chars().count()]);
                 *error_positions = vec![false; is_correct.len()];
@@ -665,6 +802,18 @@ pub fn handle_settings_buttons(
         popup_states.batch_size_selection.draw(font, color_scheme, PopupContent::BatchSizeSelection);
     } else if popup_states.top_words_selection.visible {
         popup_states.top_words_selection.draw(font, color_scheme, PopupContent::TopWordsSelection);
+    } else if popup_states.profile.visible {
+        popup_states.profile.draw(font, color_scheme, PopupContent::Profile);
+    } else if popup_states.pace.visible {
+        popup_states.pace.draw(font, color_scheme, PopupContent::Pace);
+    } else if popup_states.preset.visible {
+        popup_states.preset.draw(font, color_scheme, PopupContent::Preset);
+    } else if popup_states.font.visible {
+        popup_states.font.draw(font, color_scheme, PopupContent::Font);
+    } else if popup_states.text_size.visible {
+        popup_states.text_size.draw(font, color_scheme, PopupContent::TextSize);
+    } else if popup_states.line_spacing.visible {
+        popup_states.line_spacing.draw(font, color_scheme, PopupContent::LineSpacing);
     } else if popup_states.settings.visible {
         popup_states.settings.draw(font, color_scheme, PopupContent::Settings);
     }
@@ -672,19 +821,22 @@ pub fn handle_settings_buttons(
     any_button_hovered
 }
 
+/// Index of the option closest to `value`, for values set outside the popup
+fn nearest(options: &[f32], value: f32) -> usize {
+    (0..options.len())
+        .min_by(|&a, &b| (options[a] - value).abs().total_cmp(&(options[b] - value).abs()))
+        .unwrap_or(0)
+}
+
 fn update_config(
     label: &str,
     punctuation: &mut bool,
     numbers: &mut bool,
-    time_mode: &mut bool,
-    word_mode: &mut bool,
-    quote: &mut bool,
+    mode: &mut Mode,
     practice_menu: &mut bool,
     selected_practice_level: &mut Option<usize>,
-    practice_mode: &mut bool,
     language: &mut Language,
     popup_states: &mut PopupStates,
-    wiki_mode: &mut bool,
 ) {
     match label {
         "punctuation" => {
@@ -694,43 +846,24 @@ fn update_config(
             *numbers = !*numbers;
         }
         "time" => {
-            *time_mode = true;
-            *word_mode = false;
-            *quote = false;
-            *practice_mode = false;
-            *wiki_mode = false;
+            *mode = Mode::Time;
         }
         "words" => {
-            *word_mode = true;
-            *time_mode = false;
-            *quote = false;
-            *practice_mode = false;
-            *wiki_mode = false;
+            *mode = Mode::Words;
         }
         "quote" => {
-            *quote = true;
+            *mode = Mode::Quote;
             *punctuation = false;
             *numbers = false;
-            *time_mode = false;
-            *word_mode = false;
-            *practice_mode = false;
-            *wiki_mode = false;
         }
         "practice" => {
-            *quote = false;
             *punctuation = false;
             *numbers = false;
-            *time_mode = false;
-            *word_mode = false;
             *practice_menu = true;
             *selected_practice_level = Some(practice::get_first_not_done());
         }
         "wikipedia" => {
-            *wiki_mode = true;
-            *time_mode = false;
-            *word_mode = false;
-            *practice_mode = false;
-            *quote = false;
+            *mode = Mode::Wiki;
         }
         "english" => {
             *language = Language::English;
@@ -0,0 +1,168 @@
+use macroquad::prelude::*;
+
+use crate::color_scheme::ColorScheme;
+use crate::config::AppConfig;
+use crate::hotseat::{self, HotseatGame, HotseatPhase};
+use crate::ui::gui::main::ROBOTO_MONO;
+use crate::ui::gui::race::wrap;
+
+/// Runs a hot-seat game in the GUI window until everyone has typed or Esc is pressed
+pub async fn hotseat_main_async(mut game: HotseatGame) {
+    let font = load_ttf_font_from_bytes(ROBOTO_MONO).unwrap();
+    let color_scheme: ColorScheme = AppConfig::load().color_scheme;
+
+    loop {
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() {
+                game.type_char(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            game.backspace();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            match game.phase {
+                HotseatPhase::Handoff => game.begin_turn(),
+                HotseatPhase::Done => break,
+                HotseatPhase::Typing => {}
+            }
+        }
+
+        clear_background(color_scheme.bg_color());
+        draw_hotseat(&font, &game, &color_scheme);
+        next_frame().await;
+    }
+}
+
+fn player_color(game: &HotseatGame, name: &str) -> Color {
+    let index = game.players.iter().position(|p| p == name).unwrap_or(0);
+    let (r, g, b) = hotseat::player_color(index);
+    Color::from_rgba(r, g, b, 255)
+}
+
+fn draw_hotseat(font: &Font, game: &HotseatGame, color_scheme: &ColorScheme) {
+    let font_size = if screen_width() > 800.0 { 28.0 } else { 20.0 };
+    let small = (font_size * 0.7) as u16;
+    let line_h = font_size * 1.6;
+    let x = screen_width() * 0.1;
+    let width = screen_width() * 0.8;
+    let mut y = screen_height() * 0.12;
+
+    let text = |s: &str, x: f32, y: f32, size: u16, color: Color| {
+        draw_text_ex(s, x, y, TextParams { font: Some(font), font_size: size, color, ..Default::default() });
+    };
+
+    text("TypeMan hot-seat", x, y, font_size as u16, color_scheme.main_color());
+    y += line_h * 1.5;
+
+    match game.phase {
+        HotseatPhase::Handoff => {
+            let name = game.current_player().unwrap_or_default();
+            text(&format!("Turn {} of {}", game.turn + 1, game.players.len()), x, y, small, color_scheme.ref_color());
+            y += line_h * 1.5;
+            text(&format!("Pass the keyboard to {}", name), x, y, font_size as u16, color_scheme.main_color());
+            y += line_h;
+            text("Press Enter when ready - the clock starts with your first key", x, y, small, color_scheme.text_color());
+            // Times only, so the next player isn't chasing a number before typing
+            y += line_h * 0.5;
+            for r in &game.results {
+                y += line_h;
+                text(&format!("{} done in {:.1}s", r.name, r.time), x, y, small, color_scheme.ref_color());
+            }
+        }
+        HotseatPhase::Typing => {
+            let typing = game.typing.as_ref();
+            let name = game.current_player().unwrap_or_default();
+            let status = match typing {
+                Some(t) => format!("{}  {:.0} wpm  {:.0}s", name, t.wpm(), t.elapsed().as_secs_f64()),
+                None => format!("{}  start typing", name),
+            };
+            text(&status, x, y, small, color_scheme.main_color());
+            y += line_h * 1.5;
+
+            let reference: Vec<char> = game.text.chars().collect();
+            let cursor = typing.map(|t| t.typed.len()).unwrap_or(0);
+            let char_w = measure_text("G", Some(font), font_size as u16, 1.0).width;
+            for (start, end) in wrap(&reference, (width / char_w) as usize) {
+                for i in start..end {
+                    let correct = typing.and_then(|t| t.is_correct(i));
+                    let color = match correct {
+                        Some(true) => color_scheme.correct_color(),
+                        Some(false) => color_scheme.incorrect_color(),
+                        None => color_scheme.ref_color(),
+                    };
+                    let cx = x + (i - start) as f32 * char_w;
+                    text(&reference[i].to_string(), cx, y, font_size as u16, color);
+                    if correct == Some(false) {
+                        draw_line(cx, y - font_size * 0.3, cx + char_w, y - font_size * 0.3, 2.0, color);
+                    }
+                    if i == cursor {
+                        draw_line(cx, y + 4.0, cx + char_w, y + 4.0, 2.0, color_scheme.main_color());
+                    }
+                }
+                y += line_h;
+            }
+        }
+        HotseatPhase::Done => {
+            text(&format!("{:<6}{:<16}{:>7}{:>7}{:>8}", "Place", "Player", "WPM", "Acc%", "Time"), x, y, small, color_scheme.main_color());
+            for (i, r) in game.standings().iter().enumerate() {
+                y += line_h;
+                let row = format!("{:<6}{:<16}{:>7.1}{:>7.1}{:>7.1}s", i + 1, r.name, r.wpm, r.accuracy, r.time);
+                text(&row, x, y, small, player_color(game, &r.name));
+            }
+            y += line_h;
+            draw_speed_chart(font, game, Rect::new(x, y, width, screen_height() * 0.8 - y), color_scheme);
+        }
+    }
+
+    let footer = match game.phase {
+        HotseatPhase::Handoff => "↵ - start your turn, Esc - quit",
+        HotseatPhase::Typing => "Esc - quit",
+        HotseatPhase::Done => "↵ or Esc - quit",
+    };
+    text(footer, x, screen_height() - screen_height() / 7.5, small, color_scheme.ref_color());
+}
+
+/// Everyone's WPM per second on one chart, a line per player
+fn draw_speed_chart(font: &Font, game: &HotseatGame, area: Rect, color_scheme: &ColorScheme) {
+    if area.h < 40.0 {
+        return;
+    }
+    let max_time = game.results.iter().map(|r| r.speed_per_second.len()).max().unwrap_or(1).max(2) as f32;
+    let max_wpm = game
+        .results
+        .iter()
+        .flat_map(|r| r.speed_per_second.iter().map(|cpm| cpm / 5.0))
+        .fold(60.0_f64, f64::max) as f32
+        * 1.1;
+    let label = |s: &str, x: f32, y: f32| {
+        draw_text_ex(s, x, y, TextParams { font: Some(font), font_size: 14, color: color_scheme.ref_color(), ..Default::default() });
+    };
+
+    draw_line(area.x, area.y, area.x, area.bottom(), 1.0, color_scheme.border_color());
+    draw_line(area.x, area.bottom(), area.right(), area.bottom(), 1.0, color_scheme.border_color());
+    label(&format!("{:.0} wpm", max_wpm), area.x + 4.0, area.y + 12.0);
+    label(&format!("{:.0}s", max_time), area.right() - 24.0, area.bottom() + 16.0);
+
+    let point = |second: usize, wpm: f64| {
+        (
+            area.x + second as f32 / (max_time - 1.0) * area.w,
+            area.bottom() - wpm as f32 / max_wpm * area.h,
+        )
+    };
+    for (n, r) in game.results.iter().enumerate() {
+        let color = player_color(game, &r.name);
+        for (i, pair) in r.speed_per_second.windows(2).enumerate() {
+            let (x1, y1) = point(i, pair[0] / 5.0);
+            let (x2, y2) = point(i + 1, pair[1] / 5.0);
+            draw_line(x1, y1, x2, y2, 2.0, color);
+        }
+        // Legend in the top right corner
+        let legend_y = area.y + 14.0 + n as f32 * 18.0;
+        draw_line(area.right() - 120.0, legend_y - 4.0, area.right() - 100.0, legend_y - 4.0, 2.0, color);
+        label(&r.name, area.right() - 94.0, legend_y);
+    }
+}
@@ -0,0 +1,141 @@
+use macroquad::prelude::*;
+
+use crate::color_scheme::ColorScheme;
+use crate::keymap::{Action, Keymap};
+use crate::leaderboard::{LeaderboardData, LeaderboardView, TestType};
+
+fn test_type_label(test_type: &TestType) -> String {
+    match test_type {
+        TestType::Time(secs) => format!("{}s", secs),
+        TestType::Word(words) => format!("{}w", words),
+        TestType::Quote => "Quote".to_string(),
+        TestType::Practice(level) => format!("L{}", level),
+        TestType::Wiki => "Wiki".to_string(),
+        TestType::Race(place) => format!("Race#{}", place),
+        TestType::Daily(date) => format!("Daily {}", date.format("%m-%d")),
+    }
+}
+
+/// Arrow keys move the selection and switch tabs, Esc closes
+pub fn handle_leaderboard_keys(leaderboard: &mut LeaderboardData) {
+    // Keep typed characters from leaking into the test once the screen closes
+    while get_char_pressed().is_some() {}
+
+    if is_key_pressed(KeyCode::Escape) {
+        leaderboard.open = false;
+    } else if is_key_pressed(KeyCode::Up) && leaderboard.selected > 0 {
+        leaderboard.selected -= 1;
+    } else if is_key_pressed(KeyCode::Down)
+        && leaderboard.selected < leaderboard.entries.len().saturating_sub(1)
+    {
+        leaderboard.selected += 1;
+    } else if is_key_pressed(KeyCode::Left) {
+        leaderboard.cycle_view(false);
+    } else if is_key_pressed(KeyCode::Right) {
+        leaderboard.cycle_view(true);
+    }
+}
+
+pub fn draw_leaderboard(
+    font: Option<&Font>,
+    font_size: f32,
+    leaderboard: &LeaderboardData,
+    color_scheme: &ColorScheme,
+    keymap: &Keymap,
+) {
+    let size = font_size as u16;
+    let small = f32::max(font_size / 1.7, 11.0) as u16;
+    let line_h = measure_text("Gy", font, size, 1.0).height * 1.8;
+    let x = screen_width() * 0.1;
+    let mut y = screen_height() * 0.12;
+
+    let text = |s: &str, x: f32, y: f32, font_size: u16, color: Color| {
+        draw_text_ex(
+            s,
+            x,
+            y,
+            TextParams {
+                font,
+                font_size,
+                color,
+                ..Default::default()
+            },
+        );
+    };
+
+    // Tabs
+    let mut tab_x = x;
+    for view in LeaderboardView::all() {
+        let label = match view {
+            LeaderboardView::Local => format!("Local ({})", crate::profile::active()),
+            _ => view.to_string(),
+        };
+        let color = if view == leaderboard.view {
+            color_scheme.main_color()
+        } else {
+            color_scheme.ref_color()
+        };
+        text(&label, tab_x, y, size, color);
+        let width = measure_text(&label, font, size, 1.0).width;
+        if view == leaderboard.view {
+            draw_line(tab_x, y + 8.0, tab_x + width, y + 8.0, 3.0, color);
+        }
+        tab_x += width + font_size * 1.5;
+    }
+    y += line_h * 1.5;
+
+    if leaderboard.entries.is_empty() {
+        let message = if leaderboard.view == LeaderboardView::Team && crate::team::configured().is_none() {
+            "No team set up yet. Run 'typeman team join <DIR> --name <NAME>' with a shared folder."
+        } else {
+            "No typing test results yet. Complete a test to see your scores here!"
+        };
+        text(message, x, y, small, color_scheme.ref_color());
+    } else {
+        let owner_header = match leaderboard.view {
+            LeaderboardView::Local => "",
+            LeaderboardView::AllProfiles => "Profile",
+            LeaderboardView::Team => "Player",
+        };
+        let header = format!(
+            "{:>4}  {:<10}  {:<6}  {:>6}  {:>6}  {:<12}  {}",
+            "#", "Date", "Type", "WPM", "Acc%", owner_header, "Note"
+        );
+        text(&header, x, y, small, color_scheme.main_color());
+        y += line_h;
+
+        let max_rows = ((screen_height() * 0.85 - y) / line_h).max(1.0) as usize;
+        let offset = leaderboard.selected.saturating_sub(max_rows.saturating_sub(1));
+        for (i, entry) in leaderboard.entries.iter().enumerate().skip(offset).take(max_rows) {
+            let owner = leaderboard.owners.get(i).map(String::as_str).unwrap_or("");
+            let note = entry.flag.as_ref().map(|f| format!("\u{26A0} {}", f)).unwrap_or_default();
+            let row = format!(
+                "{:>4}  {:<10}  {:<6}  {:>6.1}  {:>6.1}  {:<12}  {}",
+                i + 1,
+                entry.timestamp.get(..10).unwrap_or(&entry.timestamp),
+                test_type_label(&entry.test_type),
+                entry.wpm,
+                entry.accuracy,
+                owner,
+                note
+            );
+            let color = if i == leaderboard.selected {
+                color_scheme.main_color()
+            } else if entry.flag.is_some() {
+                color_scheme.incorrect_color()
+            } else {
+                color_scheme.text_color()
+            };
+            text(&row, x, y, small, color);
+            y += line_h;
+        }
+    }
+
+    text(
+        &format!("↑ or ↓ to navigate, ← → to switch tab, {} or Esc to close", keymap.hint(Action::Leaderboard)),
+        x,
+        screen_height() - screen_height() / 7.5,
+        small,
+        color_scheme.ref_color(),
+    );
+}
@@ -3,14 +3,27 @@ use macroquad::prelude::*;
 use miniquad::CursorIcon;
 use miniquad::window::set_mouse_cursor;
 use std::collections::VecDeque;
-use std::thread;
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
 
 use crate::color_scheme::ColorScheme;
-use crate::config::AppConfig;
+use crate::config::{AppConfig, Mode};
+use crate::leaderboard::{LeaderboardData, LeaderboardEntry, TestType};
+use crate::pace;
+use crate::presets;
+use crate::themes;
+use crate::ghost::{self, GhostRun, Keystroke};
+use crate::daily::{self, DailyChallenge};
+use crate::fonts::{self, FontChoice};
+use crate::accessibility;
+use crate::achievements::{self, AchievementsData};
+use crate::anticheat;
+use crate::goals;
+use crate::keymap::{self, Action, Dispatch, KeyReader, Keymap};
 use crate::practice::{self, TYPING_LEVELS};
+use crate::ui::gui::achievements as gui_achievements;
 use crate::ui::gui::config::{self, reset_game_state};
+use crate::ui::gui::leaderboard as gui_leaderboard;
 use crate::ui::gui::popup::{PopupStates, PopupState};
 use crate::ui::gui::practice as gui_practice;
 use crate::ui::gui::results;
@@ -20,7 +33,7 @@ use crate::utils;
 pub const MAIN_COLOR: macroquad::color::Color =
     macroquad::color::Color::from_rgba(255, 155, 0, 255);
 
-const ROBOTO_MONO: &[u8] = include_bytes!(concat!(
+pub(crate) const ROBOTO_MONO: &[u8] = include_bytes!(concat!(
     env!("CARGO_MANIFEST_DIR"),
     "/assets/fonts/RobotoMono-VariableFont_wght.ttf"
 ));
@@ -30,23 +43,38 @@ const DEJAVU: &[u8] = include_bytes!(concat!(
     "/assets/fonts/DejaVuSansCondensed.ttf"
 ));
 
-pub async fn gui_main_async() {
+/// The chosen font, or Roboto Mono when its file can't be loaded
+fn load_font(choice: &FontChoice) -> Font {
+    let loaded = match choice {
+        FontChoice::RobotoMono => load_ttf_font_from_bytes(ROBOTO_MONO).ok(),
+        FontChoice::DejaVuSans => load_ttf_font_from_bytes(DEJAVU).ok(),
+        FontChoice::File(path) => match std::fs::read(path) {
+            Ok(bytes) => load_ttf_font_from_bytes(&bytes)
+                .map_err(|e| eprintln!("Failed to load the font {}: {}", path.display(), e))
+                .ok(),
+            Err(e) => {
+                eprintln!("Failed to read the font {}: {}", path.display(), e);
+                None
+            }
+        },
+    };
+    loaded.unwrap_or_else(|| load_ttf_font_from_bytes(ROBOTO_MONO).unwrap())
+}
+
+/// Runs the GUI; with a `ghost` the first test is a race against that earlier run,
+/// with `daily` it's today's daily challenge
+pub async fn gui_main_async(mut ghost: Option<GhostRun>, daily: bool) {
     let mut app_config = AppConfig::load();
 
     let mut punctuation = app_config.punctuation;
     let mut numbers = app_config.numbers;
-    let mut quote = app_config.quote;
-    let mut time_mode = app_config.time_mode;
-    let mut word_mode = app_config.word_mode;
+    let mut mode = app_config.mode;
     let mut language = app_config.language;
-    let mut practice_mode = app_config.practice_mode;
-    let mut wiki_mode = app_config.wiki_mode;
-
-    if !time_mode && !word_mode && !quote && !practice_mode && !wiki_mode {
-        time_mode = true;
-    }
 
-    let font = load_ttf_font_from_bytes(ROBOTO_MONO).unwrap();
+    let mut font_choice = app_config.font.clone();
+    let mut loaded_font = font_choice.clone();
+    let mut font = load_font(&font_choice);
+    let mut line_spacing = fonts::line_spacing(&app_config);
     let title_font = load_ttf_font_from_bytes(ROBOTO_MONO).unwrap();
     let emoji_font = load_ttf_font_from_bytes(DEJAVU).unwrap();
 
@@ -58,14 +86,28 @@ pub async fn gui_main_async() {
     let updated_word_list = utils::read_first_n_words(500, language);
     let mut selected_practice_level: Option<usize> = Some(app_config.selected_level);
 
-    let mut reference = if practice_mode {
+    let mut daily = daily.then(|| DailyChallenge::today(language));
+    // Streak and earlier daily results, shown with the challenge
+    let mut daily_lines = daily.as_ref().map(|d| daily::summary(d.date)).unwrap_or_default();
+
+    // Progress on the goals from the config, updated after every test
+    let mut goal_summary = goals::today_summary(&app_config.goals);
+    // Achievements unlocked by the last result, checked once it is saved
+    let mut achievement_line: Option<String> = None;
+    let mut achievements_pending = false;
+
+    let mut reference = if let Some(ghost) = &ghost {
+        ghost.reference.clone()
+    } else if let Some(daily) = &daily {
+        daily.text.clone()
+    } else if mode == Mode::Practice {
         practice::create_words(
             TYPING_LEVELS[selected_practice_level.unwrap_or(0)].1,
             50,
         )
-    } else if quote {
+    } else if mode == Mode::Quote {
         utils::get_random_quote()
-    } else if wiki_mode {
+    } else if mode == Mode::Wiki {
         utils::get_wiki_summary()
     } else {
         utils::get_reference(punctuation, false, &updated_word_list, batch_size)
@@ -90,6 +132,11 @@ pub async fn gui_main_async() {
     let mut last_recorded_time = Instant::now();
 
     let mut words_done = 0;
+    // Target WPM of the pace caret and the words of the batches already typed through
+    let mut pace_wpm: Option<f64> = None;
+    let mut earlier_batch_words = 0;
+    // Cursor position whenever it moved, for ghost races
+    let mut key_log: Vec<Keystroke> = Vec::new();
 
     let mut config_opened = false;
     let mut selected_config: String = "time".to_string();
@@ -99,6 +146,7 @@ pub async fn gui_main_async() {
     let mut saved_results = false;
 
     let mut color_scheme = app_config.color_scheme;
+    let mut text_scale = accessibility::text_scale(&app_config);
 
     let mut menu_buttons_times: HashMap<String, Instant> = HashMap::from([
         ("settings".to_string(), Instant::now() - Duration::from_secs(5)),
@@ -119,7 +167,16 @@ pub async fn gui_main_async() {
         settings: PopupState { visible: false, selected: 0 },
         batch_size_selection: PopupState { visible: false, selected: 0 },
         top_words_selection: PopupState { visible: false, selected: 0 },
+        profile: PopupState { visible: false, selected: 0 },
+        pace: PopupState { visible: false, selected: 0 },
+        preset: PopupState { visible: false, selected: 0 },
+        font: PopupState { visible: false, selected: 0 },
+        text_size: PopupState { visible: false, selected: 0 },
+        line_spacing: PopupState { visible: false, selected: 0 },
     };
+    let mut leaderboard = LeaderboardData::new();
+    let mut achievements_screen = AchievementsData::new();
+    let mut keys = KeyReader::default();
 
     let words: Vec<&str> = reference.split_whitespace().collect();
     let average_word_length: f64 = if !words.is_empty() {
@@ -129,6 +186,12 @@ pub async fn gui_main_async() {
     };
 
     loop {
+        // Edited theme files show up without a restart
+        themes::reload_if_changed();
+        if font_choice != loaded_font {
+            font = load_font(&font_choice);
+            loaded_font = font_choice.clone();
+        }
         clear_background(color_scheme.bg_color());
         let mut max_width = f32::min(
             if screen_height() > screen_width() {
@@ -147,8 +210,105 @@ pub async fn gui_main_async() {
             (40.0 - (3840.0 / screen_width()) * 5.0).round()
         } else {
             20.0
-        };
-        let line_h = measure_text("Gy", Some(&font.clone()), font_size as u16, 1.0).height * 1.6;
+        } * text_scale;
+        let actions: Vec<Action> = keymap::gui_key_presses()
+            .into_iter()
+            .filter_map(|press| match keys.read(&app_config.keymap, press) {
+                Dispatch::Action(action) => Some(action),
+                _ => None,
+            })
+            .collect();
+        if !actions.is_empty() {
+            // The key of a shortcut isn't part of the test
+            while get_char_pressed().is_some() {}
+        }
+        let idle = !game_started || game_over;
+        let scale_steps = actions.iter().fold(0, |steps, action| match action {
+            Action::TextLarger => steps + 1,
+            Action::TextSmaller => steps - 1,
+            _ => steps,
+        });
+        if scale_steps != 0 {
+            text_scale = accessibility::step_text_scale(text_scale, scale_steps);
+            app_config.text_scale = text_scale;
+            let _ = AppConfig { text_scale, ..AppConfig::load() }.save();
+        }
+        if actions.contains(&Action::Leaderboard) && idle {
+            leaderboard.open = !leaderboard.open;
+            if leaderboard.open {
+                leaderboard.selected = 0;
+                leaderboard.reload();
+            }
+        }
+        if actions.contains(&Action::Achievements) && idle && !leaderboard.open {
+            achievements_screen.toggle();
+        }
+        if actions.contains(&Action::Daily) && idle && !leaderboard.open {
+            config::reset_game_state(
+                &mut pressed_vec,
+                &mut is_correct,
+                &mut pos1,
+                &mut timer,
+                &mut start_time,
+                &mut game_started,
+                &mut game_over,
+                &mut speed_per_second,
+                &mut last_recorded_time,
+                &mut words_done,
+                &mut errors_per_second,
+                &mut saved_results,
+                &mut error_positions,
+            );
+            let challenge = DailyChallenge::today(language);
+            daily_lines = daily::summary(challenge.date);
+            reference = challenge.text.clone();
+            daily = Some(challenge);
+            ghost = None;
+            is_correct = VecDeque::from(vec![0; reference.len()]);
+            error_positions = vec![false; is_correct.len()];
+        }
+        if actions.contains(&Action::NextPreset) && !game_started && !leaderboard.open && !achievements_screen.open {
+            match presets::cycle() {
+                Ok(config) => {
+                    config::load_settings(
+                        &config,
+                        &mut punctuation,
+                        &mut numbers,
+                        &mut mode,
+                        &mut batch_size,
+                        &mut test_time,
+                        &mut language,
+                        &mut color_scheme,
+                        &mut word_number,
+                        &mut top_words,
+                        &mut selected_practice_level,
+                        &mut reference,
+                        &mut is_correct,
+                        &mut error_positions,
+                        &mut pressed_vec,
+                        &mut pos1,
+                        &mut words_done,
+                    );
+                    ghost = None;
+                    daily = None;
+                }
+                Err(e) => eprintln!("Failed to apply preset: {}", e),
+            }
+        }
+        if achievements_screen.open {
+            gui_achievements::handle_achievements_keys(&mut achievements_screen);
+            gui_achievements::draw_achievements(Some(&font), font_size, &achievements_screen, &color_scheme, &app_config.keymap);
+            next_frame().await;
+            continue;
+        }
+        if leaderboard.open {
+            gui_leaderboard::handle_leaderboard_keys(&mut leaderboard);
+            gui_leaderboard::draw_leaderboard(Some(&font), font_size, &leaderboard, &color_scheme, &app_config.keymap);
+            next_frame().await;
+            continue;
+        }
+
+        let line_h = measure_text("Gy", Some(&font.clone()), font_size as u16, 1.0).height * line_spacing;
         let char_w = measure_text("G", Some(&font.clone()), font_size as u16, 1.0)
             .width
             .floor();
@@ -157,10 +317,17 @@ pub async fn gui_main_async() {
             Some(font.clone()),
             font_size,
             max_width,
-            quote,
-            word_mode,
-            wiki_mode,
+            mode == Mode::Quote || ghost.is_some() || daily.is_some(),
+            mode == Mode::Words,
+            mode == Mode::Wiki,
         );
+        // Choosing another mode or text ends the ghost race or daily challenge
+        if ghost.as_ref().is_some_and(|g| g.reference != reference) {
+            ghost = None;
+        }
+        if daily.as_ref().is_some_and(|d| d.text != reference) {
+            daily = None;
+        }
 
         let mut chars_in_line: Vec<i32> = vec![];
         for line in &lines {
@@ -175,7 +342,7 @@ pub async fn gui_main_async() {
         }
 
         if !game_over && !practice_menu {
-            let total_height = lines.len() as f32 * font_size * 1.2;
+            let total_height = lines.len() as f32 * font_size * 0.75 * line_spacing;
             let start_y = screen_height() / 2.0 - total_height / 2.0 + font_size;
             let start_x = screen_width() / 2.0 - max_width / 2.0 + 20.0;
             let title_y = screen_height() / 7.5;
@@ -188,6 +355,7 @@ pub async fn gui_main_async() {
                 font_size,
                 start_x,
                 start_y,
+                line_spacing,
                 popup_states.language.visible,
                 &color_scheme,
             );
@@ -198,9 +366,7 @@ pub async fn gui_main_async() {
                 &word_list,
                 &mut punctuation,
                 &mut numbers,
-                &mut quote,
-                &mut time_mode,
-                &mut word_mode,
+                &mut mode,
                 &mut pressed_vec,
                 &mut is_correct,
                 &mut pos1,
@@ -221,16 +387,17 @@ pub async fn gui_main_async() {
                 &mut selected_config,
                 &mut practice_menu,
                 &mut selected_practice_level,
-                &mut practice_mode,
                 &mut saved_results,
                 &mut error_positions,
                 &mut language,
                 &mut color_scheme,
-                &mut wiki_mode,
                 &mut menu_buttons_times,
                 &mut popup_states,
                 &mut top_words,
                 &mut word_number,
+                &mut font_choice,
+                &mut text_scale,
+                &mut line_spacing,
            );
 
             set_mouse_cursor(if any_button_hovered {
@@ -249,13 +416,10 @@ pub async fn gui_main_async() {
                 &mut game_started,
                 &mut game_over,
                 test_time,
-                time_mode,
+                mode,
                 &mut words_done,
                 &mut errors_this_second,
-                &mut practice_mode,
                 practice_menu,
-                wiki_mode,
-                quote,
                 word_number,
             );
 
@@ -269,19 +433,57 @@ pub async fn gui_main_async() {
                     &mut errors_this_second,
                     &mut config_opened,
                     &mut error_positions,
-                    practice_mode,
+                    mode == Mode::Practice,
                     practice_menu,
                     game_over,
                 )
             {
                 game_started = true;
+                let test_type = match (&daily, mode) {
+                    (Some(daily), _) => TestType::Daily(daily.date),
+                    (None, Mode::Practice) => TestType::Practice(selected_practice_level.unwrap_or(0) + 1),
+                    (None, Mode::Time) => TestType::Time(test_time as u32),
+                    (None, Mode::Words) => TestType::Word(word_number),
+                    (None, Mode::Quote) => TestType::Quote,
+                    (None, Mode::Wiki) => TestType::Wiki,
+                };
+                pace_wpm = AppConfig::load().pace.target_wpm(&test_type, language);
+                earlier_batch_words = 0;
+                key_log.clear();
             }
 
             if (game_started || words_done == word_number) && !game_over {
                 timer = start_time.elapsed();
-                if (timer.as_secs_f32() >= test_time && time_mode) || (pos1 >= reference.chars().count() && (wiki_mode || quote)) || (words_done >= word_number && !wiki_mode && !quote)
-                {
+                let test_over = if ghost.is_some() || daily.is_some() {
+                    // Ghost races and dailies last until the end of the text, whatever the mode
+                    pos1 >= reference.chars().count()
+                } else {
+                    (timer.as_secs_f32() >= test_time && mode == Mode::Time) || (pos1 >= reference.chars().count() && matches!(mode, Mode::Wiki | Mode::Quote)) || (words_done >= word_number && !matches!(mode, Mode::Wiki | Mode::Quote))
+                };
+                if test_over {
                     game_over = true;
+                    // Keep the run for ghost races; runs spread over several batches can't be replayed
+                    if earlier_batch_words == 0 {
+                        let (_, correct_words, _) = utils::count_correct_words(&reference, &is_correct);
+                        let wpm = correct_words as f64 / timer.as_secs_f64() * 60.0;
+                        if let Some(run) = GhostRun::new(ghost::player_name(), wpm, language, &reference, &key_log) {
+                            ghost::record(run);
+                        }
+                    }
+                    // Only the first daily attempt of the day is scored
+                    if let Some(daily) = &daily {
+                        let scored = daily::submit(&daily_entry(daily, &reference, &is_correct, &key_log, words_done, timer, language))
+                            .unwrap_or_else(|e| {
+                                eprintln!("Failed to save the daily result: {}", e);
+                                false
+                            });
+                        daily_lines = daily::summary(daily.date);
+                        if !scored {
+                            daily_lines[0] = format!("Practice run, not scored · {}", daily_lines[0]);
+                        }
+                    }
+                    goal_summary = goals::today_summary(&app_config.goals);
+                    achievements_pending = true;
                 }
             }
 
@@ -307,13 +509,31 @@ pub async fn gui_main_async() {
                     &mut errors_this_second,
                     &mut config_opened,
                     &mut error_positions,
-                    practice_mode,
+                    mode == Mode::Practice,
                     practice_menu,
                     game_over,
                 );
             }
+            if game_started && key_log.last().map_or(pos1 > 0, |k| k.pos != pos1) {
+                key_log.push(Keystroke { ms: start_time.elapsed().as_secs_f64() * 1000.0, pos: pos1 });
+            }
 
-            if time_mode {
+            if ghost.is_some() || daily.is_some() {
+                draw_word_count(
+                    Some(&font.clone()),
+                    font_size,
+                    start_x,
+                    start_y,
+                    &mut words_done,
+                    reference.split_whitespace().count(),
+                    &color_scheme,
+                );
+                if let (Some(status), true) = (daily_lines.first(), daily.is_some()) {
+                    let x = start_x + measure_text("000/000   ", Some(&font), font_size as u16, 1.0).width;
+                    let params = TextParams { font: Some(&font), font_size: (font_size * 0.7) as u16, color: color_scheme.ref_color(), ..Default::default() };
+                    draw_text_ex(status, x, start_y - screen_height() / 20.0, params);
+                }
+            } else if mode == Mode::Time {
                 draw_timer(
                     Some(&font.clone()),
                     font_size,
@@ -323,7 +543,7 @@ pub async fn gui_main_async() {
                     test_time,
                     &color_scheme,
                 );
-            } else if word_mode {
+            } else if mode == Mode::Words {
                 draw_word_count(
                     Some(&font.clone()),
                     font_size,
@@ -333,7 +553,7 @@ pub async fn gui_main_async() {
                     word_number,
                     &color_scheme,
                 );
-            } else if practice_mode {
+            } else if mode == Mode::Practice {
                 draw_word_count(
                     Some(&font.clone()),
                     font_size,
@@ -343,7 +563,7 @@ pub async fn gui_main_async() {
                     50,
                     &color_scheme,
                 );
-            } else if quote || wiki_mode{
+            } else if matches!(mode, Mode::Quote | Mode::Wiki) {
                 draw_word_count(
                     Some(&font.clone()),
                     font_size,
@@ -367,10 +587,24 @@ pub async fn gui_main_async() {
                         start_y,
                         line_h,
                         char_w,
-                        &color_scheme,
+                        color_scheme.main_color(),
                     );
                 }
             } else {
+                if let Some(ghost) = &ghost {
+                    let index = ghost.position_at(timer);
+                    if index < reference.chars().count() {
+                        let (ghost_x, ghost_y) = calc_pos(&chars_in_line, index);
+                        draw_cursor(ghost_x, ghost_y, start_x, start_y, line_h, char_w, color_scheme.border_color());
+                    }
+                }
+                if let Some(target) = pace_wpm {
+                    let words = pace::words_at(target, timer) - earlier_batch_words as f64;
+                    if let Some(index) = pace::caret_index(&reference, words) {
+                        let (pace_x, pace_y) = calc_pos(&chars_in_line, index);
+                        draw_cursor(pace_x, pace_y, start_x, start_y, line_h, char_w, color_scheme.dimmer_main());
+                    }
+                }
                 draw_cursor(
                     calc_pos_x,
                     calc_pos_y,
@@ -378,7 +612,7 @@ pub async fn gui_main_async() {
                     start_y,
                     line_h,
                     char_w,
-                    &color_scheme,
+                    color_scheme.main_color(),
                 );
             }
 
@@ -408,23 +642,25 @@ pub async fn gui_main_async() {
                 &mut errors_this_second,
                 &mut config_opened,
                 &mut error_positions,
-                practice_mode,
+                mode == Mode::Practice,
                 practice_menu,
                 game_over,
             );
-            let mode = if time_mode {
-                "time".to_string()
-            } else if word_mode {
-                "word".to_string()
-            } else if quote {
-                "quote".to_string()
-            } else if wiki_mode {
-                "wiki".to_string()
+            let mode_name = if ghost.is_some() {
+                "ghost"
+            } else if daily.is_some() {
+                "daily"
             } else {
-                "practice".to_string()
+                match mode {
+                    Mode::Time => "time",
+                    Mode::Words => "word",
+                    Mode::Quote => "quote",
+                    Mode::Wiki => "wiki",
+                    Mode::Practice => "practice",
+                }
             };
 
-            let practice_level = if !practice_mode {
+            let practice_level = if mode != Mode::Practice || ghost.is_some() {
                 None
             } else {
                 selected_practice_level
@@ -438,15 +674,34 @@ pub async fn gui_main_async() {
                 timer.as_secs_f32(),
                 &speed_per_second,
                 average_word_length,
-                &mode,
+                mode_name,
                 punctuation,
                 numbers,
                 &errors_per_second,
                 &reference,
                 practice_level,
+                pace_wpm,
+                ghost.as_ref(),
+                &key_log,
+                if daily.is_some() { &daily_lines } else { &[] },
                 &mut saved_results,
                 &color_scheme,
             );
+            // After `write_results`, which saves practice results
+            if achievements_pending {
+                achievements_pending = false;
+                achievement_line = achievements::unlocked_line(&achievements::check_saved());
+            }
+            if let Some(unlocked) = &achievement_line {
+                let unlocked_size = f32::max(font_size / 1.5, 12.0) as u16;
+                let unlocked_w = measure_text(unlocked, Some(&font), unlocked_size, 1.0).width;
+                draw_text_ex(
+                    unlocked,
+                    (screen_width() - unlocked_w) / 2.0,
+                    screen_height() - screen_height() / 7.5 - font_size * 3.0,
+                    TextParams { font: Some(&font), font_size: unlocked_size, color: color_scheme.main_color(), ..Default::default() },
+                );
+            }
         } else if practice_menu {
             let level = gui_practice::display_practice_menu(
                 Some(font.clone()),
@@ -454,7 +709,7 @@ pub async fn gui_main_async() {
                 emoji_font.clone(),
                 &mut selected_practice_level,
                 &mut practice_menu,
-                &mut time_mode,
+                &mut mode,
                 &mut pressed_vec,
                 &mut is_correct,
                 &mut pos1,
@@ -489,19 +744,18 @@ pub async fn gui_main_async() {
                 reference = practice::create_words(TYPING_LEVELS[level.unwrap()].1, 50);
                 is_correct = VecDeque::from(vec![0; reference.len()]);
                 error_positions = vec![false; is_correct.len()];
-                practice_mode = true;
-                wiki_mode = false;
-                time_mode = false;
-                word_mode = false;
-                quote = false;
+                mode = Mode::Practice;
                 practice_menu = false;
                 config_opened = false;
             }
         }
-        if is_key_pressed(KeyCode::Escape) {
+        let quit = actions.contains(&Action::Quit);
+        if is_key_pressed(KeyCode::Escape) || quit {
             if practice_menu {
                 practice_menu = false;
-                practice_mode = false;
+                if mode == Mode::Practice {
+                    mode = Mode::default();
+                }
                 game_over = false;
 
                 reset_game_state(
@@ -552,18 +806,22 @@ pub async fn gui_main_async() {
             } else if popup_states.top_words_selection.visible {
                 popup_states.top_words_selection.visible = false;
                 config_opened = false;
+            } else if popup_states.font.visible {
+                popup_states.font.visible = false;
+            } else if popup_states.text_size.visible {
+                popup_states.text_size.visible = false;
+            } else if popup_states.line_spacing.visible {
+                popup_states.line_spacing.visible = false;
+            } else if popup_states.profile.visible {
+                popup_states.profile.visible = false;
             } else if popup_states.settings.visible {
                 popup_states.settings.visible = false;
                 config_opened = false;
-            } else {
+            } else if quit {
                 app_config = AppConfig {
                     punctuation: punctuation,
                     numbers: numbers,
-                    time_mode: time_mode,
-                    word_mode: word_mode,
-                    quote: quote,
-                    practice_mode: practice_mode,
-                    wiki_mode: wiki_mode,
+                    mode,
                     batch_size: batch_size,
                     test_time: test_time,
                     selected_level: selected_practice_level.unwrap_or(0),
@@ -571,6 +829,7 @@ pub async fn gui_main_async() {
                     color_scheme: color_scheme,
                     word_number: word_number,
                     top_words: top_words,
+                    ..AppConfig::load()
                 };
                 let _ = app_config.save();
 
@@ -578,7 +837,29 @@ pub async fn gui_main_async() {
             }
         }
 
-        if is_key_down(KeyCode::Tab) && is_key_down(KeyCode::Enter) && !practice_menu {
+        let mode_action = actions.iter().find_map(|action| match action {
+            Action::ModeTime => Some(Mode::Time),
+            Action::ModeWords => Some(Mode::Words),
+            Action::ModeQuote => Some(Mode::Quote),
+            Action::ModeWiki => Some(Mode::Wiki),
+            _ => None,
+        });
+        if let (Some(new_mode), true) = (mode_action, idle && !practice_menu) {
+            mode = new_mode;
+        }
+        if (actions.contains(&Action::NewText) || mode_action.is_some()) && !practice_menu {
+            ghost = None;
+            daily = None;
+        }
+        if actions.contains(&Action::Settings) && idle && !practice_menu {
+            popup_states.settings.visible = true;
+            config_opened = true;
+        }
+        if actions.contains(&Action::Practice) && idle && !practice_menu {
+            practice_menu = true;
+            selected_practice_level = Some(practice::get_first_not_done());
+        }
+        if actions.contains(&Action::RepeatText) && !practice_menu {
             config::reset_game_state(
                 &mut pressed_vec,
                 &mut is_correct,
@@ -594,14 +875,39 @@ pub async fn gui_main_async() {
                 &mut saved_results,
                 &mut error_positions,
             );
-            if practice_mode {
+            is_correct = VecDeque::from(vec![0; reference.len()]);
+            error_positions = vec![false; is_correct.len()];
+        }
+
+        let restart = [Action::Restart, Action::NewText].iter().any(|action| actions.contains(action));
+        if (restart || mode_action.is_some()) && !practice_menu {
+            config::reset_game_state(
+                &mut pressed_vec,
+                &mut is_correct,
+                &mut pos1,
+                &mut timer,
+                &mut start_time,
+                &mut game_started,
+                &mut game_over,
+                &mut speed_per_second,
+                &mut last_recorded_time,
+                &mut words_done,
+                &mut errors_per_second,
+                &mut saved_results,
+                &mut error_positions,
+            );
+            if let Some(ghost) = &ghost {
+                reference = ghost.reference.clone();
+            } else if let Some(daily) = &daily {
+                reference = daily.text.clone();
+            } else if mode == Mode::Practice {
                 reference = practice::create_words(
                     TYPING_LEVELS[selected_practice_level.unwrap_or(0)].1,
                     50,
                 );
-            } else if quote {
+            } else if mode == Mode::Quote {
                 reference = utils::get_random_quote();
-            } else if wiki_mode {
+            } else if mode == Mode::Wiki {
                 reference = utils::get_wiki_summary();
             } else {
                 let updated_word_list = utils::read_first_n_words(500, language);
@@ -610,11 +916,11 @@ pub async fn gui_main_async() {
             }
             is_correct = VecDeque::from(vec![0; reference.len()]);
             error_positions = vec![false; is_correct.len()];
-            thread::sleep(time::Duration::from_millis(80));
         }
 
-        if pos1 >= reference.chars().count() && (time_mode || word_mode) && !game_over {
+        if pos1 >= reference.chars().count() && matches!(mode, Mode::Time | Mode::Words) && !game_over && ghost.is_none() && daily.is_none() {
             words_done += 1;
+            earlier_batch_words += reference.split_whitespace().count();
             reference = utils::get_reference(
                 punctuation,
                 numbers,
@@ -626,6 +932,17 @@ pub async fn gui_main_async() {
             pos1 = 0;
         }
 
+        if let (Some(goals), false) = (&goal_summary, practice_menu || (game_started && !game_over)) {
+            let goals_size = f32::max(font_size / 1.7, 11.0) as u16;
+            let goals_w = measure_text(goals, Some(&font), goals_size, 1.0).width;
+            draw_text_ex(
+                goals,
+                (screen_width() - goals_w) / 2.0,
+                screen_height() - screen_height() / 7.5 - font_size * 1.5,
+                TextParams { font: Some(&font), font_size: goals_size, color: color_scheme.ref_color(), ..Default::default() },
+            );
+        }
+
         draw_shortcut_info(
             Some(&font.clone()),
             f32::max(font_size / 1.7, 11.0),
@@ -634,13 +951,41 @@ pub async fn gui_main_async() {
             emoji_font.clone(),
             practice_menu,
             game_over,
-            practice_mode,
+            mode == Mode::Practice,
             &color_scheme,
+            &app_config.keymap,
         );
         next_frame().await;
     }
 }
 
+/// Leaderboard entry of a finished daily challenge
+fn daily_entry(
+    daily: &DailyChallenge,
+    reference: &str,
+    is_correct: &VecDeque<i32>,
+    key_log: &[Keystroke],
+    words_done: usize,
+    timer: Duration,
+    language: crate::language::Language,
+) -> LeaderboardEntry {
+    let (_, correct_words, _) = utils::count_correct_words(reference, is_correct);
+    let correct_count = is_correct.iter().filter(|&&v| v == 1 || v == 2).count();
+    let all_pressed_count = is_correct.iter().filter(|&&v| v != 0).count();
+    let key_times: Vec<f64> = key_log.iter().map(|k| k.ms).collect();
+    LeaderboardEntry {
+        wpm: correct_words as f64 / timer.as_secs_f64() * 60.0,
+        accuracy: if all_pressed_count > 0 { correct_count as f64 / all_pressed_count as f64 * 100.0 } else { 0.0 },
+        test_type: TestType::Daily(daily.date),
+        test_mode: "daily".to_string(),
+        word_count: words_done,
+        test_duration: timer.as_secs_f64(),
+        timestamp: chrono::Local::now().to_rfc3339(),
+        language,
+        flag: anticheat::analyze(&key_times),
+    }
+}
+
 fn write_title(font: Option<Font>, font_size: f32, x: f32, y: f32, color_scheme: ColorScheme) {
     let (type_text, man_text) = ("Type", "Man");
     let type_width = measure_text(type_text, font.as_ref(), font_size as u16, 1.0).width;
@@ -684,8 +1029,16 @@ fn draw_shortcut_info(
     game_over: bool,
     practice_mode: bool,
     color_scheme: &ColorScheme,
+    keymap: &Keymap,
 ) {
     let mut x = if practice_menu { 200.0 } else { x };
+    let shortcuts = format!(
+        "{} - reset, {} - leaderboard, {} - daily challenge, {} - achievements",
+        keymap.hint(Action::Restart),
+        keymap.hint(Action::Leaderboard),
+        keymap.hint(Action::Daily),
+        keymap.hint(Action::Achievements),
+    );
     let mut next_y = y;
     let lines = if practice_menu {
         let text_w = measure_text(
@@ -698,23 +1051,24 @@ fn draw_shortcut_info(
         x = screen_width() - text_w - 70.0;
 
         vec![
-            "↑ or ↓ to navigate, ↵ to select (or click)",
-            "+ - double Enter to view more options"
+            "↑ or ↓ to navigate, ↵ to select (or click)".to_string(),
+            "+ - double Enter to view more options".to_string(),
         ]
     } else if practice_mode {
         vec![
-            "↑ or ↓ to navigate to config, ← → to change settings, ↵ - apply config (or click)",
-            "+ - double Enter to view more options",
-            "Tab + Enter - reset",
+            "↑ or ↓ to navigate to config, ← → to change settings, ↵ - apply config (or click)".to_string(),
+            "+ - double Enter to view more options".to_string(),
+            format!("{} - reset", keymap.hint(Action::Restart)),
         ]
     } else if game_over {
         x /= 2.0;
-        vec!["Tab + Enter - reset"]
+        vec![format!("{}, {} - same text", shortcuts, keymap.hint(Action::RepeatText))]
     } else {
         vec![
-            "↑ or ↓ to navigate to config, ← → to change settings, ↵ - apply config (or click)",
-            "+ - double Enter to view more options",
-            "Tab + Enter - reset",
+            "↑ or ↓ to navigate to config, ← → to change settings, ↵ - apply config (or click)".to_string(),
+            "+ - double Enter to view more options".to_string(),
+            shortcuts,
+            format!("{} - next preset", keymap.hint(Action::NextPreset)),
         ]
     };
 
@@ -985,6 +1339,7 @@ fn draw_reference_text(
     font_size: f32,
     start_x: f32,
     start_y: f32,
+    line_spacing: f32,
     _lang_popup_open: bool,
     color_scheme: &ColorScheme,
 ) {
@@ -1023,11 +1378,18 @@ fn draw_reference_text(
                 },
             );
             let type_width = measure_text(&char.to_string(), font, font_size as u16, 1.0).width;
+            // Errors are struck through and corrections underlined, for when colors don't tell them apart
+            let (left, right, y) = (pos_x as f32 + start_x, pos_x as f32 + start_x + type_width, pos_y + start_y);
+            match is_correct.get(pos) {
+                Some(-1) if pos < pressed_vec.len() => draw_line(left, y - font_size * 0.3, right, y - font_size * 0.3, 2.0, color),
+                Some(1) if pos < pressed_vec.len() => draw_line(left, y + font_size * 0.15, right, y + font_size * 0.15, 2.0, color),
+                _ => {}
+            }
             pos_x += type_width as usize;
             pos += 1;
         }
         let type_height = measure_text("Gy", font, font_size as u16, 1.0).height;
-        pos_y += type_height * 1.6;
+        pos_y += type_height * line_spacing;
     }
 }
 
@@ -1038,7 +1400,7 @@ fn draw_cursor(
     start_y: f32,
     line_h: f32,
     char_w: f32,
-    color_scheme: &ColorScheme,
+    color: Color,
 ) {
     let cursor_x = start_x + cursor_x as f32 * char_w;
     let cursor_y = start_y + cursor_y as f32 * line_h;
@@ -1048,7 +1410,7 @@ fn draw_cursor(
         cursor_x,
         cursor_y + line_h * 0.3,
         2.0,
-        color_scheme.main_color(),
+        color,
     );
 }
 
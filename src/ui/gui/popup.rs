@@ -1,7 +1,11 @@
 use macroquad::prelude::*;
 
 use crate::color_scheme::ColorScheme;
+use crate::config::AppConfig;
+use crate::fonts::{self, FontChoice};
 use crate::language::Language;
+use crate::pace::Pace;
+use crate::profile;
 use crate::time_selection::TimeSelection;
 use crate::settings::Settings;
 use crate::utils;
@@ -14,6 +18,12 @@ pub enum PopupContent {
     Settings,
     BatchSizeSelection,
     TopWordsSelection,
+    Profile,
+    Pace,
+    Preset,
+    Font,
+    TextSize,
+    LineSpacing,
 }
 
 pub struct PopupState {
@@ -29,6 +39,12 @@ pub struct PopupStates {
     pub settings: PopupState,
     pub batch_size_selection: PopupState,
     pub top_words_selection: PopupState,
+    pub profile: PopupState,
+    pub pace: PopupState,
+    pub preset: PopupState,
+    pub font: PopupState,
+    pub text_size: PopupState,
+    pub line_spacing: PopupState,
 }
 
 pub trait PopupData {
@@ -47,6 +63,12 @@ impl PopupData for PopupContent {
             PopupContent::Settings => "Select Setting",
             PopupContent::BatchSizeSelection => "Select Batch Size",
             PopupContent::TopWordsSelection => "Select Top Words",
+            PopupContent::Profile => "Select Profile",
+            PopupContent::Pace => "Select Pace Caret",
+            PopupContent::Preset => "Select Preset",
+            PopupContent::Font => "Select Font",
+            PopupContent::TextSize => "Select Text Size",
+            PopupContent::LineSpacing => "Select Line Spacing",
         }
     }
 
@@ -56,9 +78,15 @@ impl PopupData for PopupContent {
             PopupContent::ColorScheme => ColorScheme::all().iter().map(|x| x.name().to_string()).collect(),
             PopupContent::TimeSelection => TimeSelection::all().iter().map(|x| x.to_string()).collect(),
             PopupContent::WordNumberSelection => vec!["25".to_string(), "50".to_string(), "100".to_string(), "200".to_string(), "500".to_string()],
-            PopupContent::Settings => Settings::all().iter().map(|x| x.to_string()).collect(),
+            PopupContent::Settings => Settings::gui().iter().map(|x| x.to_string()).collect(),
             PopupContent::BatchSizeSelection => vec!["10".to_string(), "25".to_string(), "50".to_string(), "100".to_string(), "200".to_string()],
             PopupContent::TopWordsSelection => vec!["100".to_string(), "200".to_string(), "500".to_string(), "1000".to_string()],
+            PopupContent::Profile => profile::list(),
+            PopupContent::Pace => Pace::all().iter().map(|x| x.to_string()).collect(),
+            PopupContent::Preset => AppConfig::load().presets.iter().map(|x| x.to_string()).collect(),
+            PopupContent::Font => FontChoice::all().iter().map(|x| x.to_string()).collect(),
+            PopupContent::TextSize => fonts::TEXT_SCALES.iter().map(|x| format!("{:.0}%", x * 100.0)).collect(),
+            PopupContent::LineSpacing => fonts::LINE_SPACINGS.iter().map(|x| format!("{:.1}", x)).collect(),
         }
     }
 
@@ -71,6 +99,12 @@ impl PopupData for PopupContent {
             PopupContent::Settings => &popup_states.settings.selected,
             PopupContent::BatchSizeSelection => &popup_states.batch_size_selection.selected,
             PopupContent::TopWordsSelection => &popup_states.top_words_selection.selected,
+            PopupContent::Profile => &popup_states.profile.selected,
+            PopupContent::Pace => &popup_states.pace.selected,
+            PopupContent::Preset => &popup_states.preset.selected,
+            PopupContent::Font => &popup_states.font.selected,
+            PopupContent::TextSize => &popup_states.text_size.selected,
+            PopupContent::LineSpacing => &popup_states.line_spacing.selected,
         }
     }
 }
@@ -137,8 +171,11 @@ impl PopupState {
         );
 
         let item_h = 30.0;
-        for (i, item) in items.iter().enumerate() {
-            let item_y = y + 90.0 + i as f32 * item_h;
+        // Long lists, like the installed fonts, scroll to keep the selection in view
+        let rows = (((popup_h - 110.0) / item_h) as usize).max(1);
+        let first = self.selected.saturating_sub(rows - 1);
+        for (i, item) in items.iter().enumerate().skip(first).take(rows) {
+            let item_y = y + 90.0 + (i - first) as f32 * item_h;
             let rect = Rect::new(x + 20.0, item_y - 20.0, popup_w - 40.0, item_h);
 
             if i == self.selected {
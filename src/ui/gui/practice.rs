@@ -6,7 +6,8 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use crate::color_scheme::ColorScheme;
-use crate::practice::{TYPING_LEVELS, check_if_completed};
+use crate::config::Mode;
+use crate::practice::{TYPING_LEVELS, completed_levels};
 use crate::ui::gui::config;
 use crate::utils;
 
@@ -16,7 +17,7 @@ pub fn display_practice_menu(
     emoji_font: Font,
     selected_level: &mut Option<usize>,
     practice_menu: &mut bool,
-    time_mode: &mut bool,
+    mode: &mut Mode,
     pressed_vec: &mut Vec<char>,
     is_correct: &mut VecDeque<i32>,
     pos1: &mut usize,
@@ -110,6 +111,7 @@ pub fn display_practice_menu(
         y += 20.0 + font_size as f32;
     }
     let mut y: f32 = screen_height() / 10.0 + 2.0 * font_size as f32;
+    let completed = completed_levels();
 
     for (i, _) in TYPING_LEVELS
         .iter()
@@ -137,8 +139,7 @@ pub fn display_practice_menu(
             20.0 + font_size as f32,
         );
 
-        let results_path = format!("practice_results/level_{}.txt", i + 1);
-        let show_tick = check_if_completed(results_path.as_str());
+        let show_tick = completed[i];
 
         let is_hovered = if any_hovered {
             button_rect.contains(vec2(mouse_pos.0, mouse_pos.1))
@@ -313,7 +314,7 @@ pub fn display_practice_menu(
     if is_key_pressed(KeyCode::Q) {
         if *practice_menu {
             *practice_menu = false;
-            *time_mode = true;
+            *mode = Mode::Time;
             config::reset_game_state(
                 pressed_vec,
                 is_correct,
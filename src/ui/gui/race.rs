@@ -0,0 +1,189 @@
+use macroquad::prelude::*;
+
+use crate::color_scheme::ColorScheme;
+use crate::config::AppConfig;
+use crate::race::{RacePhase, RaceSession};
+use crate::ui::gui::main::ROBOTO_MONO;
+
+/// Splits the text into lines of at most `per_line` chars, breaking after spaces.
+/// Returns char ranges so typed state can be looked up by index.
+pub(crate) fn wrap(text: &[char], per_line: usize) -> Vec<(usize, usize)> {
+    let per_line = per_line.max(1);
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut last_space = None;
+    for (i, c) in text.iter().enumerate() {
+        if *c == ' ' {
+            last_space = Some(i);
+        }
+        if i + 1 - start > per_line {
+            let end = match last_space {
+                Some(space) if space >= start && space < i => space + 1,
+                _ => i,
+            };
+            lines.push((start, end));
+            start = end;
+        }
+    }
+    if start < text.len() {
+        lines.push((start, text.len()));
+    }
+    lines
+}
+
+/// Runs a race in the GUI window until the player leaves
+pub async fn race_main_async(mut session: RaceSession) {
+    let font = load_ttf_font_from_bytes(ROBOTO_MONO).unwrap();
+    let color_scheme: ColorScheme = AppConfig::load().color_scheme;
+
+    loop {
+        session.tick();
+
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() {
+                session.type_char(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            session.backspace();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            match session.phase {
+                RacePhase::Lobby => session.start(),
+                RacePhase::Done | RacePhase::Disconnected(_) => break,
+                _ => {}
+            }
+        }
+
+        clear_background(color_scheme.bg_color());
+        draw_race(&font, &session, &color_scheme);
+        next_frame().await;
+    }
+}
+
+fn draw_race(font: &Font, session: &RaceSession, color_scheme: &ColorScheme) {
+    let font_size = if screen_width() > 800.0 { 28.0 } else { 20.0 };
+    let small = (font_size * 0.7) as u16;
+    let line_h = font_size * 1.6;
+    let x = screen_width() * 0.1;
+    let width = screen_width() * 0.8;
+    let mut y = screen_height() * 0.12;
+
+    let text = |s: &str, x: f32, y: f32, size: u16, color: Color| {
+        draw_text_ex(s, x, y, TextParams { font: Some(font), font_size: size, color, ..Default::default() });
+    };
+
+    text("TypeMan race", x, y, font_size as u16, color_scheme.main_color());
+    y += line_h * 1.5;
+
+    match &session.phase {
+        RacePhase::Lobby => {
+            text(&format!("Players ({}):", session.players.len()), x, y, small, color_scheme.main_color());
+            for name in &session.players {
+                y += line_h;
+                text(name, x + font_size, y, small, color_scheme.text_color());
+            }
+            y += line_h * 1.5;
+            let hint = match &session.host {
+                Some(host) => format!(
+                    "Others join with 'typeman race join <your address>:{}'. Press Enter to start.",
+                    host.local_addr().port()
+                ),
+                None => "Waiting for the host to start the race...".to_string(),
+            };
+            text(&hint, x, y, small, color_scheme.ref_color());
+        }
+        RacePhase::Countdown(_) => {
+            let left = session.countdown_left().unwrap_or_default().as_secs() + 1;
+            text(&left.to_string(), screen_width() / 2.0, screen_height() / 2.0, (font_size * 3.0) as u16, color_scheme.main_color());
+        }
+        RacePhase::Racing | RacePhase::Waiting => {
+            if let Some(typing) = &session.typing {
+                let status = if session.phase == RacePhase::Waiting {
+                    format!("Done in {:.1}s - waiting for the others", typing.elapsed().as_secs_f64())
+                } else {
+                    format!("{:.0} wpm  {:.0}s", typing.wpm(), typing.elapsed().as_secs_f64())
+                };
+                text(&status, x, y, small, color_scheme.main_color());
+                y += line_h * 1.5;
+
+                let char_w = measure_text("G", Some(font), font_size as u16, 1.0).width;
+                for (start, end) in wrap(&typing.reference, (width / char_w) as usize) {
+                    for i in start..end {
+                        let correct = typing.is_correct(i);
+                        let color = match correct {
+                            Some(true) => color_scheme.correct_color(),
+                            Some(false) => color_scheme.incorrect_color(),
+                            None => color_scheme.ref_color(),
+                        };
+                        let cx = x + (i - start) as f32 * char_w;
+                        text(&typing.reference[i].to_string(), cx, y, font_size as u16, color);
+                        if correct == Some(false) {
+                            draw_line(cx, y - font_size * 0.3, cx + char_w, y - font_size * 0.3, 2.0, color);
+                        }
+                        if i == typing.typed.len() {
+                            draw_line(cx, y + 4.0, cx + char_w, y + 4.0, 2.0, color_scheme.main_color());
+                        }
+                    }
+                    y += line_h;
+                }
+            }
+        }
+        RacePhase::Done => {
+            text(&format!("{:<6}{:<16}{:>7}{:>7}{:>8}", "Place", "Player", "WPM", "Acc%", "Time"), x, y, small, color_scheme.main_color());
+            for s in &session.standings {
+                y += line_h;
+                let (accuracy, time) = if s.finished {
+                    (format!("{:.1}", s.accuracy), format!("{:.1}s", s.time))
+                } else {
+                    ("-".to_string(), "DNF".to_string())
+                };
+                let color = if Some(s.id) == session.id() { color_scheme.main_color() } else { color_scheme.text_color() };
+                text(&format!("{:<6}{:<16}{:>7.1}{:>7}{:>8}", s.place, s.name, s.wpm, accuracy, time), x, y, small, color);
+            }
+            if let Some(message) = &session.message {
+                y += line_h * 1.5;
+                text(message, x, y, small, color_scheme.ref_color());
+            }
+        }
+        RacePhase::Disconnected(reason) => {
+            text(&format!("Race over: {}", reason), x, y, small, color_scheme.incorrect_color());
+        }
+    }
+
+    // Progress bars
+    if !matches!(session.phase, RacePhase::Lobby | RacePhase::Done) {
+        let mut bar_y = screen_height() * 0.6;
+        let bar_h = font_size * 0.5;
+        for player in &session.progress {
+            let color = if Some(player.id) == session.id() { color_scheme.main_color() } else { color_scheme.dimmer_main() };
+            let label = format!("{}  {:.0} wpm{}", player.name, player.wpm, if player.finished { "  ✓" } else { "" });
+            text(&label, x, bar_y, small, color_scheme.text_color());
+            draw_rectangle(x, bar_y + 8.0, width, bar_h, color_scheme.border_color());
+            draw_rectangle(x, bar_y + 8.0, width * player.progress.clamp(0.0, 1.0) as f32, bar_h, color);
+            bar_y += line_h * 1.4;
+        }
+    }
+
+    let footer = match session.phase {
+        RacePhase::Lobby if session.is_host() => "↵ - start the race, Esc - quit",
+        RacePhase::Done | RacePhase::Disconnected(_) => "↵ or Esc - quit",
+        _ => "Esc - quit",
+    };
+    text(footer, x, screen_height() - screen_height() / 7.5, small, color_scheme.ref_color());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_breaks_after_spaces() {
+        let text: Vec<char> = "aaa bbb cccc".chars().collect();
+        assert_eq!(wrap(&text, 8), [(0, 8), (8, 12)]);
+        assert_eq!(wrap(&text, 4), [(0, 4), (4, 8), (8, 12)]);
+    }
+}
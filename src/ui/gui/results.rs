@@ -5,20 +5,10 @@ use macroquad::prelude::*;
 use std::collections::VecDeque;
 
 use crate::color_scheme::ColorScheme;
-use crate::practice;
+use crate::ghost::{self, GhostRun, Keystroke};
+use crate::{pace, practice};
 use crate::utils;
 
-fn calc_standard_deviation(values: &[f64], average_word_length: f64) -> f64 {
-    let wpm_values: Vec<f64> = values
-        .iter()
-        .map(|&cpm| cpm / average_word_length)
-        .collect();
-    let mean = wpm_values.iter().sum::<f64>() / wpm_values.len() as f64;
-    let variance =
-        wpm_values.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / wpm_values.len() as f64;
-    variance.sqrt()
-}
-
 pub fn write_results(
     is_correct: &VecDeque<i32>,
     screen_width: f32,
@@ -33,6 +23,10 @@ pub fn write_results(
     errors_per_second: &Vec<f64>,
     reference: &String,
     practice_level: Option<usize>,
+    pace_wpm: Option<f64>,
+    ghost: Option<&GhostRun>,
+    key_log: &[Keystroke],
+    daily_lines: &[String],
     saved_results: &mut bool,
     color_scheme: &ColorScheme,
 ) {
@@ -159,8 +153,22 @@ pub fn write_results(
         .map(|(i, &cpm)| [i as f64, cpm])
         .collect();
 
+    let ghost_points: Vec<[f64; 2]> = match ghost {
+        Some(ghost) => {
+            let mut speeds = ghost.speed_per_second();
+            speeds.push(*speeds.last().unwrap_or(&0.0));
+            smooth(&speeds, 2, average_word_length)
+                .iter()
+                .enumerate()
+                .map(|(i, &cpm)| [i as f64, cpm])
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
     draw_chart(
         &chart_points,
+        &ghost_points,
         chart_width,
         chart_height,
         chart_x,
@@ -171,6 +179,35 @@ pub fn write_results(
     );
     egui_macroquad::draw();
 
+    let verdict_y = chart_y + chart_height + fontsize_4 as f32 * 2.0 + fontsize_3 as f32 * 2.5;
+    if let Some(target) = pace_wpm {
+        let verdict = pace::verdict(wpm as f64, target);
+        let text_size = measure_text(&verdict, font, fontsize_4, 1.0);
+        draw_text_ex(
+            &verdict,
+            (screen_width - text_size.width) / 2.0,
+            verdict_y,
+            TextParams {
+                font,
+                font_size: fontsize_4,
+                color: color_scheme.dimmer_main(),
+                ..Default::default()
+            },
+        );
+    }
+    if let Some(ghost) = ghost {
+        write_ghost_deltas(ghost, key_log, reference, test_time, font, (screen_width - chart_width) / 2.0, verdict_y + fontsize_4 as f32 * 1.8, chart_width, fontsize_4, color_scheme);
+    }
+    for (i, line) in daily_lines.iter().enumerate() {
+        let text_size = measure_text(line, font, fontsize_4, 1.0);
+        draw_text_ex(
+            line,
+            (screen_width - text_size.width) / 2.0,
+            verdict_y + fontsize_4 as f32 * 1.8 * (i + 1) as f32,
+            TextParams { font, font_size: fontsize_4, color: color_scheme.dimmer_main(), ..Default::default() },
+        );
+    }
+
     if practice_level.is_some() {
         let passed_text_font = if screen_width > 1900.0 && screen_height > 1000.0 {
             30
@@ -230,6 +267,52 @@ pub fn write_results(
     }
 }
 
+/// Overall result against the ghost, then how far ahead (-) or behind (+) each
+/// word was finished, for as many words as fit in `width`
+fn write_ghost_deltas(
+    ghost: &GhostRun,
+    key_log: &[Keystroke],
+    reference: &str,
+    test_time: f32,
+    font: Option<&Font>,
+    x: f32,
+    y: f32,
+    width: f32,
+    font_size: u16,
+    color_scheme: &ColorScheme,
+) {
+    let gap = test_time as f64 - ghost.duration().as_secs_f64();
+    let summary = if gap <= 0.0 {
+        format!("Ghost {}: beaten by {:.1}s", ghost.player, -gap)
+    } else {
+        format!("Ghost {}: lost by {:.1}s", ghost.player, gap)
+    };
+    let summary_size = measure_text(&summary, font, font_size, 1.0);
+    draw_text_ex(
+        &summary,
+        x + (width - summary_size.width) / 2.0,
+        y,
+        TextParams { font, font_size, color: color_scheme.dimmer_main(), ..Default::default() },
+    );
+
+    let deltas = ghost::word_deltas(&ghost::word_times(reference, key_log), &ghost.word_times());
+    let mut word_x = x;
+    for (word, delta) in reference.split_whitespace().zip(deltas) {
+        let word_text = format!("{} ", word);
+        let delta_text = format!("{:+.1}  ", delta);
+        let word_w = measure_text(&word_text, font, font_size, 1.0).width;
+        let delta_w = measure_text(&delta_text, font, font_size, 1.0).width;
+        if word_x + word_w + delta_w > x + width {
+            break;
+        }
+        let color = if delta <= 0.0 { color_scheme.correct_color() } else { color_scheme.incorrect_color() };
+        let line_y = y + font_size as f32 * 1.8;
+        draw_text_ex(&word_text, word_x, line_y, TextParams { font, font_size, color: color_scheme.ref_color(), ..Default::default() });
+        draw_text_ex(&delta_text, word_x + word_w, line_y, TextParams { font, font_size, color, ..Default::default() });
+        word_x += word_w + delta_w;
+    }
+}
+
 fn write_mode(
     font: Option<&Font>,
     x: f32,
@@ -376,12 +459,7 @@ fn write_consistency(
     fontsize_4: u16,
     color_scheme: &ColorScheme,
 ) {
-    let standard_deviation = calc_standard_deviation(speed_per_second, average_word_length);
-    let consistency = if avg_wpm > 0.0 {
-        (100.0 - (standard_deviation / avg_wpm as f64 * 100.0).round()).max(0.0)
-    } else {
-        0.0
-    };
+    let consistency = utils::consistency(speed_per_second, average_word_length, avg_wpm as f64);
 
     let consistency_text = format!("{consistency}%");
     draw_text_ex(
@@ -531,6 +609,7 @@ fn smooth(values: &[f64], window: usize, average_word_length: f64) -> Vec<f64> {
 
 fn draw_chart(
     points: &[[f64; 2]],
+    ghost_points: &[[f64; 2]],
     chart_width: f32,
     chart_height: f32,
     chart_x: f32,
@@ -572,7 +651,7 @@ fn draw_chart(
 
                 let max_x = f64::max(points.len() as f64 - 1.0, 5.0);
                 let mut max_y = 50.0;
-                for point in points {
+                for point in points.iter().chain(ghost_points) {
                     if point[1] > max_y {
                         max_y = point[1];
                     }
@@ -596,6 +675,13 @@ fn draw_chart(
                             .name("Performance");
                         plot_ui.line(line);
 
+                        if !ghost_points.is_empty() {
+                            let ghost_line = Line::new("Ghost", ghost_points.to_vec())
+                                .color(ColorScheme::mq_to_color32(color_scheme.dimmer_main()))
+                                .name("Ghost");
+                            plot_ui.line(ghost_line);
+                        }
+
                         let mut last_drawn_pixel_x: Option<f32> = None;
                         for (i, &val) in errors.iter().enumerate() {
                             if val > 0.0 {
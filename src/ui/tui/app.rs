@@ -1,24 +1,38 @@
 use std::io;
-use crossterm::event::{self, Event as CEvent, KeyEvent, KeyCode};
+use crossterm::event::{self, Event as CEvent, KeyEvent, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::DefaultTerminal;
+use ratatui::layout::Rect;
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
 use chrono;
 
 use crate::batch_size_selection::BatchSizeSelection;
-use crate::ui::tui::ui::render_app;
+use crate::ui::tui::ui::{config_button_at, render_app};
 use crate::{practice, utils};
 use crate::practice::TYPING_LEVELS;
 use crate::language::Language;
 use crate::color_scheme::ColorScheme;
-use crate::config::AppConfig;
+use crate::config::{AppConfig, Mode};
 use crate::button_states::{ButtonStates, ButtonState};
-use crate::ui::tui::popup::{PopupStates, PopupState};
+use crate::ui::tui::popup::{self, PopupClick, PopupData, PopupStates, PopupState};
 use crate::time_selection::TimeSelection;
 use crate::word_number_selection::WordNumberSelection;
 use crate::top_words_selection::TopWordsSelection;
 use crate::settings::Settings;
-use crate::leaderboard::LeaderboardData;
+use crate::leaderboard::{LeaderboardData, LeaderboardView};
+use crate::anticheat::{self, CheatFlag};
+use crate::leaderboard::TestType;
+use crate::pace::{self, Pace};
+use crate::presets::{self, Preset};
+use crate::themes;
+use crate::ghost::{self, GhostRun, Keystroke};
+use crate::daily::{self, DailyChallenge};
+use crate::goals;
+use crate::achievements::{self, AchievementsData};
+use crate::keymap::{Action, Dispatch, KeyPress, KeyReader};
+use crate::export;
+use crate::paths;
+use crate::ui::tui::palette::{self, Command, Palette, Prompt};
 
 
 #[derive(PartialEq, Eq)]
@@ -42,20 +56,17 @@ pub struct App {
     pub config: bool,
     pub punctuation: bool,
     pub numbers: bool,
-    pub time_mode: bool,
-    pub word_mode: bool,
-    pub quote: bool,
-    pub wiki_mode: bool,
+    pub mode: Mode,
     pub batch_size: usize,
     pub selected_config: String,
     pub speed_per_second: Vec<f64>,
     pub char_number: usize,
     pub errors_per_second: Vec<f32>,
-    pub tab_pressed: Instant,
+    /// Turns keys into the actions of the keymap
+    pub keys: KeyReader,
     pub correct_count: usize,
     pub error_count: usize,
     pub practice_menu: bool,
-    pub practice_mode: bool,
     pub selected_level: usize,
     pub timer: Duration,
     pub language: Language,
@@ -67,11 +78,37 @@ pub struct App {
     pub popup_states: PopupStates,
     pub menu_buttons_times: HashMap<String, Instant>,
     pub leaderboard: LeaderboardData,
+    /// Milliseconds since start for every typed character, checked by the anti-cheat
+    pub keystroke_times: Vec<f64>,
+    pub last_flag: Option<CheatFlag>,
+    pub pace: Pace,
+    /// Target WPM of the pace caret, resolved when the test starts
+    pub pace_wpm: Option<f64>,
+    /// Words of the batches already typed through in this test
+    pub earlier_batch_words: usize,
+    pub pace_verdict: Option<String>,
+    /// Where the cursor was after every key press, recorded for ghost races
+    pub key_log: Vec<Keystroke>,
+    /// Earlier run being raced, see `active_ghost`
+    pub ghost: Option<GhostRun>,
+    /// Daily challenge being typed, see `active_daily`
+    pub daily: Option<DailyChallenge>,
+    /// Streak and earlier daily results, shown with the challenge
+    pub daily_lines: Vec<String>,
+    /// Progress on the goals from the config, updated after every test
+    pub goal_summary: Option<String>,
+    pub achievements: AchievementsData,
+    /// Achievements unlocked by the last result
+    pub achievement_line: Option<String>,
+    pub palette: Palette,
+    /// Text of a custom file being typed, see `active_custom`
+    pub custom_text: Option<String>,
 }
 
 impl App {
     pub fn new() -> Self {
         let app_config = AppConfig::load();
+        let goal_summary = goals::today_summary(&app_config.goals);
         
         Self {
             exit: false,
@@ -87,30 +124,28 @@ impl App {
             config: false,
             punctuation: app_config.punctuation,
             numbers: app_config.numbers,
-            time_mode: app_config.time_mode,
-            word_mode: app_config.word_mode,
-            quote: app_config.quote,
-            wiki_mode: app_config.wiki_mode,
+            mode: app_config.mode,
             batch_size: app_config.batch_size,
-            selected_config: if app_config.time_mode { "time".into() }
-                else if app_config.word_mode { "words".into() }
-                else if app_config.quote { "quote".into() }
-                else if app_config.practice_mode { "practice".into() }
-                else { "time".into() },
+            selected_config: match app_config.mode {
+                Mode::Words => "words".into(),
+                Mode::Quote => "quote".into(),
+                Mode::Practice => "practice".into(),
+                _ => "time".into(),
+            },
             speed_per_second: Vec::new(),
             char_number: 0,
             errors_per_second: Vec::new(),
-            tab_pressed: Instant::now() - Duration::from_secs(5),
+            keys: KeyReader::default(),
             correct_count: 0,
             error_count: 0,
             practice_menu: false,
-            practice_mode: app_config.practice_mode,
             selected_level: app_config.selected_level,
             timer: Duration::from_secs(0),
             language: app_config.language,
             color_scheme: app_config.color_scheme,
             word_number: app_config.word_number,
             top_words: app_config.top_words,
+            pace: app_config.pace,
             app_config,
             button_states: ButtonStates::new(),
             popup_states: PopupStates {
@@ -121,6 +156,9 @@ impl App {
                 settings: PopupState { open: false, selected: 0 },
                 batch_size_selection: PopupState { open: false, selected: 0 },
                 top_words_selection: PopupState { open: false, selected: 0 },
+                profile: PopupState { open: false, selected: 0 },
+                pace: PopupState { open: false, selected: 0 },
+                preset: PopupState { open: false, selected: 0 },
             },
             menu_buttons_times: HashMap::from([
                 ("settings".to_string(), Instant::now() - Duration::from_secs(5)),
@@ -136,49 +174,205 @@ impl App {
                 open: false,
                 entries: crate::leaderboard::load_entries().unwrap_or_default(),
                 selected: 0,
+                view: LeaderboardView::Local,
+                owners: Vec::new(),
             },
+            keystroke_times: Vec::new(),
+            last_flag: None,
+            pace_wpm: None,
+            earlier_batch_words: 0,
+            pace_verdict: None,
+            key_log: Vec::new(),
+            ghost: None,
+            daily: None,
+            daily_lines: Vec::new(),
+            goal_summary,
+            achievements: AchievementsData::new(),
+            achievement_line: None,
+            palette: Palette::default(),
+            custom_text: None,
         }
     }
 
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
-        if self.quote {
-            self.reference = utils::get_random_quote();
-            self.batch_size = self.reference.split_whitespace().count();
-        } else if self.practice_mode {
-            let level = practice::get_first_not_done();
-            self.reference = practice::create_words(TYPING_LEVELS[level].1, 50);
-        } else if self.time_mode {
-            self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
-        } else if self.wiki_mode {
-            self.reference = utils::get_wiki_summary();
+    /// Starts a race against an earlier run on its text
+    pub fn with_ghost(run: GhostRun) -> Self {
+        let mut app = App::new();
+        app.ghost = Some(run);
+        app
+    }
+
+    /// Starts on today's daily challenge
+    pub fn with_daily() -> Self {
+        let mut app = App::new();
+        app.start_daily();
+        app
+    }
+
+    /// Switches to today's daily challenge
+    fn start_daily(&mut self) {
+        let challenge = DailyChallenge::today(self.language);
+        self.daily_lines = daily::summary(challenge.date);
+        self.daily = Some(challenge);
+        self.ghost = None;
+        self.custom_text = None;
+        self.restart();
+    }
+
+    /// The daily challenge, as long as its text is still the one on screen
+    pub fn active_daily(&self) -> Option<&DailyChallenge> {
+        self.daily.as_ref().filter(|daily| daily.text == self.reference)
+    }
+
+    /// The custom text, as long as it's still the one on screen
+    pub fn active_custom(&self) -> Option<&String> {
+        self.custom_text.as_ref().filter(|text| **text == self.reference)
+    }
+
+    /// Ghost races, daily challenges and custom texts run to the end of their text, whatever the mode
+    pub fn fixed_text(&self) -> bool {
+        self.active_ghost().is_some() || self.active_daily().is_some() || self.active_custom().is_some()
+    }
+
+    /// The ghost being raced, as long as its text is still the one on screen
+    pub fn active_ghost(&self) -> Option<&GhostRun> {
+        self.ghost.as_ref().filter(|ghost| ghost.reference == self.reference)
+    }
+
+    /// Char of the reference the ghost's cursor is on
+    pub fn ghost_index(&self) -> Option<usize> {
+        if self.game_state != GameState::Started {
+            return None;
+        }
+        let index = self.active_ghost()?.position_at(self.timer);
+        (index < self.is_correct.len()).then_some(index)
+    }
+
+    /// Leaderboard bucket of the current test settings
+    pub fn test_type(&self) -> TestType {
+        if let Some(daily) = self.active_daily() {
+            TestType::Daily(daily.date)
+        } else {
+            match self.mode {
+                Mode::Practice => TestType::Practice(self.selected_level + 1),
+                Mode::Time => TestType::Time(self.test_time as u32),
+                Mode::Words => TestType::Word(self.word_number),
+                Mode::Quote => TestType::Quote,
+                Mode::Wiki => TestType::Wiki,
+            }
+        }
+    }
+
+    /// Char of the reference the pace caret is on, if it's running and still in this batch
+    pub fn pace_index(&self) -> Option<usize> {
+        if self.game_state != GameState::Started {
+            return None;
+        }
+        let words = pace::words_at(self.pace_wpm?, self.timer) - self.earlier_batch_words as f64;
+        pace::caret_index(&self.reference, words)
+    }
+
+    /// Picks the first text for the configured mode
+    fn load_initial_reference(&mut self) {
+        if let Some(ghost) = &self.ghost {
+            self.reference = ghost.reference.clone();
+        } else if let Some(daily) = &self.daily {
+            self.reference = daily.text.clone();
         } else {
-            self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
+            match self.mode {
+                Mode::Quote => {
+                    self.reference = utils::get_random_quote();
+                    self.batch_size = self.reference.split_whitespace().count();
+                }
+                Mode::Practice => {
+                    let level = practice::get_first_not_done();
+                    self.reference = practice::create_words(TYPING_LEVELS[level].1, 50);
+                }
+                Mode::Time => {
+                    self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
+                }
+                Mode::Wiki => self.reference = utils::get_wiki_summary(),
+                Mode::Words => {
+                    self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
+                }
+            }
         }
    
         self.is_correct = vec![0; self.reference.chars().count()];
+    }
+
+    /// Switches to another profile and reloads its config, leaderboard and text
+    fn switch_profile(&mut self, name: &str) {
+        if let Err(e) = crate::profile::set_active(name) {
+            eprintln!("Failed to switch profile: {}", e);
+            return;
+        }
+        *self = App::new();
+        self.load_initial_reference();
+    }
+
+    /// Applies the named preset, or the next one when no name is given
+    fn apply_preset(&mut self, name: Option<&str>) {
+        self.save_config();
+        let applied = match name {
+            Some(name) => presets::apply_named(name),
+            None => presets::cycle(),
+        };
+        if let Err(e) = applied {
+            eprintln!("Failed to apply preset: {}", e);
+            return;
+        }
+        *self = App::new();
+        self.load_initial_reference();
+    }
+
+    /// The preset that was applied last, while the settings still match it
+    pub fn active_preset(&self) -> Option<&Preset> {
+        let preset = presets::find(&self.app_config.presets, self.app_config.preset.as_deref()?)?;
+        let current = AppConfig {
+            mode: self.mode,
+            test_time: self.test_time,
+            word_number: self.word_number,
+            top_words: self.top_words,
+            punctuation: self.punctuation,
+            numbers: self.numbers,
+            language: self.language,
+            ..AppConfig::default()
+        };
+        preset.matches(&current).then_some(preset)
+    }
+
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        self.load_initial_reference();
         let mut last_recorded_time = Instant::now();
         
         while !self.exit {
+            // Edited theme files show up without a restart
+            themes::reload_if_changed();
             self.button_states = ButtonStates {
                 settings: ButtonState::new("settings", "settings", "...", false, true),
                 divider0: ButtonState::new("|", "|", "|", true, true),
-                punctuation: ButtonState::new("punctuation", "! punctuation", "! punct", self.punctuation, !self.quote && !self.practice_mode && !self.wiki_mode),
-                numbers: ButtonState::new("numbers", "# numbers", "# num", self.numbers, !self.quote && !self.practice_mode && !self.wiki_mode),
-                divider1: ButtonState::new("|", "|", "|", true, self.time_mode || self.word_mode),
-                time: ButtonState::new("time", "⌄ time", "⌄ time", self.time_mode, true),
-                words: ButtonState::new("words", "⌄ words", "⌄ words", self.word_mode, true),
-                quote: ButtonState::new("quote", "quote", "quote", self.quote, true),
-                wiki_mode: ButtonState::new("wiki", "wikipedia", "wiki", self.wiki_mode, true),
-                practice: ButtonState::new("practice", "practice", "practice", self.practice_mode, true),
+                punctuation: ButtonState::new("punctuation", "! punctuation", "! punct", self.punctuation, !matches!(self.mode, Mode::Quote | Mode::Practice | Mode::Wiki)),
+                numbers: ButtonState::new("numbers", "# numbers", "# num", self.numbers, !matches!(self.mode, Mode::Quote | Mode::Practice | Mode::Wiki)),
+                divider1: ButtonState::new("|", "|", "|", true, matches!(self.mode, Mode::Time | Mode::Words)),
+                time: ButtonState::new("time", "⌄ time", "⌄ time", self.mode == Mode::Time, true),
+                words: ButtonState::new("words", "⌄ words", "⌄ words", self.mode == Mode::Words, true),
+                quote: ButtonState::new("quote", "quote", "quote", self.mode == Mode::Quote, true),
+                wiki_mode: ButtonState::new("wiki", "wikipedia", "wiki", self.mode == Mode::Wiki, true),
+                practice: ButtonState::new("practice", "practice", "practice", self.mode == Mode::Practice, true),
             };
 
             if self.game_state != GameState::Started {
                 last_recorded_time = Instant::now();
             }
             if event::poll(Duration::from_millis(16))? {
-                if let CEvent::Key(key) = event::read()? {
+                match event::read()? {
                     // Pass mutable reference to button_states
-                    self.handle_key_event(key, self.reference.clone())?;
+                    CEvent::Key(key) => self.handle_key_event(key, self.reference.clone())?,
+                    CEvent::Mouse(mouse) => {
+                        let size = terminal.size()?;
+                        self.handle_mouse_event(mouse, Rect::new(0, 0, size.width, size.height))?;
+                    }
+                    _ => {}
                 }
             }
             self.timer = if let Some(start_time) = self.start_time {
@@ -193,14 +387,18 @@ impl App {
                 Duration::from_secs(0)
             };
 
-            if self.game_state != GameState::Results && ((self.test_time - self.timer.as_secs_f32() < 0.0 && self.game_state == GameState::Started && self.time_mode)
-                || (self.words_done >= self.word_number && self.word_mode)
-                || (self.words_done >= self.reference.split_whitespace().count() && (self.quote || self.wiki_mode) && self.game_state != GameState::Results)
+            let test_over = if self.fixed_text() {
+                self.game_state == GameState::Started && self.pos1 >= self.reference.chars().count()
+            } else {
+                (self.test_time - self.timer.as_secs_f32() < 0.0 && self.game_state == GameState::Started && self.mode == Mode::Time)
+                || (self.words_done >= self.word_number && self.mode == Mode::Words)
+                || (self.words_done >= self.reference.split_whitespace().count() && matches!(self.mode, Mode::Quote | Mode::Wiki) && self.game_state != GameState::Results)
                 || (self.words_done >= self.word_number 
-                    && (self.word_mode|| self.practice_mode)
+                    && matches!(self.mode, Mode::Words | Mode::Practice)
                     && self.game_state != GameState::Results)
-                || ((self.words_done >= 50 || self.pos1 >= self.reference.chars().count()) && self.practice_mode && self.game_state != GameState::Results))
-
+                || ((self.words_done >= 50 || self.pos1 >= self.reference.chars().count()) && self.mode == Mode::Practice && self.game_state != GameState::Results)
+            };
+            if self.game_state != GameState::Results && test_over
             {
                 self.errors_per_second.push(self.errors_this_second);
                 let total_typed = self.pressed_vec.len();
@@ -218,7 +416,7 @@ impl App {
                     0.0
                 };
 
-                if self.practice_mode {
+                if self.mode == Mode::Practice && !self.fixed_text() {
                     practice::save_results(
                         self.test_time as f64,
                         accuracy as f64,
@@ -229,6 +427,8 @@ impl App {
                 
                 // Save result to leaderboard
                 self.save_to_leaderboard();
+                self.goal_summary = goals::today_summary(&self.app_config.goals);
+                self.achievement_line = achievements::unlocked_line(&achievements::check_saved());
             }
             let now = Instant::now();
             let time_since_last = now.duration_since(last_recorded_time);
@@ -259,6 +459,11 @@ impl App {
         let reference_chars: Vec<char> = reference.chars().collect();
 
         if key_event.kind == crossterm::event::KeyEventKind::Press {
+            self.palette.message = None;
+            if self.palette.open {
+                self.handle_palette_key(key_event);
+                return Ok(());
+            }
             let schemes = ColorScheme::all();
             if self.popup_states.color_scheme.open {
                 match key_event.code {
@@ -348,10 +553,10 @@ impl App {
                             _ => Language::English,
                         };
                         self.popup_states.language.open = false;
-                        if self.word_mode || self.time_mode {
-                            if self.time_mode {
+                        if matches!(self.mode, Mode::Time | Mode::Words) {
+                            if self.mode == Mode::Time {
                                 self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
-                            } else if self.word_mode {
+                            } else if self.mode == Mode::Words {
                                 self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
                             }
                             self.is_correct = vec![0; self.reference.chars().count()];
@@ -389,9 +594,9 @@ impl App {
                         if self.popup_states.word_number_selection.selected < schemes.len() {
                             self.word_number = schemes[self.popup_states.word_number_selection.selected].to_words() as usize;
                         }
-                        if self.time_mode {
+                        if self.mode == Mode::Time {
                             self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
-                        } else if self.word_mode {
+                        } else if self.mode == Mode::Words {
                             self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
                         }
                         self.is_correct = vec![0; self.reference.chars().count()];
@@ -429,9 +634,9 @@ impl App {
                         if self.popup_states.batch_size_selection.selected < schemes.len() {
                             self.batch_size = schemes[self.popup_states.batch_size_selection.selected].to_words() as usize;
                         }
-                        if self.time_mode {
+                        if self.mode == Mode::Time {
                             self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
-                        } else if self.word_mode {
+                        } else if self.mode == Mode::Words {
                             self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
                         }
                         self.is_correct = vec![0; self.reference.chars().count()];
@@ -469,9 +674,9 @@ impl App {
                         if self.popup_states.top_words_selection.selected < schemes.len() {
                             self.top_words = schemes[self.popup_states.top_words_selection.selected].to_words() as usize;
                         }
-                        if self.time_mode {
+                        if self.mode == Mode::Time {
                             self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
-                        } else if self.word_mode {
+                        } else if self.mode == Mode::Words {
                             self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
                         }
                         self.is_correct = vec![0; self.reference.chars().count()];
@@ -486,6 +691,100 @@ impl App {
                 }
             }
 
+            if self.popup_states.profile.open {
+                let profiles = crate::profile::list();
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.popup_states.profile.open = false;
+                        return Ok(());
+                    }
+                    KeyCode::Up => {
+                        if self.popup_states.profile.selected > 0 {
+                            self.popup_states.profile.selected -= 1;
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Down => {
+                        if self.popup_states.profile.selected < profiles.len() - 1 {
+                            self.popup_states.profile.selected += 1;
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Enter => {
+                        self.popup_states.profile.open = false;
+                        if let Some(name) = profiles.get(self.popup_states.profile.selected) {
+                            if *name != crate::profile::active() {
+                                self.save_config();
+                                self.switch_profile(name);
+                            }
+                        }
+                        return Ok(());
+                    }
+                    _ => return Ok(()),
+                }
+            }
+
+            if self.popup_states.pace.open {
+                let paces = Pace::all();
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.popup_states.pace.open = false;
+                        return Ok(());
+                    }
+                    KeyCode::Up => {
+                        if self.popup_states.pace.selected > 0 {
+                            self.popup_states.pace.selected -= 1;
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Down => {
+                        if self.popup_states.pace.selected < paces.len() - 1 {
+                            self.popup_states.pace.selected += 1;
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Enter => {
+                        if let Some(&pace) = paces.get(self.popup_states.pace.selected) {
+                            self.pace = pace;
+                        }
+                        self.popup_states.pace.open = false;
+                        self.save_config();
+                        return Ok(());
+                    }
+                    _ => return Ok(()),
+                }
+            }
+
+            if self.popup_states.preset.open {
+                let names: Vec<String> = self.app_config.presets.iter().map(|p| p.name.clone()).collect();
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.popup_states.preset.open = false;
+                        return Ok(());
+                    }
+                    KeyCode::Up => {
+                        if self.popup_states.preset.selected > 0 {
+                            self.popup_states.preset.selected -= 1;
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Down => {
+                        if self.popup_states.preset.selected + 1 < names.len() {
+                            self.popup_states.preset.selected += 1;
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Enter => {
+                        self.popup_states.preset.open = false;
+                        if let Some(name) = names.get(self.popup_states.preset.selected) {
+                            self.apply_preset(Some(name));
+                        }
+                        return Ok(());
+                    }
+                    _ => return Ok(()),
+                }
+            }
+
             if self.popup_states.settings.open {
                 match key_event.code {
                     KeyCode::Esc => {
@@ -520,14 +819,47 @@ impl App {
                             self.popup_states.batch_size_selection.open = true;
                         } else if self.popup_states.settings.selected == 3 {
                             self.popup_states.top_words_selection.open = true;
+                        } else if self.popup_states.settings.selected == 4 {
+                            self.popup_states.profile.open = true;
+                            let active = crate::profile::active();
+                            self.popup_states.profile.selected = crate::profile::list().iter().position(|p| *p == active).unwrap_or(0);
+                        } else if self.popup_states.settings.selected == 5 {
+                            self.popup_states.pace.open = true;
+                            self.popup_states.pace.selected = Pace::all().iter().position(|&p| p == self.pace).unwrap_or(0);
+                        } else if self.popup_states.settings.selected == 6 {
+                            self.popup_states.preset.open = true;
+                            let current = self.app_config.preset.as_deref().unwrap_or_default();
+                            self.popup_states.preset.selected = self.app_config.presets.iter().position(|p| p.name == current).unwrap_or(0);
                         }
                     }
                     _ => return Ok(()),
                 }
             }
 
+            let action = match KeyPress::from_crossterm(&key_event).map(|press| self.keys.read(&self.app_config.keymap, press)) {
+                Some(Dispatch::Action(action)) => Some(action),
+                Some(Dispatch::Leader) => return Ok(()),
+                Some(Dispatch::Key(_)) | None => None,
+            };
+
+            if self.achievements.open {
+                match (action, key_event.code) {
+                    (Some(Action::Achievements), _) | (_, KeyCode::Esc) => self.achievements.open = false,
+                    (_, KeyCode::Up) => self.achievements.scroll = self.achievements.scroll.saturating_sub(1),
+                    (_, KeyCode::Down) => {
+                        self.achievements.scroll = (self.achievements.scroll + 1).min(achievements::all().len().saturating_sub(1))
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+
             // Handle leaderboard if it's open
             if self.leaderboard.open {
+                if action == Some(Action::Leaderboard) {
+                    self.leaderboard.open = false;
+                    return Ok(());
+                }
                 match key_event.code {
                     KeyCode::Esc => {
                         self.leaderboard.open = false;
@@ -545,26 +877,20 @@ impl App {
                         }
                         return Ok(());
                     }
-                    KeyCode::Tab => {
-                        self.tab_pressed = Instant::now();
-                        return Ok(());
-                    }
-                    KeyCode::Char('l') | KeyCode::Char('L') => {
-                        if self.tab_pressed.elapsed() < Duration::from_secs(1) {
-                            self.leaderboard.open = false;
-                            self.tab_pressed = Instant::now() - Duration::from_secs(5);
-                        }
+                    KeyCode::Left | KeyCode::Right => {
+                        self.leaderboard.cycle_view(key_event.code == KeyCode::Right);
                         return Ok(());
                     }
                     _ => return Ok(()),
                 }
             }
 
+            if let Some(action) = action {
+                self.perform(action);
+                return Ok(());
+            }
+
             match key_event.code {
-                KeyCode::Esc => {
-                    self.save_config();
-                    self.exit = true;
-                },
                 KeyCode::Backspace => {
                     if !self.pressed_vec.is_empty() && reference_chars.get(self.pos1) == Some(&' ') {
                         self.words_done = self.words_done.saturating_sub(1);
@@ -578,6 +904,9 @@ impl App {
                     if self.pos1 > 0 {
                         self.pos1 -= 1;
                     }
+                    if let (Some(start_time), GameState::Started) = (self.start_time, &self.game_state) {
+                        self.key_log.push(Keystroke { ms: start_time.elapsed().as_secs_f64() * 1000.0, pos: self.pos1 });
+                    }
                     self.config = false;
                 }
                 KeyCode::Up => {
@@ -596,58 +925,9 @@ impl App {
                         self.config = true;
                     }
                 }
-                KeyCode::Tab => {
-                    self.tab_pressed = Instant::now();
-                },
                 KeyCode::Enter => {
-                    if self.tab_pressed.elapsed() < Duration::from_secs(1) {
-                        if self.word_mode {
-                            self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
-                        } else if self.time_mode {
-                            self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
-                        } else if self.quote {
-                            self.reference = utils::get_random_quote();
-                        } else if self.practice_mode {
-                            self.reference = practice::create_words(TYPING_LEVELS[self.selected_level].1, 50);
-                        } else if self.wiki_mode {
-                            self.reference = utils::get_wiki_summary();
-                        }
-                        self.is_correct = vec![0; self.reference.chars().count()];
-                        self.pressed_vec.clear();
-                        self.pos1 = 0;
-                        self.words_done = 0;
-                        self.errors_this_second = 0.0;
-                        self.start_time = None;
-                        self.game_state = GameState::NotStarted;
-                        self.speed_per_second.clear();
-                        self.char_number = 0;
-                        self.errors_per_second.clear();
-                        self.tab_pressed = Instant::now() - Duration::from_secs(5);
-                        self.correct_count = 0;
-                        self.error_count = 0;
-                    }
                     if self.practice_menu {
-                        self.practice_menu = false;
-                        self.practice_mode = true;
-                        self.time_mode = false;
-                        self.word_mode = false;
-                        self.quote = false;
-                        self.wiki_mode = false;
-                        self.pressed_vec.clear();
-                        self.pos1 = 0;
-                        self.words_done = 0;
-                        self.errors_this_second = 0.0;
-                        self.start_time = None;
-                        self.game_state = GameState::NotStarted;
-                        self.speed_per_second.clear();
-                        self.char_number = 0;
-                        self.errors_per_second.clear();
-                        self.tab_pressed = Instant::now() - Duration::from_secs(5);
-                        self.correct_count = 0;
-                        self.error_count = 0;
-                        self.config = false;
-                        self.reference = practice::create_words(TYPING_LEVELS[self.selected_level].1, 50);
-                        self.is_correct = vec![0; self.reference.chars().count()];
+                        self.start_practice_level(self.selected_level);
                     }
                     if self.config {
                         match self.selected_config.as_str() {
@@ -655,12 +935,7 @@ impl App {
                                 if self.menu_buttons_times.get("time").map_or(true, |&t| t.elapsed() <= Duration::from_millis(500)) {
                                     self.popup_states.time_selection.open = true;
                                 }
-                                self.time_mode = true;
-                                self.word_mode = false;
-                                self.quote = false;
-                                self.practice_mode = false;
-                                self.wiki_mode = false;
-                                self.practice_mode = false;
+                                self.mode = Mode::Time;
                                 if let Some(time) = self.menu_buttons_times.get_mut("time") {
                                     *time = Instant::now();
                                 }
@@ -669,22 +944,12 @@ impl App {
                                 if self.menu_buttons_times.get("words").map_or(true, |&t| t.elapsed() <= Duration::from_millis(500)) {
                                     self.popup_states.word_number_selection.open = true;
                                 }
-                                self.time_mode = false;
-                                self.word_mode = true;
-                                self.wiki_mode = false;
-                                self.quote = false;
-                                self.practice_mode = false;
+                                self.mode = Mode::Words;
                                 if let Some(time) = self.menu_buttons_times.get_mut("words") {
                                     *time = Instant::now();
                                 }
                             }
-                            "quote" => {
-                                self.quote = true;
-                                self.time_mode = false;
-                                self.wiki_mode = false;
-                                self.word_mode = false;
-                                self.practice_mode = false;
-                            }
+                            "quote" => self.mode = Mode::Quote,
                             "practice" => {
                                 self.practice_menu = !self.practice_menu;
                                 self.selected_level = practice::get_first_not_done();
@@ -695,13 +960,7 @@ impl App {
                             "numbers" => {
                                 self.numbers = !self.numbers;
                             }
-                            "wiki" => {
-                                self.quote = false;
-                                self.time_mode = false;
-                                self.word_mode = false;
-                                self.wiki_mode = true;
-                                self.practice_mode = false;
-                            }
+                            "wiki" => self.mode = Mode::Wiki,
                             "language" => {
                                 self.popup_states.language.open = true;
                                 self.popup_states.language.selected = match self.language {
@@ -741,11 +1000,14 @@ impl App {
                             }
                             _ => {}
                         }
+                        self.ghost = None;
+                        self.daily = None;
+                        self.custom_text = None;
                         if self.selected_config == "quote" {
                             self.reference = utils::get_random_quote();
-                        } else if self.time_mode {
+                        } else if self.mode == Mode::Time {
                             self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
-                        } else if self.wiki_mode {
+                        } else if self.mode == Mode::Wiki {
                             self.reference = utils::get_wiki_summary();
                         } else if !self.popup_states.settings.open {
                             self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
@@ -760,7 +1022,6 @@ impl App {
                         self.speed_per_second.clear();
                         self.char_number = 0;
                         self.errors_per_second.clear();
-                        self.tab_pressed = Instant::now() - Duration::from_secs(5);
                         self.correct_count = 0;
                         self.error_count = 0;
                         self.save_config();
@@ -823,21 +1084,16 @@ impl App {
                     }
                 }
                 KeyCode::Char(ch) => {
-                    // Handle Tab+L leaderboard toggle
-                    if (ch == 'l' || ch == 'L') && self.tab_pressed.elapsed() < Duration::from_secs(1) {
-                        self.leaderboard.open = !self.leaderboard.open;
-                        self.tab_pressed = Instant::now() - Duration::from_secs(5);
-                        // Reload entries when opening leaderboard
-                        if self.leaderboard.open {
-                            self.leaderboard.entries = crate::leaderboard::load_entries().unwrap_or_default();
-                            self.leaderboard.selected = 0;
-                        }
+                    // `:` opens the palette before the test starts, unless the text starts with one
+                    if ch == ':' && self.game_state != GameState::Started && !self.practice_menu && !self.reference.starts_with(':') {
+                        self.palette.show(palette::entries(&self.app_config));
                         return Ok(());
                     }
-                    
                     if self.practice_menu && ch == 'q' {
                         self.practice_menu = false;
-                        self.practice_mode = false;
+                        if self.mode == Mode::Practice {
+                            self.mode = Mode::default();
+                        }
                         return Ok(());
                     }
                     if self.is_correct[0] == 0 && ch == ' ' {
@@ -851,6 +1107,15 @@ impl App {
                         if self.game_state == GameState::NotStarted {
                             self.game_state = GameState::Started;
                             self.start_time = Some(Instant::now());
+                            self.keystroke_times.clear();
+                            self.last_flag = None;
+                            self.pace_wpm = self.pace.target_wpm(&self.test_type(), self.language);
+                            self.earlier_batch_words = 0;
+                            self.pace_verdict = None;
+                            self.key_log.clear();
+                        }
+                        if let Some(start_time) = self.start_time {
+                            self.keystroke_times.push(start_time.elapsed().as_secs_f64() * 1000.0);
                         }
                         if self.is_correct.len() > self.pos1 {
                             
@@ -865,14 +1130,17 @@ impl App {
                                 self.is_correct[self.pos1] = -1; // Incorrect
                                 self.errors_this_second += 1.0;
                                 self.error_count += 1;
-                                if !self.practice_mode {
+                                if self.mode != Mode::Practice {
                                     self.pos1 += 1;
                                 }
                             }
                         }
                         
                         self.pressed_vec.push(ch);
-                        if (reference_chars.get(self.pos1) == Some(&' ') && !self.practice_mode) || (reference_chars.get(self.pos1) == Some(&' ') && self.is_correct[self.pos1] != -1 || self.pos1 == reference_chars.len()) {
+                        if let Some(start_time) = self.start_time {
+                            self.key_log.push(Keystroke { ms: start_time.elapsed().as_secs_f64() * 1000.0, pos: self.pos1 });
+                        }
+                        if (reference_chars.get(self.pos1) == Some(&' ') && self.mode != Mode::Practice) || (reference_chars.get(self.pos1) == Some(&' ') && self.is_correct[self.pos1] != -1 || self.pos1 == reference_chars.len()) {
                             self.words_done += 1;
                         }
                     }
@@ -880,7 +1148,7 @@ impl App {
 
                     if self.pos1 >= self.reference.chars().count() {
                         // If we've reached the end of reference text, count the final word for word/quote modes
-                        if (self.word_mode || self.quote) && self.pos1 > 0 {
+                        if matches!(self.mode, Mode::Words | Mode::Quote) && self.pos1 > 0 {
                             // Check if we just completed a word (not already counted)
                             let previous_char = reference_chars.get(self.pos1 - 1);
                             if previous_char.is_some() && previous_char != Some(&' ') {
@@ -889,10 +1157,11 @@ impl App {
                         }
                         
                         // Only generate new reference if we haven't reached target word count yet
-                        if self.time_mode || self.word_mode {
-                            if self.time_mode {
+                        if matches!(self.mode, Mode::Time | Mode::Words) && !self.fixed_text() {
+                            self.earlier_batch_words += self.reference.split_whitespace().count();
+                            if self.mode == Mode::Time {
                                 self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size);
-                            } else if self.word_mode {
+                            } else if self.mode == Mode::Words {
                                 self.reference = utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number));
                             }
                             self.is_correct = vec![0; self.reference.chars().count()];
@@ -906,15 +1175,302 @@ impl App {
         Ok(())
     }
 
+    /// Clicks act like choosing with the keyboard and pressing Enter; the wheel scrolls lists
+    fn handle_mouse_event(&mut self, mouse: MouseEvent, screen: Rect) -> io::Result<()> {
+        if self.palette.open {
+            return Ok(());
+        }
+        let scroll_up = match mouse.kind {
+            MouseEventKind::ScrollUp => true,
+            MouseEventKind::ScrollDown => false,
+            MouseEventKind::Down(MouseButton::Left) => return self.handle_click(mouse.column, mouse.row, screen),
+            _ => return Ok(()),
+        };
+        if let Some(content) = popup::open_popup(self) {
+            let last = content.items().len().saturating_sub(1);
+            let selected = content.selected_index_mut(self);
+            *selected = if scroll_up { selected.saturating_sub(1) } else { (*selected + 1).min(last) };
+        } else if self.leaderboard.open || self.achievements.open || self.practice_menu {
+            self.press(if scroll_up { KeyCode::Up } else { KeyCode::Down })?;
+        }
+        Ok(())
+    }
+
+    fn handle_click(&mut self, column: u16, row: u16, screen: Rect) -> io::Result<()> {
+        if let Some(content) = popup::open_popup(self) {
+            return match popup::popup_click(self, screen, &content, column, row) {
+                PopupClick::Item(index) => {
+                    *content.selected_index_mut(self) = index;
+                    self.press(KeyCode::Enter)
+                }
+                PopupClick::Inside => Ok(()),
+                PopupClick::Outside => self.press(KeyCode::Esc),
+            };
+        }
+        if self.game_state == GameState::Started {
+            return Ok(());
+        }
+        if let Some(label) = config_button_at(self, screen, column, row) {
+            self.config = true;
+            self.selected_config = label;
+            self.press(KeyCode::Enter)?;
+        }
+        Ok(())
+    }
+
+    /// Handles `code` as if it was typed
+    fn press(&mut self, code: KeyCode) -> io::Result<()> {
+        self.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE), self.reference.clone())
+    }
+
+    /// Carries out an action of the keymap
+    fn perform(&mut self, action: Action) {
+        let idle = self.game_state != GameState::Started;
+        match action {
+            Action::Quit => {
+                self.save_config();
+                self.exit = true;
+            }
+            Action::Restart => self.restart(),
+            Action::NewText => {
+                self.ghost = None;
+                self.daily = None;
+                self.custom_text = None;
+                self.restart();
+            }
+            Action::RepeatText => self.reset_test(),
+            Action::Settings if idle => self.popup_states.settings.open = true,
+            Action::CommandPalette if idle => self.palette.show(palette::entries(&self.app_config)),
+            Action::Leaderboard => {
+                self.leaderboard.open = !self.leaderboard.open;
+                // Reload entries when opening leaderboard
+                if self.leaderboard.open {
+                    self.leaderboard.selected = 0;
+                    self.leaderboard.reload();
+                }
+            }
+            Action::Practice if idle => {
+                self.practice_menu = !self.practice_menu;
+                self.selected_level = practice::get_first_not_done();
+            }
+            Action::Achievements if idle => self.achievements.toggle(),
+            Action::NextPreset if idle => self.apply_preset(None),
+            Action::Daily if idle => self.start_daily(),
+            // Races the run that just finished
+            Action::GhostRace if self.game_state == GameState::Results => {
+                if let Some(run) = ghost::load_saved().pop() {
+                    self.ghost = Some(run);
+                    self.daily = None;
+                    self.custom_text = None;
+                    self.restart();
+                }
+            }
+            Action::ModeTime if idle => self.switch_mode(Mode::Time),
+            Action::ModeWords if idle => self.switch_mode(Mode::Words),
+            Action::ModeQuote if idle => self.switch_mode(Mode::Quote),
+            Action::ModeWiki if idle => self.switch_mode(Mode::Wiki),
+            _ => {}
+        }
+    }
+
+    /// Switches to another kind of test with a new text
+    fn switch_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.practice_menu = false;
+        self.ghost = None;
+        self.daily = None;
+        self.custom_text = None;
+        self.restart();
+        self.save_config();
+    }
+
+    /// Starts typing practice level `level`, counted from 0
+    fn start_practice_level(&mut self, level: usize) {
+        self.selected_level = level;
+        self.practice_menu = false;
+        self.mode = Mode::Practice;
+        self.config = false;
+        self.ghost = None;
+        self.daily = None;
+        self.custom_text = None;
+        self.reference = practice::create_words(TYPING_LEVELS[level].1, 50);
+        self.reset_test();
+    }
+
+    /// Starts over with a new text after a setting changed
+    fn apply_setting(&mut self) {
+        self.ghost = None;
+        self.daily = None;
+        self.custom_text = None;
+        self.restart();
+        self.save_config();
+    }
+
+    /// Types the text of a custom file; the result isn't saved
+    fn start_custom_text(&mut self, text: String) {
+        self.ghost = None;
+        self.daily = None;
+        self.custom_text = Some(text);
+        self.restart();
+    }
+
+    fn handle_palette_key(&mut self, key_event: KeyEvent) {
+        let ctrl = key_event.modifiers.contains(event::KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Esc if self.palette.prompt.is_some() => {
+                let entries = std::mem::take(&mut self.palette.entries);
+                self.palette.show(entries);
+            }
+            KeyCode::Esc => self.palette.close(),
+            KeyCode::Up => self.palette.select_previous(),
+            KeyCode::Down => self.palette.select_next(),
+            KeyCode::Char('p') if ctrl => self.palette.select_previous(),
+            KeyCode::Char('n') if ctrl => self.palette.select_next(),
+            KeyCode::Backspace => self.palette.backspace(),
+            KeyCode::Char(c) if !ctrl => self.palette.type_char(c),
+            KeyCode::Enter => match self.palette.prompt {
+                Some(prompt) => self.answer_prompt(prompt),
+                None => {
+                    if let Some(command) = self.palette.selected_command() {
+                        self.palette.close();
+                        self.run_command(command);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Carries out a command picked in the palette
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Action(action) => self.perform(action),
+            Command::Mode(Mode::Practice) => {
+                self.start_practice_level(practice::get_first_not_done());
+                self.save_config();
+            }
+            Command::Mode(mode) => self.switch_mode(mode),
+            Command::TestTime(time) => {
+                self.test_time = time;
+                self.switch_mode(Mode::Time);
+            }
+            Command::WordCount(words) => {
+                self.word_number = words;
+                self.switch_mode(Mode::Words);
+            }
+            Command::Language(language) => {
+                self.language = language;
+                self.apply_setting();
+            }
+            Command::Theme(scheme) => {
+                self.color_scheme = scheme;
+                self.save_config();
+            }
+            Command::PracticeLevel(level) => {
+                self.start_practice_level(level);
+                self.save_config();
+            }
+            Command::Punctuation => {
+                self.punctuation = !self.punctuation;
+                self.apply_setting();
+            }
+            Command::Numbers => {
+                self.numbers = !self.numbers;
+                self.apply_setting();
+            }
+            Command::BatchSize(size) => {
+                self.batch_size = size;
+                self.apply_setting();
+            }
+            Command::TopWords(top) => {
+                self.top_words = top;
+                self.apply_setting();
+            }
+            Command::Pace(pace) => {
+                self.pace = pace;
+                self.save_config();
+            }
+            Command::Preset(name) => self.apply_preset(Some(&name)),
+            Command::Profile(name) => {
+                if name != crate::profile::active() {
+                    self.save_config();
+                    self.switch_profile(&name);
+                }
+            }
+            Command::Export(format) => {
+                self.palette.open = true;
+                self.palette.ask(Prompt::Export(format));
+            }
+            Command::CustomFile => {
+                self.palette.open = true;
+                self.palette.ask(Prompt::CustomFile);
+            }
+        }
+    }
+
+    /// Uses the file name typed into the palette
+    fn answer_prompt(&mut self, prompt: Prompt) {
+        let path = paths::expand_home(self.palette.query.trim());
+        let outcome = match prompt {
+            Prompt::Export(format) => export::export_to_string(format, None)
+                .map_err(|e| format!("Export failed: {}", e))
+                .and_then(|data| std::fs::write(&path, data).map_err(|e| format!("Failed to write {}: {}", path.display(), e)))
+                .map(|()| format!("Results exported to {}", path.display())),
+            Prompt::CustomFile => utils::read_custom_text(&path).map(|text| {
+                self.start_custom_text(text);
+                format!("Typing {}", path.display())
+            }),
+        };
+        match outcome {
+            Ok(message) => {
+                self.palette.close();
+                self.palette.message = Some(message);
+            }
+            Err(message) => self.palette.message = Some(message),
+        }
+    }
+
+    /// Starts the test over with a new text, or the same one during a ghost race, daily challenge or custom text
+    fn restart(&mut self) {
+        if let Some(ghost) = &self.ghost {
+            self.reference = ghost.reference.clone();
+        } else if let Some(daily) = &self.daily {
+            self.reference = daily.text.clone();
+        } else if let Some(text) = &self.custom_text {
+            self.reference = text.clone();
+        } else {
+            self.reference = match self.mode {
+                Mode::Words => utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), usize::min(self.batch_size, self.word_number)),
+                Mode::Time => utils::get_reference(self.punctuation, self.numbers, &utils::read_first_n_words(self.top_words, self.language), self.batch_size),
+                Mode::Quote => utils::get_random_quote(),
+                Mode::Practice => practice::create_words(TYPING_LEVELS[self.selected_level].1, 50),
+                Mode::Wiki => utils::get_wiki_summary(),
+            };
+        }
+        self.reset_test();
+    }
+
+    /// Starts the test over on the text that is on screen
+    fn reset_test(&mut self) {
+        self.is_correct = vec![0; self.reference.chars().count()];
+        self.pressed_vec.clear();
+        self.pos1 = 0;
+        self.words_done = 0;
+        self.errors_this_second = 0.0;
+        self.start_time = None;
+        self.game_state = GameState::NotStarted;
+        self.speed_per_second.clear();
+        self.char_number = 0;
+        self.errors_per_second.clear();
+        self.correct_count = 0;
+        self.error_count = 0;
+    }
+
     fn save_config(&mut self) {
         self.app_config = AppConfig {
             punctuation: self.punctuation,
             numbers: self.numbers,
-            time_mode: self.time_mode,
-            word_mode: self.word_mode,
-            quote: self.quote,
-            practice_mode: self.practice_mode,
-            wiki_mode: self.wiki_mode,
+            mode: self.mode,
             batch_size: self.batch_size,
             test_time: self.test_time,
             selected_level: self.selected_level,
@@ -922,6 +1478,8 @@ impl App {
             color_scheme: self.color_scheme,
             word_number: self.word_number,
             top_words: self.top_words,
+            pace: self.pace,
+            ..self.app_config.clone()
         };
         
         let _ = self.app_config.save();
@@ -945,44 +1503,65 @@ impl App {
                 0.0
             };
             
-            // Determine test type
-            let test_type = if self.practice_mode {
-                crate::leaderboard::TestType::Practice(self.selected_level + 1)
-            } else if self.time_mode {
-                crate::leaderboard::TestType::Time(self.test_time as u32)
-            } else if self.word_mode {
-                crate::leaderboard::TestType::Word(self.word_number)
-            } else if self.quote {
-                crate::leaderboard::TestType::Quote
-            } else if self.wiki_mode {
-                crate::leaderboard::TestType::Wiki
-            } else {
-                crate::leaderboard::TestType::Time(30) // Default fallback
-            };
+            let test_type = self.test_type();
             
             // Create leaderboard entry
             let entry = crate::leaderboard::LeaderboardEntry {
                 wpm: wpm as f64,
                 accuracy,
                 test_type,
-                test_mode: if self.practice_mode { "practice".to_string() }
-                          else if self.time_mode { "time".to_string() }
-                          else if self.word_mode { "word".to_string() }
-                          else if self.quote { "quote".to_string() }
-                          else if self.wiki_mode { "wiki".to_string() }
-                          else { "time".to_string() },
+                test_mode: if self.active_daily().is_some() { "daily".to_string() } else {
+                    match self.mode {
+                        Mode::Practice => "practice",
+                        Mode::Time => "time",
+                        Mode::Words => "word",
+                        Mode::Quote => "quote",
+                        Mode::Wiki => "wiki",
+                    }.to_string()
+                },
                 word_count: self.words_done, // Actual completed words
                 test_duration: elapsed,
                 timestamp: chrono::Local::now().to_rfc3339(),
                 language: self.language,
+                flag: anticheat::analyze(&self.keystroke_times),
             };
+            self.last_flag = entry.flag.clone();
+            self.pace_verdict = self.pace_wpm.map(|target| pace::verdict(entry.wpm, target));
+
+            // Keep the run for ghost races; runs spread over several batches can't be replayed
+            if entry.flag.is_none() && self.earlier_batch_words == 0 {
+                if let Some(run) = GhostRun::new(ghost::player_name(), entry.wpm, self.language, &self.reference, &self.key_log) {
+                    ghost::record(run);
+                }
+            }
+            // A rematch on an old text isn't a result of the selected mode
+            if self.active_ghost().is_some() {
+                return;
+            }
+            // Custom texts differ every time, so they aren't saved
+            if self.active_custom().is_some() {
+                return;
+            }
+            // Only the first daily attempt of the day is scored
+            if let Some(date) = self.active_daily().map(|daily| daily.date) {
+                let scored = daily::submit(&entry).unwrap_or_else(|e| {
+                    eprintln!("Failed to save the daily result: {}", e);
+                    false
+                });
+                self.daily_lines = daily::summary(date);
+                if !scored {
+                    self.daily_lines[0] = format!("Practice run, not scored · {}", self.daily_lines[0]);
+                }
+                self.leaderboard.reload();
+                return;
+            }
             
             // Save entry
             if let Err(e) = crate::leaderboard::save_entry(&entry) {
                 // Enhanced error logging with specific error types
                 match e {
                     crate::leaderboard::LeaderboardError::ValidationError(ref validation_err) => {
-                        eprintln!("Invalid leaderboard entry data: {:?}", validation_err);
+                        eprintln!("Invalid leaderboard entry data: {}", validation_err);
                         eprintln!("Entry not saved due to validation failure");
                     }
                     crate::leaderboard::LeaderboardError::IoError(ref io_err) => {
@@ -1001,12 +1580,25 @@ impl App {
                         eprintln!("Failed to acquire leaderboard file lock: {}", lock_err);
                         eprintln!("Check file permissions and system resources");
                     }
+                    crate::leaderboard::LeaderboardError::DatabaseError(ref db_err) => {
+                        eprintln!("Failed to save leaderboard entry to the database: {}", db_err);
+                        eprintln!("Try 'typeman migrate --to json' if the database is damaged");
+                    }
                 }
             }
+
+            // Share the result with the team, if one is set up
+            if let Err(e) = crate::team::publish_configured(&entry) {
+                eprintln!("Failed to update the team leaderboard: {}", e);
+            }
+            #[cfg(feature = "sync")]
+            if let Err(e) = crate::sync::submit_configured(&entry) {
+                eprintln!("Failed to queue the result for the leaderboard server: {}", e);
+            }
             
             // Always update in-memory entries to ensure synchronization
             // This ensures the leaderboard immediately reflects the latest game results
-            self.leaderboard.entries = crate::leaderboard::load_entries().unwrap_or_default();
+            self.leaderboard.reload();
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+use std::io;
+use std::time::Duration;
+
+use crossterm::event::{self, Event as CEvent, KeyCode, KeyEventKind};
+use ratatui::{DefaultTerminal, Frame, prelude::*, widgets::*};
+
+use crate::color_scheme::ColorScheme;
+use crate::config::AppConfig;
+use crate::custom_colors::MyColor;
+use crate::hotseat::{self, HotseatGame, HotseatPhase};
+
+/// Runs a hot-seat game in the terminal until everyone has typed or Esc is pressed
+pub fn run(mut game: HotseatGame) -> io::Result<()> {
+    let color_scheme = AppConfig::load().color_scheme;
+    let mut terminal = ratatui::init();
+    let result = run_loop(&mut terminal, &mut game, color_scheme);
+    ratatui::restore();
+    result
+}
+
+fn run_loop(terminal: &mut DefaultTerminal, game: &mut HotseatGame, color_scheme: ColorScheme) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| render(frame, game, color_scheme))?;
+
+        if !event::poll(Duration::from_millis(16))? {
+            continue;
+        }
+        let CEvent::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match (key.code, game.phase) {
+            (KeyCode::Esc, _) => return Ok(()),
+            (KeyCode::Enter, HotseatPhase::Handoff) => game.begin_turn(),
+            (KeyCode::Enter, HotseatPhase::Done) => return Ok(()),
+            (KeyCode::Backspace, HotseatPhase::Typing) => game.backspace(),
+            (KeyCode::Char(c), HotseatPhase::Typing) => game.type_char(c),
+            _ => {}
+        }
+    }
+}
+
+fn render(frame: &mut Frame, game: &HotseatGame, color_scheme: ColorScheme) {
+    let area = frame.area();
+    frame.render_widget(Block::default().style(Style::default().bg(color_scheme.bg_color())), area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([Constraint::Length(2), Constraint::Min(5), Constraint::Length(1)])
+        .split(area);
+
+    let title = Line::from(vec![
+        Span::styled("Type", Style::default().fg(color_scheme.main_color())),
+        Span::styled("Man", Style::default().fg(color_scheme.text_color())),
+        Span::styled(" hot-seat", Style::default().fg(color_scheme.ref_color())),
+    ]);
+    frame.render_widget(Paragraph::new(title), chunks[0]);
+
+    let instructions = match game.phase {
+        HotseatPhase::Handoff => "↵ - start your turn, Esc - quit",
+        HotseatPhase::Typing => "Esc - quit",
+        HotseatPhase::Done => "↵ or Esc - quit",
+    };
+    match game.phase {
+        HotseatPhase::Handoff => render_handoff(frame, chunks[1], game, color_scheme),
+        HotseatPhase::Typing => render_text(frame, chunks[1], game, color_scheme),
+        HotseatPhase::Done => render_results(frame, chunks[1], game, color_scheme),
+    }
+
+    frame.render_widget(
+        Paragraph::new(format!("  {}", instructions)).style(Style::default().fg(color_scheme.ref_color())),
+        chunks[2],
+    );
+}
+
+fn render_handoff(frame: &mut Frame, area: Rect, game: &HotseatGame, color_scheme: ColorScheme) {
+    let name = game.current_player().unwrap_or_default();
+    let mut lines = vec![
+        Line::styled(
+            format!("Turn {} of {}", game.turn + 1, game.players.len()),
+            Style::default().fg(color_scheme.ref_color()),
+        ),
+        Line::from(""),
+        Line::styled(
+            format!("Pass the keyboard to {}", name),
+            Style::default().fg(color_scheme.main_color()).add_modifier(Modifier::BOLD),
+        ),
+        Line::styled(
+            "Press Enter when ready - the clock starts with your first key",
+            Style::default().fg(color_scheme.text_color()),
+        ),
+    ];
+    // Times only, so the next player isn't chasing a number before typing
+    if !game.results.is_empty() {
+        lines.push(Line::from(""));
+        lines.extend(game.results.iter().map(|r| {
+            Line::styled(format!("{} done in {:.1}s", r.name, r.time), Style::default().fg(color_scheme.ref_color()))
+        }));
+    }
+
+    let height = (lines.len() as u16).min(area.height);
+    let rect = Rect { y: area.y + (area.height - height) / 2, height, ..area };
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), rect);
+}
+
+fn render_text(frame: &mut Frame, area: Rect, game: &HotseatGame, color_scheme: ColorScheme) {
+    let typing = game.typing.as_ref();
+    let cursor = typing.map(|t| t.typed.len()).unwrap_or(0);
+    let spans: Vec<Span> = game
+        .text
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let style = match typing.and_then(|t| t.is_correct(i)) {
+                Some(true) => Style::default().fg(color_scheme.correct_color()),
+                Some(false) => Style::default().fg(color_scheme.incorrect_color()).add_modifier(Modifier::CROSSED_OUT),
+                None if i == cursor => Style::default().fg(color_scheme.ref_color()).add_modifier(Modifier::UNDERLINED),
+                None => Style::default().fg(color_scheme.ref_color()),
+            };
+            Span::styled(c.to_string(), style)
+        })
+        .collect();
+
+    let status = match typing {
+        Some(t) => format!("{}  {:.0} wpm  {:.0}s", game.current_player().unwrap_or_default(), t.wpm(), t.elapsed().as_secs_f64()),
+        None => format!("{}  start typing", game.current_player().unwrap_or_default()),
+    };
+    let lines = vec![
+        Line::styled(status, Style::default().fg(color_scheme.main_color())),
+        Line::from(""),
+        Line::from(spans),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+}
+
+fn render_results(frame: &mut Frame, area: Rect, game: &HotseatGame, color_scheme: ColorScheme) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(game.results.len() as u16 + 2), Constraint::Min(8)])
+        .split(area);
+
+    let header = Row::new(["Place", "Player", "WPM", "Acc%", "Time"])
+        .style(Style::default().fg(color_scheme.main_color()));
+    let rows: Vec<Row> = game
+        .standings()
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let (red, green, blue) = hotseat::player_color(player_index(game, &r.name));
+            Row::new(vec![
+                (i + 1).to_string(),
+                r.name.clone(),
+                format!("{:.1}", r.wpm),
+                format!("{:.1}", r.accuracy),
+                format!("{:.1}s", r.time),
+            ])
+            .style(Style::default().fg(Color::from(MyColor::new(red, green, blue, 255))))
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(6),
+        Constraint::Length(16),
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Length(7),
+    ];
+    frame.render_widget(Table::new(rows, widths).header(header), chunks[0]);
+    let curves = speed_curves(game);
+    frame.render_widget(speed_chart(game, &curves, color_scheme), chunks[1]);
+}
+
+/// Index of a player in turn order, which picks their color
+fn player_index(game: &HotseatGame, name: &str) -> usize {
+    game.players.iter().position(|p| p == name).unwrap_or(0)
+}
+
+/// WPM per second of every finished turn, with the player's index
+fn speed_curves(game: &HotseatGame) -> Vec<(usize, Vec<(f64, f64)>)> {
+    game.results
+        .iter()
+        .map(|r| {
+            let points = r
+                .speed_per_second
+                .iter()
+                .enumerate()
+                .map(|(i, cpm)| (i as f64 + 1.0, cpm / 5.0))
+                .collect();
+            (player_index(game, &r.name), points)
+        })
+        .collect()
+}
+
+/// Everyone's speed on one chart, a line per player
+fn speed_chart<'a>(game: &HotseatGame, curves: &'a [(usize, Vec<(f64, f64)>)], color_scheme: ColorScheme) -> Chart<'a> {
+    let ref_color = color_scheme.ref_color();
+    let max_time = curves.iter().map(|(_, p)| p.len()).max().unwrap_or(1).max(1) as f64;
+    let max_wpm = curves
+        .iter()
+        .flat_map(|(_, p)| p.iter().map(|(_, wpm)| *wpm))
+        .fold(60.0_f64, f64::max);
+
+    let datasets = curves
+        .iter()
+        .map(|(index, points)| {
+            let (red, green, blue) = hotseat::player_color(*index);
+            Dataset::default()
+                .name(game.players[*index].clone())
+                .graph_type(GraphType::Line)
+                .marker(symbols::Marker::Braille)
+                .style(Style::default().fg(Color::from(MyColor::new(red, green, blue, 255))))
+                .data(points)
+        })
+        .collect();
+
+    Chart::new(datasets)
+        .style(Style::default().bg(color_scheme.bg_color()))
+        .legend_position(Some(LegendPosition::TopRight))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 1)))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(ref_color))
+                .bounds([1.0, max_time])
+                .labels(vec![Span::from("1s"), Span::from(format!("{:.0}s", max_time))]),
+        )
+        .y_axis(
+            Axis::default()
+                .title("wpm")
+                .style(Style::default().fg(ref_color))
+                .bounds([0.0, max_wpm * 1.1])
+                .labels(vec![Span::from("0"), Span::from(format!("{:.0}", max_wpm))]),
+        )
+}
@@ -1,12 +1,33 @@
 use std::io;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::execute;
 use crate ::ui::tui::app::App;
+use crate::ghost::GhostRun;
 
 
 pub fn main() -> io::Result<()> {
+    run_app(App::new())
+}
+/// Runs the TUI racing an earlier run on its text
+pub fn ghost_race(run: GhostRun) -> io::Result<()> {
+    run_app(App::with_ghost(run))
+}
+/// Runs the TUI on today's daily challenge
+pub fn daily() -> io::Result<()> {
+    run_app(App::with_daily())
+}
+
+/// Runs `app` with mouse capture on, releasing it before the terminal is restored
+fn run_app(mut app: App) -> io::Result<()> {
     let mut terminal = ratatui::init();
-    let mut app = App::new();
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+        hook(info);
+    }));
 
-    let app_result = app.run(&mut terminal);
+    let app_result = execute!(io::stdout(), EnableMouseCapture).and_then(|_| app.run(&mut terminal));
+    let release_result = execute!(io::stdout(), DisableMouseCapture);
     ratatui::restore();
-    app_result
-}
\ No newline at end of file
+    app_result.and(release_result)
+}
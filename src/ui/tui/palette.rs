@@ -0,0 +1,284 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use crate::batch_size_selection::BatchSizeSelection;
+use crate::color_scheme::ColorScheme;
+use crate::config::{AppConfig, Mode};
+use crate::export::ExportFormat;
+use crate::keymap::{Action, Keymap};
+use crate::language::Language;
+use crate::pace::Pace;
+use crate::practice::TYPING_LEVELS;
+use crate::profile;
+use crate::time_selection::TimeSelection;
+use crate::top_words_selection::TopWordsSelection;
+use crate::ui::tui::popup::centered_rect;
+use crate::utils;
+use crate::word_number_selection::WordNumberSelection;
+
+/// Something the palette can do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Action(Action),
+    Mode(Mode),
+    TestTime(f32),
+    WordCount(usize),
+    Language(Language),
+    Theme(ColorScheme),
+    PracticeLevel(usize),
+    Punctuation,
+    Numbers,
+    BatchSize(usize),
+    TopWords(usize),
+    Pace(Pace),
+    Preset(String),
+    Profile(String),
+    /// Asks for the file to write, then exports the results
+    Export(ExportFormat),
+    /// Asks for the file, then types its text
+    CustomFile,
+}
+
+pub struct Entry {
+    pub label: String,
+    /// Key binding of the action, if it has one
+    pub hint: Option<String>,
+    pub command: Command,
+}
+
+/// A file name the palette is asking for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prompt {
+    Export(ExportFormat),
+    CustomFile,
+}
+
+impl Prompt {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Prompt::Export(ExportFormat::Csv) => "Export results as CSV to",
+            Prompt::Export(ExportFormat::Json) => "Export results as JSON to",
+            Prompt::CustomFile => "Type the text of",
+        }
+    }
+
+    /// What the input starts with
+    pub fn default_input(&self) -> String {
+        match self {
+            Prompt::Export(ExportFormat::Csv) => "typeman-results.csv".to_string(),
+            Prompt::Export(ExportFormat::Json) => "typeman-results.json".to_string(),
+            Prompt::CustomFile => String::new(),
+        }
+    }
+}
+
+/// Fuzzy-searchable list of every action and setting, opened with Ctrl+P or `:`
+#[derive(Default)]
+pub struct Palette {
+    pub open: bool,
+    pub query: String,
+    /// Index into the matches of the query
+    pub selected: usize,
+    pub entries: Vec<Entry>,
+    pub prompt: Option<Prompt>,
+    /// Outcome of the last command, shown until the next key
+    pub message: Option<String>,
+}
+
+impl Palette {
+    pub fn show(&mut self, entries: Vec<Entry>) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+        self.entries = entries;
+        self.prompt = None;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.prompt = None;
+    }
+
+    pub fn ask(&mut self, prompt: Prompt) {
+        self.prompt = Some(prompt);
+        self.query = prompt.default_input();
+    }
+
+    /// Entries matching the query, best first
+    pub fn matches(&self) -> Vec<&Entry> {
+        let mut scored: Vec<(i32, &Entry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| utils::fuzzy_score(&self.query, &entry.label).map(|score| (score, entry)))
+            .collect();
+        // Stable, so equal scores keep the order of `entries`
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    pub fn selected_command(&self) -> Option<Command> {
+        self.matches().get(self.selected).map(|entry| entry.command.clone())
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.matches().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub fn backspace(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+}
+
+/// Everything the palette lists, actions first
+pub fn entries(config: &AppConfig) -> Vec<Entry> {
+    let keymap: &Keymap = &config.keymap;
+    let entry = |label: String, command: Command| Entry { label, hint: None, command };
+    let mut entries: Vec<Entry> = Action::all()
+        .iter()
+        // The text size is the terminal's to set
+        .filter(|&&action| !matches!(action, Action::CommandPalette | Action::TextLarger | Action::TextSmaller))
+        .map(|&action| Entry {
+            label: capitalize(&action.to_string()),
+            hint: (!keymap.bindings(action).is_empty()).then(|| keymap.hint(action)),
+            command: Command::Action(action),
+        })
+        .collect();
+    entries.push(entry("Practice mode".to_string(), Command::Mode(Mode::Practice)));
+    entries.push(entry("Toggle punctuation".to_string(), Command::Punctuation));
+    entries.push(entry("Toggle numbers".to_string(), Command::Numbers));
+    entries.extend(
+        TimeSelection::all()
+            .iter()
+            .map(|time| entry(format!("Time: {}", time), Command::TestTime(time.to_seconds() as f32))),
+    );
+    entries.extend(
+        WordNumberSelection::all()
+            .iter()
+            .map(|words| entry(format!("Words: {}", words.to_words()), Command::WordCount(words.to_words() as usize))),
+    );
+    entries.extend(Language::all().iter().map(|&language| entry(format!("Language: {}", language), Command::Language(language))));
+    entries.extend(ColorScheme::all().into_iter().map(|scheme| entry(format!("Theme: {}", scheme.name()), Command::Theme(scheme))));
+    entries.extend(
+        TYPING_LEVELS
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| entry(format!("Practice level {}: {}", i + 1, name), Command::PracticeLevel(i))),
+    );
+    entries.extend(
+        BatchSizeSelection::all()
+            .iter()
+            .map(|size| entry(format!("Batch size: {}", size.to_words()), Command::BatchSize(size.to_words() as usize))),
+    );
+    entries.extend(
+        TopWordsSelection::all()
+            .iter()
+            .map(|top| entry(format!("Top words: {}", top.to_words()), Command::TopWords(top.to_words() as usize))),
+    );
+    entries.extend(Pace::all().into_iter().map(|pace| entry(format!("Pace caret: {}", pace), Command::Pace(pace))));
+    entries.extend(config.presets.iter().map(|preset| entry(format!("Preset: {}", preset.name), Command::Preset(preset.name.clone()))));
+    entries.extend(profile::list().into_iter().map(|name| entry(format!("Profile: {}", name), Command::Profile(name))));
+    entries.push(entry("Export results: CSV".to_string(), Command::Export(ExportFormat::Csv)));
+    entries.push(entry("Export results: JSON".to_string(), Command::Export(ExportFormat::Json)));
+    entries.push(entry("Load custom file".to_string(), Command::CustomFile));
+    entries
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+pub fn render_palette(frame: &mut Frame, palette: &Palette, area: Rect, color_scheme: ColorScheme) {
+    let bg_color = color_scheme.bg_color();
+    let main_color = color_scheme.main_color();
+    let ref_color = color_scheme.ref_color();
+    let border_color = color_scheme.border_color();
+
+    let popup_area = centered_rect(50, 60, area);
+    frame.render_widget(Clear, popup_area);
+    let title = match palette.prompt {
+        Some(prompt) => format!(" {} ", prompt.title()),
+        None => " Command palette ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border_color))
+        .title_style(Style::default().fg(main_color))
+        .style(Style::default().bg(bg_color));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(inner);
+    let input = Line::from(vec![
+        Span::styled("> ", Style::default().fg(main_color)),
+        Span::styled(format!("{}\u{2502}", palette.query), Style::default().fg(color_scheme.text_color())),
+    ]);
+    frame.render_widget(Paragraph::new(input), chunks[0]);
+
+    if palette.prompt.is_some() {
+        let mut lines = vec![Line::from(Span::styled("↵ - confirm, Esc - back", Style::default().fg(ref_color)))];
+        if let Some(message) = &palette.message {
+            lines.insert(0, Line::from(Span::styled(message.clone(), Style::default().fg(color_scheme.incorrect_color()))));
+        }
+        frame.render_widget(Paragraph::new(lines), chunks[1]);
+        return;
+    }
+
+    let matches = palette.matches();
+    let height = chunks[1].height as usize;
+    // Keep the selection in view
+    let skip = (palette.selected + 1).saturating_sub(height);
+    let width = chunks[1].width as usize;
+    let items: Vec<ListItem> = matches
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(i, entry)| {
+            let hint = entry.hint.clone().unwrap_or_default();
+            let padding = width.saturating_sub(entry.label.chars().count() + hint.chars().count() + 2);
+            let text = format!(" {}{}{} ", entry.label, " ".repeat(padding), hint);
+            let style = if i == palette.selected {
+                Style::default().fg(bg_color).bg(main_color)
+            } else {
+                Style::default().fg(ref_color)
+            };
+            ListItem::new(text).style(style)
+        })
+        .collect();
+    if items.is_empty() {
+        frame.render_widget(Paragraph::new(Span::styled(" no matches", Style::default().fg(ref_color))), chunks[1]);
+    } else {
+        frame.render_widget(List::new(items), chunks[1]);
+    }
+}
+
+/// The message of the last command, on the top line once the palette is closed
+pub fn render_message(frame: &mut Frame, message: &str, area: Rect, color_scheme: ColorScheme) {
+    let line = Rect { height: 1.min(area.height), ..area };
+    frame.render_widget(Clear, line);
+    frame.render_widget(
+        Paragraph::new(format!(" {}", message)).style(Style::default().fg(color_scheme.main_color()).bg(color_scheme.bg_color())),
+        line,
+    );
+}
@@ -1,12 +1,15 @@
 use ratatui::{
     style::Style,
     widgets::{Block, Borders, List, ListItem},
-    layout::{Rect, Layout, Constraint, Direction},
+    layout::{Rect, Layout, Constraint, Direction, Position},
     Frame,
 };
 
 use crate::color_scheme::ColorScheme;
+use crate::config::AppConfig;
 use crate::language::Language;
+use crate::pace::Pace;
+use crate::profile;
 use crate::time_selection::TimeSelection;
 use crate::settings::Settings;
 use crate::ui::tui::app::App;
@@ -19,6 +22,9 @@ pub enum PopupContent {
     Settings,
     BatchSizeSelection,
     TopWordsSelection,
+    Profile,
+    Pace,
+    Preset,
 }
 
 pub struct PopupState {
@@ -34,12 +40,16 @@ pub struct PopupStates {
     pub settings: PopupState,
     pub batch_size_selection: PopupState,
     pub top_words_selection: PopupState,
+    pub profile: PopupState,
+    pub pace: PopupState,
+    pub preset: PopupState,
 }
 
 pub trait PopupData {
     fn title(&self) -> &'static str;
     fn items(&self) -> Vec<String>;
     fn selected_index<'a>(&self, app: &'a App) -> &'a usize;
+    fn selected_index_mut<'a>(&self, app: &'a mut App) -> &'a mut usize;
 }
 
 impl PopupData for PopupContent {
@@ -52,6 +62,9 @@ impl PopupData for PopupContent {
             PopupContent::Settings => "Select Setting",
             PopupContent::BatchSizeSelection => "Select Batch Size",
             PopupContent::TopWordsSelection => "Select Top Words",
+            PopupContent::Profile => "Select Profile",
+            PopupContent::Pace => "Select Pace Caret",
+            PopupContent::Preset => "Select Preset",
         }
     }
 
@@ -64,6 +77,9 @@ impl PopupData for PopupContent {
             PopupContent::Settings => Settings::all().iter().map(|x| x.to_string()).collect(),
             PopupContent::BatchSizeSelection => vec!["10".to_string(), "25".to_string(), "50".to_string(), "100".to_string(), "200".to_string()],
             PopupContent::TopWordsSelection => vec!["100".to_string(), "200".to_string(), "500".to_string(), "1000".to_string()],
+            PopupContent::Profile => profile::list(),
+            PopupContent::Pace => Pace::all().iter().map(|x| x.to_string()).collect(),
+            PopupContent::Preset => AppConfig::load().presets.iter().map(|x| x.to_string()).collect(),
         }
     }
 
@@ -76,11 +92,48 @@ impl PopupData for PopupContent {
             PopupContent::Settings => &app.popup_states.settings.selected,
             PopupContent::BatchSizeSelection => &app.popup_states.batch_size_selection.selected,
             PopupContent::TopWordsSelection => &app.popup_states.top_words_selection.selected,
+            PopupContent::Profile => &app.popup_states.profile.selected,
+            PopupContent::Pace => &app.popup_states.pace.selected,
+            PopupContent::Preset => &app.popup_states.preset.selected,
+        }
+    }
+
+    fn selected_index_mut<'a>(&self, app: &'a mut App) -> &'a mut usize {
+        match self {
+            PopupContent::Language => &mut app.popup_states.language.selected,
+            PopupContent::ColorScheme => &mut app.popup_states.color_scheme.selected,
+            PopupContent::TimeSelection => &mut app.popup_states.time_selection.selected,
+            PopupContent::WordNumberSelection => &mut app.popup_states.word_number_selection.selected,
+            PopupContent::Settings => &mut app.popup_states.settings.selected,
+            PopupContent::BatchSizeSelection => &mut app.popup_states.batch_size_selection.selected,
+            PopupContent::TopWordsSelection => &mut app.popup_states.top_words_selection.selected,
+            PopupContent::Profile => &mut app.popup_states.profile.selected,
+            PopupContent::Pace => &mut app.popup_states.pace.selected,
+            PopupContent::Preset => &mut app.popup_states.preset.selected,
         }
     }
 }
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+/// The popup on top; settings stays open under the popup it opened
+pub fn open_popup(app: &App) -> Option<PopupContent> {
+    let states = &app.popup_states;
+    [
+        (states.language.open, PopupContent::Language),
+        (states.color_scheme.open, PopupContent::ColorScheme),
+        (states.time_selection.open, PopupContent::TimeSelection),
+        (states.word_number_selection.open, PopupContent::WordNumberSelection),
+        (states.batch_size_selection.open, PopupContent::BatchSizeSelection),
+        (states.top_words_selection.open, PopupContent::TopWordsSelection),
+        (states.profile.open, PopupContent::Profile),
+        (states.pace.open, PopupContent::Pace),
+        (states.preset.open, PopupContent::Preset),
+        (states.settings.open, PopupContent::Settings),
+    ]
+    .into_iter()
+    .find_map(|(open, content)| open.then_some(content))
+}
+
+pub(crate) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let safe_percent_x = percent_x.min(95);
     let safe_percent_y = percent_y.min(95);
 
@@ -112,15 +165,38 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+fn popup_area(app: &App, area: Rect) -> Rect {
+    let percent_y = if area.height < 25 && app.popup_states.color_scheme.open { 80 } else if area.height < 40 && app.popup_states.color_scheme.open { 50 } else { 30 };
+    centered_rect(30, percent_y, area)
+}
+
+/// Where a click at `column`, `row` landed in the popup on top
+pub enum PopupClick {
+    Item(usize),
+    Inside,
+    Outside,
+}
+
+pub fn popup_click(app: &App, area: Rect, content: &PopupContent, column: u16, row: u16) -> PopupClick {
+    let popup_area = popup_area(app, area);
+    if !popup_area.contains(Position::new(column, row)) {
+        return PopupClick::Outside;
+    }
+    // Items start under the top border
+    let inner_bottom = popup_area.bottom().saturating_sub(1);
+    match (row > popup_area.y && row < inner_bottom).then(|| (row - popup_area.y - 1) as usize) {
+        Some(index) if index < content.items().len() => PopupClick::Item(index),
+        _ => PopupClick::Inside,
+    }
+}
+
 pub fn render_popup(frame: &mut Frame, app: &App, area: Rect, color_scheme: ColorScheme, content: PopupContent) {
     let bg_color = color_scheme.bg_color();
     let main_color = color_scheme.main_color();
     let ref_color = color_scheme.ref_color();
     let border_color = color_scheme.border_color();
 
-    let percent_y = if area.height < 25 && app.popup_states.color_scheme.open { 80 } else if area.height < 40 && app.popup_states.color_scheme.open { 50 } else { 30 };
-
-    let popup_area = centered_rect(30, percent_y, area);
+    let popup_area = popup_area(app, area);
     frame.render_widget(ratatui::widgets::Clear, popup_area);
 
     let items: Vec<ListItem> = content
@@ -0,0 +1,235 @@
+use std::io;
+use std::time::Duration;
+
+use crossterm::event::{self, Event as CEvent, KeyCode, KeyEventKind};
+use ratatui::{DefaultTerminal, Frame, prelude::*, widgets::*};
+
+use crate::color_scheme::ColorScheme;
+use crate::config::AppConfig;
+use crate::race::{RacePhase, RaceSession};
+
+/// Runs a race in the terminal until the player leaves
+pub fn run(mut session: RaceSession) -> io::Result<()> {
+    let color_scheme = AppConfig::load().color_scheme;
+    let mut terminal = ratatui::init();
+    let result = run_loop(&mut terminal, &mut session, color_scheme);
+    ratatui::restore();
+    if let Some(message) = &session.message {
+        println!("{}", message);
+    }
+    result
+}
+
+fn run_loop(terminal: &mut DefaultTerminal, session: &mut RaceSession, color_scheme: ColorScheme) -> io::Result<()> {
+    loop {
+        session.tick();
+        terminal.draw(|frame| render(frame, session, color_scheme))?;
+
+        if !event::poll(Duration::from_millis(16))? {
+            continue;
+        }
+        let CEvent::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match (key.code, &session.phase) {
+            (KeyCode::Esc, _) => return Ok(()),
+            (KeyCode::Enter, RacePhase::Lobby) => session.start(),
+            (KeyCode::Enter, RacePhase::Done | RacePhase::Disconnected(_)) => return Ok(()),
+            (KeyCode::Backspace, _) => session.backspace(),
+            (KeyCode::Char(c), _) => session.type_char(c),
+            _ => {}
+        }
+    }
+}
+
+fn render(frame: &mut Frame, session: &RaceSession, color_scheme: ColorScheme) {
+    let area = frame.area();
+    frame.render_widget(Block::default().style(Style::default().bg(color_scheme.bg_color())), area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Min(5),
+            Constraint::Length(session.progress.len().max(session.players.len()) as u16 + 2),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    let title = Line::from(vec![
+        Span::styled("Type", Style::default().fg(color_scheme.main_color())),
+        Span::styled("Man", Style::default().fg(color_scheme.text_color())),
+        Span::styled(" race", Style::default().fg(color_scheme.ref_color())),
+    ]);
+    frame.render_widget(Paragraph::new(title), chunks[0]);
+
+    let instructions = match session.phase {
+        RacePhase::Lobby if session.is_host() => "↵ - start the race, Esc - quit",
+        RacePhase::Done | RacePhase::Disconnected(_) => "↵ or Esc - quit",
+        _ => "Esc - quit",
+    };
+
+    match &session.phase {
+        RacePhase::Lobby => render_lobby(frame, chunks[1], session, color_scheme),
+        RacePhase::Countdown(_) => {
+            let left = session.countdown_left().unwrap_or_default();
+            let text = format!("Starting in {}...", left.as_secs() + 1);
+            render_message(frame, chunks[1], &text, color_scheme.main_color());
+        }
+        RacePhase::Racing | RacePhase::Waiting => render_text(frame, chunks[1], session, color_scheme),
+        RacePhase::Done => render_standings(frame, chunks[1], session, color_scheme),
+        RacePhase::Disconnected(reason) => {
+            render_message(frame, chunks[1], &format!("Race over: {}", reason), color_scheme.incorrect_color());
+        }
+    }
+
+    if !matches!(session.phase, RacePhase::Lobby | RacePhase::Done) {
+        render_progress(frame, chunks[2], session, color_scheme);
+    }
+
+    frame.render_widget(
+        Paragraph::new(format!("  {}", instructions)).style(Style::default().fg(color_scheme.ref_color())),
+        chunks[3],
+    );
+}
+
+fn render_message(frame: &mut Frame, area: Rect, text: &str, color: Color) {
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(color))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_lobby(frame: &mut Frame, area: Rect, session: &RaceSession, color_scheme: ColorScheme) {
+    let mut lines = vec![Line::styled(
+        format!("Players ({}):", session.players.len()),
+        Style::default().fg(color_scheme.main_color()),
+    )];
+    lines.extend(
+        session
+            .players
+            .iter()
+            .map(|name| Line::styled(format!("  {}", name), Style::default().fg(color_scheme.text_color()))),
+    );
+    lines.push(Line::from(""));
+    let hint = match &session.host {
+        Some(host) => format!(
+            "Others can join with 'typeman race join <your address>:{}'. Press Enter when everyone is in.",
+            host.local_addr().port()
+        ),
+        None => "Waiting for the host to start the race...".to_string(),
+    };
+    lines.push(Line::styled(hint, Style::default().fg(color_scheme.ref_color())));
+
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+}
+
+fn render_text(frame: &mut Frame, area: Rect, session: &RaceSession, color_scheme: ColorScheme) {
+    let Some(typing) = &session.typing else {
+        return;
+    };
+    let cursor = typing.typed.len();
+    let spans: Vec<Span> = typing
+        .reference
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let style = match typing.is_correct(i) {
+                Some(true) => Style::default().fg(color_scheme.correct_color()),
+                Some(false) => Style::default().fg(color_scheme.incorrect_color()).add_modifier(Modifier::CROSSED_OUT),
+                None if i == cursor => Style::default().fg(color_scheme.ref_color()).add_modifier(Modifier::UNDERLINED),
+                None => Style::default().fg(color_scheme.ref_color()),
+            };
+            Span::styled(c.to_string(), style)
+        })
+        .collect();
+
+    let status = if session.phase == RacePhase::Waiting {
+        format!("Done in {:.1}s - waiting for the others", typing.elapsed().as_secs_f64())
+    } else {
+        format!("{:.0} wpm  {:.0}s", typing.wpm(), typing.elapsed().as_secs_f64())
+    };
+    let lines = vec![
+        Line::styled(status, Style::default().fg(color_scheme.main_color())),
+        Line::from(""),
+        Line::from(spans),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+}
+
+fn render_progress(frame: &mut Frame, area: Rect, session: &RaceSession, color_scheme: ColorScheme) {
+    let block = Block::default()
+        .title("Progress")
+        .borders(Borders::TOP)
+        .border_style(Style::default().fg(color_scheme.border_color()))
+        .title_style(Style::default().fg(color_scheme.main_color()));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    for (row, player) in session.progress.iter().enumerate().take(inner.height as usize) {
+        let color = if Some(player.id) == session.id() {
+            color_scheme.main_color()
+        } else {
+            color_scheme.dimmer_main()
+        };
+        let label = format!(
+            "{:<12} {:>4.0} wpm{}",
+            player.name,
+            player.wpm,
+            if player.finished { " ✓" } else { "" }
+        );
+        let gauge = LineGauge::default()
+            .label(label)
+            .ratio(player.progress.clamp(0.0, 1.0))
+            .filled_style(Style::default().fg(color))
+            .unfilled_style(Style::default().fg(color_scheme.border_color()));
+        let rect = Rect { x: inner.x, y: inner.y + row as u16, width: inner.width, height: 1 };
+        frame.render_widget(gauge, rect);
+    }
+}
+
+fn render_standings(frame: &mut Frame, area: Rect, session: &RaceSession, color_scheme: ColorScheme) {
+    let header = Row::new(["Place", "Player", "WPM", "Acc%", "Time"])
+        .style(Style::default().fg(color_scheme.main_color()));
+    let rows: Vec<Row> = session
+        .standings
+        .iter()
+        .map(|s| {
+            let style = if Some(s.id) == session.id() {
+                Style::default().fg(color_scheme.main_color()).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(color_scheme.text_color())
+            };
+            let (accuracy, time) = if s.finished {
+                (format!("{:.1}", s.accuracy), format!("{:.1}s", s.time))
+            } else {
+                ("-".to_string(), "DNF".to_string())
+            };
+            Row::new(vec![s.place.to_string(), s.name.clone(), format!("{:.1}", s.wpm), accuracy, time]).style(style)
+        })
+        .collect();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(area);
+    let widths = [
+        Constraint::Length(6),
+        Constraint::Length(16),
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Length(7),
+    ];
+    frame.render_widget(Table::new(rows, widths).header(header), chunks[0]);
+    if let Some(message) = &session.message {
+        frame.render_widget(
+            Paragraph::new(message.as_str()).style(Style::default().fg(color_scheme.ref_color())),
+            chunks[1],
+        );
+    }
+}
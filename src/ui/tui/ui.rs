@@ -3,14 +3,20 @@ use ratatui::widgets::canvas::Canvas;
 use std::collections::HashMap;
 use std::time::Duration;
 use crate::utils;
+use crate::achievements;
+use crate::ghost::{self, GhostRun};
 
 use crate::color_scheme::ColorScheme;
+use crate::config::Mode;
 use crate::custom_colors::MyColor;
+use crate::keymap::{Action, Keymap};
 use crate::language::Language;
+use crate::leaderboard::LeaderboardView;
 use crate::practice;
 use crate::practice::TYPING_LEVELS;
 use crate::ui::tui::app::{App, GameState};
-use crate::button_states::ButtonStates;
+use crate::button_states::{ButtonState, ButtonStates};
+use crate::ui::tui::palette::{render_message, render_palette};
 use crate::ui::tui::popup::*;
 
 fn render_instructions(
@@ -19,11 +25,17 @@ fn render_instructions(
     show: bool,
     practice_menu: bool,
     leaderboard_open: bool,
+    achievements_open: bool,
+    results: bool,
     color_scheme: ColorScheme,
+    keymap: &Keymap,
 ) {
+    let key = |action: Action| keymap.hint(action);
     let mut lines = Vec::new();
-    if leaderboard_open {
-        lines.push(Line::from("  ↑/↓ - navigate, Tab + L - close, Esc - exit"));
+    if achievements_open {
+        lines.push(Line::from(format!("  ↑/↓ - scroll, {} - close, Esc - back", key(Action::Achievements))));
+    } else if leaderboard_open {
+        lines.push(Line::from(format!("  ↑/↓ - navigate, ←/→ - switch tab, {} - close, Esc - exit", key(Action::Leaderboard))));
     } else if show {
         lines.push(Line::from(
             "  \u{2191} - enter config, \u{2190}/\u{2192} - toggle config, ↵ - apply config",
@@ -32,13 +44,32 @@ fn render_instructions(
         lines.push(Line::from("  ↑ or ↓ to navigate, ↵ to select"));
         lines.push(Line::from("  q - quit menu"));
     }
+    if results {
+        lines.push(Line::from(format!(
+            "  {} - restart, {} - same text, {} - race this run as a ghost",
+            key(Action::Restart),
+            key(Action::RepeatText),
+            key(Action::GhostRace)
+        )));
+    } else if !practice_menu && !leaderboard_open {
+        lines.push(Line::from(format!(
+            "  {} - restart, {} - next preset, {} or : - command palette",
+            key(Action::Restart),
+            key(Action::NextPreset),
+            key(Action::CommandPalette)
+        )));
+    }
     if !practice_menu && !leaderboard_open {
-        lines.push(Line::from("  Tab + Enter - restart"));
         lines.push(Line::from("  ⌄ - double Enter to view more options"));
-        lines.push(Line::from("  Tab + L - local leaderboard"));
+        lines.push(Line::from(format!(
+            "  {} - local leaderboard, {} - daily challenge, {} - achievements",
+            key(Action::Leaderboard),
+            key(Action::Daily),
+            key(Action::Achievements)
+        )));
     }
-    if !leaderboard_open {
-        lines.push(Line::from("  Esc - exit"));
+    if !leaderboard_open && !achievements_open {
+        lines.push(Line::from(format!("  {} - exit", key(Action::Quit))));
     }
 
     let text = Paragraph::new(lines)
@@ -51,12 +82,13 @@ fn render_instructions(
     frame.render_widget(text, area);
 }
 
-pub fn render_app(frame: &mut Frame, app: &App) {
-    let chunks = Layout::default()
+/// The screen of the current view above the instructions
+fn main_layout(app: &App, area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Min(0),
-            if app.leaderboard.open {
+            if app.leaderboard.open || app.achievements.open {
                 Constraint::Length(1)
             } else if app.game_state == GameState::Results {
                 Constraint::Length(2)
@@ -64,9 +96,15 @@ pub fn render_app(frame: &mut Frame, app: &App) {
                 Constraint::Length(4)
             },
         ])
-        .split(frame.area());
+        .split(area)
+}
 
-    if app.leaderboard.open {
+pub fn render_app(frame: &mut Frame, app: &App) {
+    let chunks = main_layout(app, frame.area());
+
+    if app.achievements.open {
+        render_achievements(frame, chunks[0], app, app.color_scheme);
+    } else if app.leaderboard.open {
         render_leaderboard(frame, chunks[0], app, app.color_scheme);
     } else if app.game_state == GameState::Results {
         render_results(frame, chunks[0], app, app.color_scheme);
@@ -78,27 +116,23 @@ pub fn render_app(frame: &mut Frame, app: &App) {
     render_instructions(
         frame,
         chunks[1],
-        app.game_state != GameState::Results && !app.practice_menu && !app.leaderboard.open,
+        app.game_state != GameState::Results && !app.practice_menu && !app.leaderboard.open && !app.achievements.open,
         app.practice_menu,
         app.leaderboard.open,
+        app.achievements.open,
+        app.game_state == GameState::Results,
         app.color_scheme,
+        &app.app_config.keymap,
     );
 
-    // Render language popup if open
-    if app.popup_states.language.open {
-        render_popup(frame, app, frame.area(), app.color_scheme, PopupContent::Language);
-    } else if app.popup_states.color_scheme.open {
-        render_popup(frame, app, frame.area(), app.color_scheme, PopupContent::ColorScheme);
-    } else if app.popup_states.time_selection.open {
-        render_popup(frame, app, frame.area(), app.color_scheme, PopupContent::TimeSelection);
-    } else if app.popup_states.word_number_selection.open {
-        render_popup(frame, app, frame.area(), app.color_scheme, PopupContent::WordNumberSelection);
-    } else if app.popup_states.batch_size_selection.open {
-        render_popup(frame, app, frame.area(), app.color_scheme, PopupContent::BatchSizeSelection);
-    } else if app.popup_states.top_words_selection.open {
-        render_popup(frame, app, frame.area(), app.color_scheme, PopupContent::TopWordsSelection);
-    } else if app.popup_states.settings.open {
-        render_popup(frame, app, frame.area(), app.color_scheme, PopupContent::Settings);
+    if let Some(content) = open_popup(app) {
+        render_popup(frame, app, frame.area(), app.color_scheme, content);
+    }
+
+    if app.palette.open {
+        render_palette(frame, &app.palette, frame.area(), app.color_scheme);
+    } else if let Some(message) = &app.palette.message {
+        render_message(frame, message, frame.area(), app.color_scheme);
     }
 }
 
@@ -114,6 +148,7 @@ fn render_practice_menu(frame: &mut Frame, area: Rect, app: &App, color_scheme:
     } else {
         0
     };
+    let completed = practice::completed_levels();
     for level in TYPING_LEVELS.iter().enumerate().skip(to_skip as usize) {
         let mut fg_color = color_scheme.text_color();
         let mut bg_color = color_scheme.bg_color();
@@ -124,12 +159,12 @@ fn render_practice_menu(frame: &mut Frame, area: Rect, app: &App, color_scheme:
         }
 
         let line =
-            if practice::check_if_completed(&format!("practice_results/level_{}.txt", level.0 + 1))
+            if completed[level.0]
             {
                 Line::from(vec![
                     Span::styled(
                         "✔ ",
-                        Style::default().fg(Color::Rgb(0, 255, 0)).bg(bg_color),
+                        Style::default().fg(Color::from(MyColor::new(0, 255, 0, 255))).bg(bg_color),
                     ),
                     if level.0 < 9 {
                         Span::styled(
@@ -147,7 +182,7 @@ fn render_practice_menu(frame: &mut Frame, area: Rect, app: &App, color_scheme:
                 Line::from(vec![
                     Span::styled(
                         "  ",
-                        Style::default().fg(Color::Rgb(0, 255, 0)).bg(bg_color),
+                        Style::default().fg(Color::from(MyColor::new(0, 255, 0, 255))).bg(bg_color),
                     ),
                     if level.0 < 9 {
                         Span::styled(
@@ -234,17 +269,6 @@ fn smooth(
     smoothed
 }
 
-fn calc_standard_deviation(values: &[f64], average_word_length: f64) -> f64 {
-    let wpm_values: Vec<f64> = values
-        .iter()
-        .map(|&cpm| cpm / average_word_length)
-        .collect();
-    let mean = wpm_values.iter().sum::<f64>() / wpm_values.len() as f64;
-    let variance =
-        wpm_values.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / wpm_values.len() as f64;
-    variance.sqrt()
-}
-
 fn get_stats(app: &App, color_scheme: ColorScheme) -> (Line<'static>, Line<'static>, bool) {
     let bg_color = color_scheme.bg_color();
     let main_color = color_scheme.main_color();
@@ -264,31 +288,23 @@ fn get_stats(app: &App, color_scheme: ColorScheme) -> (Line<'static>, Line<'stat
 
     let raw_str = format!("{}", raw.round());
 
-    let standard_deviation = calc_standard_deviation(&app.speed_per_second, 6.0);
-    let consistency = if wpm > 0.0 {
-        (100.0 - (standard_deviation / wpm as f64 * 100.0).round()).max(0.0)
-    } else {
-        0.0
-    };
+    let consistency = utils::consistency(&app.speed_per_second, 6.0, wpm as f64);
     let consistency_str = format!("{consistency}%");
 
     let time_str = format!("{:.0}s", app.timer.as_secs_f32());
 
-    let mut mode_str = if app.time_mode {
-        "time".to_string()
-    } else if app.word_mode {
-        "words".to_string()
-    } else if app.quote {
-        "quote".to_string()
-    } else if app.wiki_mode {
-        "wiki".to_string()
+    let mut mode_str = if app.active_ghost().is_some() {
+        "ghost".to_string()
+    } else if app.active_daily().is_some() {
+        "daily".to_string()
     } else {
-        "practice".to_string()
+        app.mode.to_string()
     };
-    if app.punctuation && !app.quote && !app.practice_mode {
+    let shows_toggles = !matches!(app.mode, Mode::Quote | Mode::Practice);
+    if app.punctuation && shows_toggles {
         mode_str += " !";
     }
-    if app.numbers && !app.quote && !app.practice_mode {
+    if app.numbers && shows_toggles {
         mode_str += " #";
     }
 
@@ -354,6 +370,7 @@ fn get_stats(app: &App, color_scheme: ColorScheme) -> (Line<'static>, Line<'stat
 
 fn get_chart(
     smoothed_speeds: &[f64],
+    ghost_speeds: Option<&[f64]>,
     app: &App,
     step: usize,
     color_scheme: ColorScheme,
@@ -369,6 +386,7 @@ fn get_chart(
 
     let data: &'static [(f64, f64)] = Box::leak(data.into_boxed_slice());
 
+    let ghost_max = ghost_speeds.unwrap_or_default().iter().fold(0.0_f64, |a, &b| a.max(b));
     let max_speed: f64 = f64::max(
         70.0,
         app.speed_per_second
@@ -377,7 +395,8 @@ fn get_chart(
             .max(1.0)
             / 6.0
             + 30.0,
-    );
+    )
+    .max(ghost_max + 30.0);
     let max_time = app.timer.as_secs_f32().ceil() as f64;
 
     let bar_dataset = Dataset::default()
@@ -385,8 +404,26 @@ fn get_chart(
         .style(Style::default().fg(chart_color).bg(bg_color))
         .marker(symbols::Marker::HalfBlock)
         .data(data);
+    let mut datasets = vec![bar_dataset];
+
+    // The ghost's speed as a line over the bars
+    if let Some(ghost_speeds) = ghost_speeds {
+        let ghost_data: Vec<(f64, f64)> = ghost_speeds
+            .iter()
+            .enumerate()
+            .map(|(i, &speed)| (i as f64 + 1.0, speed))
+            .collect();
+        let ghost_data: &'static [(f64, f64)] = Box::leak(ghost_data.into_boxed_slice());
+        datasets.push(
+            Dataset::default()
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(color_scheme.dimmer_main()).bg(bg_color))
+                .marker(symbols::Marker::Braille)
+                .data(ghost_data),
+        );
+    }
 
-    let chart = Chart::new(vec![bar_dataset])
+    let chart = Chart::new(datasets)
         .block(Block::default().style(Style::default().bg(bg_color)))
         .bg(bg_color)
         .style(Style::default().bg(bg_color))
@@ -520,13 +557,22 @@ fn render_results(frame: &mut Frame, area: Rect, app: &App, color_scheme: ColorS
 
     let smoothed_speeds = smooth(&speed_per_second, 6.0, extra_columns, columns_to_delete);
 
-    let chart = get_chart(&smoothed_speeds, app, step, color_scheme);
+    let ghost_speeds = app.active_ghost().map(|ghost| {
+        let mut speeds = ghost.speed_per_second();
+        if test_time >= 120 {
+            speeds = speeds.into_iter().step_by(2).collect();
+        }
+        speeds.truncate(speed_per_second.len());
+        smooth(&speeds, 6.0, extra_columns, columns_to_delete)
+    });
+
+    let chart = get_chart(&smoothed_speeds, ghost_speeds.as_deref(), app, step, color_scheme);
 
     let block = create_reference_block(5, color_scheme);
 
     let inner_area = block.inner(area);
 
-    let chart_height = 12u16;
+    let chart_height = 17u16;
     let y_offset = if inner_area.height > chart_height {
         (inner_area.height - chart_height) / 2
     } else {
@@ -545,6 +591,9 @@ fn render_results(frame: &mut Frame, area: Rect, app: &App, color_scheme: ColorS
         Constraint::Min(3),
         Constraint::Length(1),
         Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(2),
+        Constraint::Length(2),
     ])
     .split(centered_area);
 
@@ -620,7 +669,14 @@ fn render_results(frame: &mut Frame, area: Rect, app: &App, color_scheme: ColorS
     frame.render_widget(stats, chunks[2]);
     frame.render_widget(empty_line, chunks[3]);
 
-    if app.practice_mode {
+    if let Some(flag) = &app.last_flag {
+        frame.render_widget(
+            Line::from(format!("\u{26A0} Result flagged as suspicious: {}", flag))
+                .style(Style::default().fg(color_scheme.incorrect_color()))
+                .alignment(Alignment::Center),
+            chunks[4],
+        );
+    } else if app.mode == Mode::Practice {
         if passed {
             frame.render_widget(
                 Line::from("Congratulations! You passed this level.").alignment(Alignment::Center),
@@ -637,6 +693,68 @@ fn render_results(frame: &mut Frame, area: Rect, app: &App, color_scheme: ColorS
             );
         }
     }
+
+    if let Some(verdict) = &app.pace_verdict {
+        frame.render_widget(
+            Line::from(verdict.as_str())
+                .style(Style::default().fg(color_scheme.dimmer_main()))
+                .alignment(Alignment::Center),
+            chunks[5],
+        );
+    }
+
+    if let Some(ghost) = app.active_ghost() {
+        frame.render_widget(
+            Paragraph::new(ghost_lines(app, ghost, chunks[6].width as usize, color_scheme)).alignment(Alignment::Center),
+            chunks[6],
+        );
+    } else if app.active_daily().is_some() {
+        let lines: Vec<Line> = app
+            .daily_lines
+            .iter()
+            .map(|line| Line::styled(line.clone(), Style::default().fg(color_scheme.dimmer_main())))
+            .collect();
+        frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), chunks[6]);
+    }
+
+    let mut lines = Vec::new();
+    if let Some(goals) = &app.goal_summary {
+        lines.push(Line::styled(goals.as_str(), Style::default().fg(color_scheme.ref_color())));
+    }
+    if let Some(unlocked) = &app.achievement_line {
+        lines.push(Line::styled(unlocked.as_str(), Style::default().fg(color_scheme.main_color())));
+    }
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), chunks[7]);
+}
+
+/// Overall result against the ghost and how far ahead (-) or behind (+) each word was
+/// finished, for as many words as fit in `width`
+fn ghost_lines(app: &App, ghost: &GhostRun, width: usize, color_scheme: ColorScheme) -> Vec<Line<'static>> {
+    let gap = app.timer.as_secs_f64() - ghost.duration().as_secs_f64();
+    let summary = if gap <= 0.0 {
+        format!("Ghost {}: beaten by {:.1}s", ghost.player, -gap)
+    } else {
+        format!("Ghost {}: lost by {:.1}s", ghost.player, gap)
+    };
+
+    let deltas = ghost::word_deltas(&ghost::word_times(&app.reference, &app.key_log), &ghost.word_times());
+    let mut spans = Vec::new();
+    let mut used = 0;
+    for (word, delta) in app.reference.split_whitespace().zip(deltas) {
+        let delta_text = format!("{:+.1}  ", delta);
+        used += word.chars().count() + 1 + delta_text.len();
+        if used > width {
+            break;
+        }
+        let color = if delta <= 0.0 { color_scheme.correct_color() } else { color_scheme.incorrect_color() };
+        spans.push(Span::styled(format!("{} ", word), Style::default().fg(color_scheme.ref_color())));
+        spans.push(Span::styled(delta_text, Style::default().fg(color)));
+    }
+
+    vec![
+        Line::styled(summary, Style::default().fg(color_scheme.dimmer_main())),
+        Line::from(spans),
+    ]
 }
 
 fn render_reference_frame(
@@ -653,30 +771,39 @@ fn render_reference_frame(
 
     let instruction_line = create_config_line(app, color_scheme, &button_states, area);
     let horizontal_line = create_horizontal_line(area, color_scheme);
-    let time_words = if app.time_mode {
+    let mut time_words = if app.mode == Mode::Time && !app.fixed_text() {
         create_timer(timer, app.test_time, color_scheme)
     } else {
-        let all_words = if app.word_mode {
+        let all_words = if app.mode == Mode::Words && !app.fixed_text() {
             app.word_number
-        } else if app.quote || app.wiki_mode {
+        } else if matches!(app.mode, Mode::Quote | Mode::Wiki) || app.fixed_text() {
             app.reference.split_whitespace().count()
-        } else if app.practice_mode {
+        } else if app.mode == Mode::Practice {
             50
         } else {
             app.batch_size
         };
         create_words_count(all_words, app.words_done, color_scheme)
     };
+    if let (Some(status), Some(_)) = (app.daily_lines.first(), app.active_daily()) {
+        time_words.push_span(Span::styled(format!("   {}", status), Style::default().fg(color_scheme.ref_color())));
+    } else if let (Some(preset), GameState::NotStarted) = (app.active_preset(), &app.game_state) {
+        time_words.push_span(Span::styled(format!("   preset: {}", preset.name), Style::default().fg(color_scheme.ref_color())));
+    }
     let colored_lines = create_colored_lines(app, max_ref_width, color_scheme);
     let empty_space = calculate_vertical_padding(area, colored_lines.len());
 
-    let content = assemble_content(
+    let mut content = assemble_content(
         instruction_line,
         horizontal_line,
         time_words,
         colored_lines,
         empty_space,
     );
+    // Goal progress goes in the blank line under the config line before the test starts
+    if let (Some(goals), GameState::NotStarted) = (&app.goal_summary, &app.game_state) {
+        content[3] = Line::from(goals.clone()).style(Style::default().fg(color_scheme.ref_color()));
+    }
 
     let block = create_reference_block(ref_padding, color_scheme);
     let paragraph = Paragraph::new(content)
@@ -722,6 +849,44 @@ fn create_words_count(
         .alignment(Alignment::Left)
 }
 
+/// Visible buttons of the config line with the text drawn for them
+fn config_buttons(button_states: &ButtonStates, area: Rect) -> Vec<(&ButtonState, String)> {
+    button_states
+        .as_vec()
+        .into_iter()
+        .filter(|button_state| button_state.visible)
+        .map(|button_state| {
+            let name = if area.width < 100 { &button_state.short_name } else { &button_state.display_name };
+            (button_state, format!(" {} ", name))
+        })
+        .collect()
+}
+
+/// Label of the config button at `column`, `row` of the screen, if the config line is showing
+pub fn config_button_at(app: &App, screen: Rect, column: u16, row: u16) -> Option<String> {
+    if app.game_state == GameState::Results || app.practice_menu || app.leaderboard.open || app.achievements.open {
+        return None;
+    }
+    let area = main_layout(app, screen)[0];
+    let ref_padding = calculate_ref_padding(area, calculate_max_ref_width(area));
+    let inner = create_reference_block(ref_padding, app.color_scheme).inner(area);
+    // Below the empty first line, see `assemble_content`
+    if row != inner.y + 1 {
+        return None;
+    }
+    let buttons = config_buttons(&app.button_states, area);
+    let width: usize = buttons.iter().map(|(_, text)| Span::raw(text.as_str()).width()).sum();
+    let mut x = inner.x + inner.width.saturating_sub(width as u16) / 2;
+    for (button_state, text) in buttons {
+        let end = x + Span::raw(text.as_str()).width() as u16;
+        if (x..end).contains(&column) {
+            return (button_state.label != "|").then(|| button_state.label.clone());
+        }
+        x = end;
+    }
+    None
+}
+
 fn create_config_line(app: &App, color_scheme: ColorScheme, button_states: &ButtonStates, area: Rect) -> Line<'static> {
     let bg_color = color_scheme.bg_color();
     let main_color = color_scheme.main_color();
@@ -731,12 +896,10 @@ fn create_config_line(app: &App, color_scheme: ColorScheme, button_states: &Butt
 
     let mut spans: Vec<Span<'static>> = vec![];
 
-    let mut fg_colors = vec![ref_color; button_states.as_vec().len()];
-    let mut bg_colors = vec![bg_color; button_states.as_vec().len()];
-    for (i, button_state) in button_states.as_vec().iter_mut().enumerate() {
-        if !button_state.visible {
-            continue;
-        }
+    let buttons = config_buttons(button_states, area);
+    let mut fg_colors = vec![ref_color; buttons.len()];
+    let mut bg_colors = vec![bg_color; buttons.len()];
+    for (i, (button_state, text)) in buttons.into_iter().enumerate() {
         if button_state.state_val && button_state.label != "|" && app.selected_config == button_state.label && app.config {
             bg_colors[i] = dimmer_main;
             fg_colors[i] = bg_color;
@@ -748,14 +911,7 @@ fn create_config_line(app: &App, color_scheme: ColorScheme, button_states: &Butt
             } else {
                 fg_colors[i] = ref_color;
         }
-        spans.push(Span::styled(
-            if area.width < 100 {
-                format!(" {} ", button_state.short_name)
-            } else {
-                format!(" {} ", button_state.display_name)
-            },
-            Style::default().fg(fg_colors[i]).bg(bg_colors[i]),
-        ));
+        spans.push(Span::styled(text, Style::default().fg(fg_colors[i]).bg(bg_colors[i])));
     }
 
     Line::from(spans).alignment(Alignment::Center)
@@ -786,6 +942,15 @@ fn create_colored_lines<'a>(
     let incorrect_color = color_scheme.incorrect_color();
     let mut fg_colors: Vec<Color> = vec![ref_color; app.reference.chars().count()];
     let mut bg_colors: Vec<Color> = vec![bg_color; app.reference.chars().count()];
+    // Errors are struck through and corrections underlined, for when colors don't tell them apart
+    let mut modifiers: Vec<Modifier> = vec![Modifier::empty(); app.reference.chars().count()];
+
+    if let Some(ghost) = app.ghost_index() {
+        bg_colors[ghost] = color_scheme.border_color();
+    }
+    if let Some(pace) = app.pace_index() {
+        bg_colors[pace] = color_scheme.dimmer_main();
+    }
 
     for i in 0..app.is_correct.len() {
         if app.pos1 == i {
@@ -797,8 +962,10 @@ fn create_colored_lines<'a>(
             fg_colors[i] = correct_color;
         } else if app.is_correct[i] == 1 {
             fg_colors[i] = corrected_color;
+            modifiers[i] = Modifier::UNDERLINED;
         } else if app.is_correct[i] == -1 {
             fg_colors[i] = incorrect_color;
+            modifiers[i] = Modifier::CROSSED_OUT;
         } else {
             fg_colors[i] = ref_color;
         }
@@ -815,8 +982,9 @@ fn create_colored_lines<'a>(
                 .map(|c| {
                     let fg_color = fg_colors.get(char_index).cloned().unwrap_or(ref_color);
                     let bg_color = bg_colors.get(char_index).cloned().unwrap_or(bg_color);
+                    let modifier = modifiers.get(char_index).cloned().unwrap_or(Modifier::empty());
                     char_index += 1;
-                    Span::styled(c.to_string(), Style::default().fg(fg_color).bg(bg_color))
+                    Span::styled(c.to_string(), Style::default().fg(fg_color).bg(bg_color).add_modifier(modifier))
                 })
                 .collect();
             Line::from(spans)
@@ -908,9 +1076,64 @@ fn split_lines(text: &str, width: usize) -> Vec<String> {
         .collect()
 }
 
+/// Every achievement, the unlocked ones first in the main color with when
+fn render_achievements(frame: &mut Frame, area: Rect, app: &App, color_scheme: ColorScheme) {
+    let list = achievements::list(&app.achievements.unlocks);
+    let unlocked = list.iter().filter(|(_, when)| when.is_some()).count();
+    let block = Block::default()
+        .title(format!(" Achievements {}/{} ({}) ", unlocked, list.len(), crate::profile::active()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color_scheme.border_color()))
+        .title_style(Style::default().fg(color_scheme.main_color()));
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows: Vec<Row> = list
+        .iter()
+        .filter(|(_, when)| when.is_some())
+        .chain(list.iter().filter(|(_, when)| when.is_none()))
+        .skip(app.achievements.scroll)
+        .map(|(achievement, when)| {
+            let (mark, style) = match when {
+                Some(_) => ("\u{2713}", Style::default().fg(color_scheme.main_color())),
+                None => (" ", Style::default().fg(color_scheme.ref_color())),
+            };
+            Row::new(vec![
+                mark.to_string(),
+                achievement.name.clone(),
+                achievement.description.clone(),
+                when.clone().unwrap_or_default(),
+            ])
+            .style(style)
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(2),
+        Constraint::Length(18),
+        Constraint::Min(20),
+        Constraint::Length(16),
+    ];
+    frame.render_widget(Table::new(rows, widths), inner_area);
+}
+
 fn render_leaderboard(frame: &mut Frame, area: Rect, app: &App, color_scheme: ColorScheme) {
+    // Tab header, the active view highlighted
+    let mut tabs = vec![Span::raw(" ")];
+    for view in LeaderboardView::all() {
+        let label = match view {
+            LeaderboardView::Local => format!(" Local ({}) ", crate::profile::active()),
+            _ => format!(" {} ", view),
+        };
+        let style = if view == app.leaderboard.view {
+            Style::default().bg(color_scheme.main_color()).fg(color_scheme.bg_color())
+        } else {
+            Style::default().fg(color_scheme.dimmer_main())
+        };
+        tabs.push(Span::styled(label, style));
+        tabs.push(Span::raw(" "));
+    }
     let block = Block::default()
-        .title("Local Leaderboard")
+        .title(Line::from(tabs))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(color_scheme.border_color()))
         .title_style(Style::default().fg(color_scheme.main_color()));
@@ -919,8 +1142,13 @@ fn render_leaderboard(frame: &mut Frame, area: Rect, app: &App, color_scheme: Co
     frame.render_widget(block, area);
 
     if app.leaderboard.entries.is_empty() {
+        let message = if app.leaderboard.view == LeaderboardView::Team && crate::team::configured().is_none() {
+            "No team set up yet.\nRun 'typeman team join <DIR> --name <NAME>' with a shared folder to compare results."
+        } else {
+            "No typing test results yet.\nComplete a test to see your scores here!"
+        };
         let empty_text =
-            Paragraph::new("No typing test results yet.\nComplete a test to see your scores here!")
+            Paragraph::new(message)
                 .style(Style::default().fg(color_scheme.ref_color()))
                 .alignment(Alignment::Center)
                 .wrap(Wrap { trim: true });
@@ -929,7 +1157,7 @@ fn render_leaderboard(frame: &mut Frame, area: Rect, app: &App, color_scheme: Co
     }
 
     // Create table headers
-    let header = Row::new(vec![
+    let mut header_cells = vec![
         Cell::from("Rank").style(Style::default().fg(color_scheme.main_color())),
         Cell::from("Date").style(Style::default().fg(color_scheme.main_color())),
         Cell::from("Time").style(Style::default().fg(color_scheme.main_color())),
@@ -938,7 +1166,18 @@ fn render_leaderboard(frame: &mut Frame, area: Rect, app: &App, color_scheme: Co
         Cell::from("Acc%").style(Style::default().fg(color_scheme.main_color())),
         Cell::from("Words").style(Style::default().fg(color_scheme.main_color())),
         Cell::from("Lang").style(Style::default().fg(color_scheme.main_color())),
-    ]);
+    ];
+    match app.leaderboard.view {
+        LeaderboardView::AllProfiles => {
+            header_cells.push(Cell::from("Profile").style(Style::default().fg(color_scheme.main_color())))
+        }
+        LeaderboardView::Team => {
+            header_cells.push(Cell::from("Player").style(Style::default().fg(color_scheme.main_color())))
+        }
+        LeaderboardView::Local => {}
+    }
+    header_cells.push(Cell::from("Note").style(Style::default().fg(color_scheme.main_color())));
+    let header = Row::new(header_cells);
 
     // Calculate viewport for scrolling
     let available_height = inner_area.height.saturating_sub(2); // Subtract header and border
@@ -996,6 +1235,8 @@ fn render_leaderboard(frame: &mut Frame, area: Rect, app: &App, color_scheme: Co
             crate::leaderboard::TestType::Quote => "Quote".to_string(),
             crate::leaderboard::TestType::Practice(level) => format!("L{}", level),
             crate::leaderboard::TestType::Wiki => "Wiki".to_string(),
+            crate::leaderboard::TestType::Race(place) => format!("Race#{}", place),
+            crate::leaderboard::TestType::Daily(date) => format!("Daily {}", date.format("%m-%d")),
         };
 
         // Format language
@@ -1013,7 +1254,7 @@ fn render_leaderboard(frame: &mut Frame, area: Rect, app: &App, color_scheme: Co
             Style::default().fg(color_scheme.text_color())
         };
 
-        let row = Row::new(vec![
+        let mut cells = vec![
             Cell::from(rank),
             Cell::from(date),
             Cell::from(time),
@@ -1022,25 +1263,39 @@ fn render_leaderboard(frame: &mut Frame, area: Rect, app: &App, color_scheme: Co
             Cell::from(format!("{:.1}", entry.accuracy)),
             Cell::from(entry.word_count.to_string()),
             Cell::from(lang),
-        ])
-        .style(row_style);
+        ];
+        if let Some(owner) = app.leaderboard.owners.get(i) {
+            cells.push(Cell::from(owner.clone()));
+        }
+        match &entry.flag {
+            Some(flag) if i == app.leaderboard.selected => cells.push(Cell::from(format!("\u{26A0} {}", flag))),
+            Some(flag) => cells.push(
+                Cell::from(format!("\u{26A0} {}", flag))
+                    .style(Style::default().fg(color_scheme.incorrect_color())),
+            ),
+            None => cells.push(Cell::from("")),
+        }
+        let row = Row::new(cells).style(row_style);
 
         rows.push(row);
     }
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(4),  // Rank
-            Constraint::Length(10), // Date
-            Constraint::Length(8),  // Time (HH:MM AM/PM)
-            Constraint::Length(6),  // Type
-            Constraint::Length(5),  // WPM
-            Constraint::Length(5),  // Acc%
-            Constraint::Length(6),  // Words
-            Constraint::Length(4),  // Lang
-        ],
-    )
+    let mut widths = vec![
+        Constraint::Length(4),  // Rank
+        Constraint::Length(10), // Date
+        Constraint::Length(8),  // Time (HH:MM AM/PM)
+        Constraint::Length(6),  // Type
+        Constraint::Length(5),  // WPM
+        Constraint::Length(5),  // Acc%
+        Constraint::Length(6),  // Words
+        Constraint::Length(4),  // Lang
+    ];
+    if app.leaderboard.view != LeaderboardView::Local {
+        widths.push(Constraint::Length(12)); // Profile / Player
+    }
+    widths.push(Constraint::Min(0)); // Note (anti-cheat flag)
+
+    let table = Table::new(rows, widths)
     .header(header)
     .style(Style::default().fg(color_scheme.text_color()));
 
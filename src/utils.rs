@@ -95,8 +95,12 @@ pub fn draw_rounded_rect_lines(x: f32, y: f32, w: f32, h: f32, radius: f32, widt
 }
 
 pub fn get_reference(punctuation: bool, digits: bool, word_list: &[String], batch_size: usize) -> String {
+    get_reference_with(punctuation, digits, word_list, batch_size, &mut ::rand::rng())
+}
+
+/// `get_reference` drawing from the given generator, so a seeded one always gives the same text
+pub fn get_reference_with(punctuation: bool, digits: bool, word_list: &[String], batch_size: usize, rng: &mut impl Rng) -> String {
     let mut items = Vec::new();
-    let mut rng = ::rand::rng();
 
     // Calculate how many digits to include (if enabled)
     let num_digits = if digits {
@@ -111,7 +115,7 @@ pub fn get_reference(punctuation: bool, digits: bool, word_list: &[String], batc
 
     // Generate words
     for _ in 0..num_words {
-        let mut word = word_list.choose(&mut rng).unwrap().clone();
+        let mut word = word_list.choose(rng).unwrap().clone();
         if punctuation {
             let punctuations = [".", ",", "!", "?", ";", ":"];
             if rng.random_bool(0.2) {
@@ -121,7 +125,7 @@ pub fn get_reference(punctuation: bool, digits: bool, word_list: &[String], batc
                 }
             }
             if rng.random_bool(0.2) {
-                word.push_str(punctuations.choose(&mut rng).unwrap());
+                word.push_str(punctuations.choose(rng).unwrap());
             }
         }
         items.push(word);
@@ -141,7 +145,7 @@ pub fn get_reference(punctuation: bool, digits: bool, word_list: &[String], batc
     }
 
     // Shuffle all items and join with spaces
-    items.shuffle(&mut rng);
+    items.shuffle(rng);
     
     // Clean each item to remove any embedded whitespace that could cause extra words
     let cleaned_items: Vec<String> = items.into_iter()
@@ -241,11 +245,108 @@ pub fn count_correct_words(reference: &str, is_correct: &VecDeque<i32>) -> (usiz
     (no_corrected_words, correct_words, all_words)
 }
 
+/// How steady the speed was, 100% meaning every second was as fast as the
+/// average; `speed_per_second` is in characters per minute
+pub fn consistency(speed_per_second: &[f64], average_word_length: f64, wpm: f64) -> f64 {
+    if speed_per_second.is_empty() || wpm <= 0.0 {
+        return 0.0;
+    }
+    let wpm_values: Vec<f64> = speed_per_second.iter().map(|&cpm| cpm / average_word_length).collect();
+    let mean = wpm_values.iter().sum::<f64>() / wpm_values.len() as f64;
+    let variance = wpm_values.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / wpm_values.len() as f64;
+    (100.0 - (variance.sqrt() / wpm * 100.0).round()).max(0.0)
+}
+
+/// `values` as a row of block characters at most `width` long, neighbouring
+/// values averaged when there are more of them than fit
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let columns = values.len().min(width);
+    let averages: Vec<f64> = (0..columns)
+        .map(|i| {
+            let bucket = &values[i * values.len() / columns..(i + 1) * values.len() / columns];
+            bucket.iter().sum::<f64>() / bucket.len() as f64
+        })
+        .collect();
+    let max = averages.iter().cloned().fold(0.0, f64::max);
+    averages
+        .iter()
+        .map(|&v| if max > 0.0 { BARS[((v / max) * 7.0).round() as usize] } else { BARS[0] })
+        .collect()
+}
+
+/// How well `query` matches `candidate` in a fuzzy search, `None` when its
+/// characters don't all appear in order. Matches at the start of a word and
+/// runs of matching characters score higher; spaces in the query are ignored.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+    for q in query.chars().flat_map(char::to_lowercase).filter(|c| !c.is_whitespace()) {
+        let i = next + candidate[next..].iter().position(|&c| c == q)?;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == i) {
+            score += 5;
+        }
+        if i == 0 || !candidate[i - 1].is_alphanumeric() {
+            score += 8;
+        }
+        score -= (i - next).min(3) as i32;
+        last_match = Some(i);
+        next = i + 1;
+    }
+    Some(score)
+}
+
+/// Text of a custom file as one line of words
+pub fn read_custom_text(path: &std::path::Path) -> Result<String, String> {
+    if !path.is_file() {
+        return Err(format!("Custom file does not exist or is not a file: {:?}", path));
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Error reading {:?}: {}", path, e))?;
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return Err(format!("Custom file is empty: {:?}", path));
+    }
+    Ok(text)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::language::Language;
 
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "Theme: dark"), Some(0));
+        assert!(fuzzy_score("xyz", "Theme: dark").is_none());
+        // Characters have to come in order
+        assert!(fuzzy_score("kd", "dark").is_none());
+        assert!(fuzzy_score("Th DARK", "Theme: dark").is_some());
+        // Word starts and runs beat scattered matches
+        let start = fuzzy_score("lang", "Language: English").unwrap();
+        let scattered = fuzzy_score("lang", "Leaderboard: all tags").unwrap_or(i32::MIN);
+        assert!(start > scattered);
+        assert!(fuzzy_score("t 60", "Time: 60s") > fuzzy_score("t 60", "Words: 500 (time 0)"));
+    }
+
+    #[test]
+    fn test_sparkline_and_consistency() {
+        assert_eq!(sparkline(&[0.0, 120.0, 240.0, 480.0], 10), "▁▃▅█");
+        // Pairs averaged to fit
+        assert_eq!(sparkline(&[0.0, 0.0, 480.0, 480.0], 2), "▁█");
+        assert_eq!(sparkline(&[], 10), "");
+        assert_eq!(sparkline(&[0.0, 0.0], 10), "▁▁");
+
+        assert_eq!(consistency(&[300.0, 300.0, 300.0], 6.0, 50.0), 100.0);
+        assert_eq!(consistency(&[240.0, 360.0], 6.0, 50.0), 80.0);
+        assert_eq!(consistency(&[], 6.0, 50.0), 0.0);
+    }
+
     #[test]
     fn test_reference_text_structure() {
         let word_list = read_first_n_words(500, Language::English);
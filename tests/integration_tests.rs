@@ -1,5 +1,34 @@
 // Integration tests for leaderboard functionality
 
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+/// Keeps a test out of the developer's real config and leaderboard, and out
+/// of the other tests' results: a fresh data directory that is removed once
+/// the test is done. The directory is global, so these tests run one at a time.
+struct TempDataDir {
+    path: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TempDataDir {
+    fn new(name: &str) -> Self {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let lock = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let path = std::env::temp_dir().join(format!("typeman-integration-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        typeman::paths::set_data_dir(path.clone());
+        TempDataDir { path, _lock: lock }
+    }
+}
+
+impl Drop for TempDataDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
 #[test]
 fn test_leaderboard_data_structures() {
     // Test that leaderboard data structures work correctly
@@ -15,6 +44,7 @@ fn test_leaderboard_data_structures() {
         test_duration: 30.0,
         timestamp: "2025-09-11T10:30:00Z".to_string(),
         language: Language::English,
+        flag: None,
     };
     
     // Verify the entry was created successfully
@@ -28,6 +58,7 @@ fn test_leaderboard_storage() {
     // Test that leaderboard storage and retrieval works
     use typeman::leaderboard::{save_entry, load_entries, LeaderboardEntry, TestType};
     use typeman::language::Language;
+    let _data_dir = TempDataDir::new("storage");
     
     let entry = LeaderboardEntry {
         wpm: 75.0,
@@ -38,6 +69,7 @@ fn test_leaderboard_storage() {
         test_duration: 45.0,
         timestamp: "2025-09-11T11:00:00Z".to_string(),
         language: Language::English,
+        flag: None,
     };
     
     // Should be able to save and load entries
@@ -49,6 +81,7 @@ fn test_leaderboard_storage() {
 fn test_app_state_integration() {
     // Test that the App struct includes leaderboard fields
     use typeman::ui::tui::app::App;
+    let _data_dir = TempDataDir::new("app");
     
     let app = App::new();
     
@@ -74,4 +107,37 @@ fn test_test_type_variants() {
     assert!(matches!(word_test, TestType::Word(100)));
     assert!(matches!(quote_test, TestType::Quote));
     assert!(matches!(practice_test, TestType::Practice(5)));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_daily_result_counts_after_leaving_the_leaderboard() {
+    // The daily is only scored once, even when the leaderboard no longer has it
+    use typeman::daily;
+    use typeman::leaderboard::{load_entries, save_entries, LeaderboardEntry, TestType};
+    use typeman::language::Language;
+    let _data_dir = TempDataDir::new("daily");
+
+    let date = chrono::NaiveDate::from_ymd_opt(2001, 1, 1).unwrap();
+    let entry = |wpm: f64, test_type: TestType, timestamp: String| LeaderboardEntry {
+        wpm,
+        accuracy: 95.0,
+        test_type,
+        test_mode: "daily".to_string(),
+        word_count: daily::DAILY_WORDS,
+        test_duration: 30.0,
+        timestamp,
+        language: Language::English,
+        flag: None,
+    };
+    let first = entry(1.0, TestType::Daily(date), "2001-01-01T08:00:00Z".to_string());
+    assert!(daily::submit(&first).unwrap());
+
+    let faster: Vec<LeaderboardEntry> = (0..110)
+        .map(|i| entry(50.0, TestType::Time(30), format!("2001-01-02T08:{:02}:{:02}Z", i / 60, i % 60)))
+        .collect();
+    save_entries(&faster).unwrap();
+    assert!(load_entries().unwrap().iter().all(|e| e.timestamp != first.timestamp));
+
+    let again = entry(90.0, TestType::Daily(date), "2001-01-01T09:00:00Z".to_string());
+    assert!(!daily::submit(&again).unwrap());
+}
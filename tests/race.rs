@@ -0,0 +1,75 @@
+// Integration tests for LAN races, several clients against a localhost host
+
+use std::time::Duration;
+
+use typeman::race::{RaceClient, RaceHost, RaceMessage};
+
+const WAIT: Duration = Duration::from_secs(5);
+
+/// Skips messages until one matches
+fn wait_for(client: &RaceClient, matches: impl Fn(&RaceMessage) -> bool) -> RaceMessage {
+    loop {
+        let message = client.recv_timeout(WAIT).expect("Timed out waiting for the host");
+        if matches(&message) {
+            return message;
+        }
+    }
+}
+
+#[test]
+fn test_race_with_three_players() {
+    let host = RaceHost::bind("127.0.0.1:0").expect("Should bind to localhost");
+    let addr = host.local_addr();
+
+    let mut clients: Vec<RaceClient> = ["ana", "bo", "ana"]
+        .iter()
+        .map(|name| RaceClient::connect(addr, name).expect("Should connect"))
+        .collect();
+
+    let mut ids = Vec::new();
+    for client in &clients {
+        match wait_for(client, |m| matches!(m, RaceMessage::Welcome { .. })) {
+            RaceMessage::Welcome { id } => ids.push(id),
+            _ => unreachable!(),
+        }
+    }
+    // Everyone sees the full lobby, with the duplicate name made unique
+    let lobby = wait_for(&clients[0], |m| matches!(m, RaceMessage::Lobby { players } if players.len() == 3));
+    assert_eq!(lobby, RaceMessage::Lobby { players: vec!["ana".into(), "bo".into(), "ana (2)".into()] });
+    assert_eq!(host.player_count(), 3);
+
+    host.begin("the quick brown fox");
+    for client in &clients {
+        let start = wait_for(client, |m| matches!(m, RaceMessage::Start { .. }));
+        assert!(matches!(start, RaceMessage::Start { ref text, .. } if text == "the quick brown fox"));
+    }
+
+    // Late joiners are turned away
+    let late = RaceClient::connect(addr, "late").unwrap();
+    assert!(matches!(wait_for(&late, |_| true), RaceMessage::Refused { .. }));
+
+    // Progress is relayed to the other players
+    clients[1].send(&RaceMessage::Progress { typed: 10, wpm: 60.0 }).unwrap();
+    let update = wait_for(&clients[0], |m| matches!(m, RaceMessage::Update { .. }));
+    if let RaceMessage::Update { players } = update {
+        let bo = players.iter().find(|p| p.id == ids[1]).unwrap();
+        assert!((bo.progress - 10.0 / 19.0).abs() < 1e-9);
+    }
+
+    // Finish in the order bo, ana; the third player leaves mid-race
+    clients[1].send(&RaceMessage::Finished { wpm: 95.0, accuracy: 99.0, time: 12.0 }).unwrap();
+    clients[0].send(&RaceMessage::Finished { wpm: 80.0, accuracy: 97.0, time: 14.5 }).unwrap();
+    clients[2].send(&RaceMessage::Progress { typed: 5, wpm: 40.0 }).unwrap();
+    wait_for(&clients[0], |m| matches!(m, RaceMessage::Update { players } if players.iter().any(|p| p.id == ids[2] && p.progress > 0.0)));
+    drop(clients.pop());
+
+    for client in &clients {
+        let standings = match wait_for(client, |m| matches!(m, RaceMessage::Standings { .. })) {
+            RaceMessage::Standings { standings } => standings,
+            _ => unreachable!(),
+        };
+        let order: Vec<(&str, usize, bool)> =
+            standings.iter().map(|s| (s.name.as_str(), s.place, s.finished)).collect();
+        assert_eq!(order, [("bo", 1, true), ("ana", 2, true), ("ana (2)", 3, false)]);
+    }
+}
@@ -0,0 +1,133 @@
+// Integration tests for the leaderboard server and the sync client, run against localhost
+#![cfg(all(feature = "server", feature = "sync"))]
+
+use std::path::{Path, PathBuf};
+
+use typeman::language::Language;
+use typeman::leaderboard::{LeaderboardEntry, TestType};
+use typeman::server::LeaderboardServer;
+use typeman::sync;
+
+fn entry(wpm: f64, timestamp: &str) -> LeaderboardEntry {
+    LeaderboardEntry {
+        wpm,
+        accuracy: 96.0,
+        test_type: TestType::Time(30),
+        test_mode: "time".to_string(),
+        word_count: 40,
+        test_duration: 30.0,
+        timestamp: timestamp.to_string(),
+        language: Language::English,
+        flag: None,
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("typeman-{}-{}", name, std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Starts a server on a free localhost port and returns its URL
+fn start_server(data_dir: &Path) -> String {
+    start_server_with_token(data_dir, None)
+}
+
+fn start_server_with_token(data_dir: &Path, token: Option<&str>) -> String {
+    let server = LeaderboardServer::bind("127.0.0.1:0", data_dir)
+        .expect("Should bind to localhost")
+        .with_token(token.map(str::to_string));
+    let url = format!("http://{}", server.local_addr().unwrap());
+    std::thread::spawn(move || server.run());
+    url
+}
+
+#[test]
+fn test_push_and_pull() {
+    let url = start_server(&temp_dir("server-push"));
+
+    let summary = sync::push(&url, "ana", None, &[entry(70.0, "2025-09-11T10:00:00Z"), entry(82.0, "2025-09-12T10:00:00Z")]).unwrap();
+    assert_eq!(summary.added, 2);
+    sync::push(&url, "bo", None, &[entry(90.0, "2025-09-11T10:00:00Z")]).unwrap();
+
+    // Pushing the same result again is recognised as a duplicate
+    let again = sync::push(&url, "ana", None, &[entry(70.0, "2025-09-11T10:00:00Z")]).unwrap();
+    assert_eq!((again.added, again.duplicates), (0, 1));
+
+    let best = sync::pull(&url, Some("time"), true, 10).unwrap();
+    let ranking: Vec<(&str, f64)> = best.iter().map(|e| (e.player.as_str(), e.entry.wpm)).collect();
+    assert_eq!(ranking, [("bo", 90.0), ("ana", 82.0)]);
+    assert_eq!(sync::pull(&url, None, false, 10).unwrap().len(), 3);
+
+    // Query parameters are URL-decoded
+    let encoded: Vec<serde_json::Value> =
+        ureq::get(&format!("{}/api/leaderboard?mode=ti%6De&best=tru%65", url)).call().unwrap().into_json().unwrap();
+    assert_eq!(encoded.len(), 2);
+
+    // Invalid data is refused
+    assert!(matches!(sync::push(&url, "", None, &[entry(70.0, "2025-09-13T10:00:00Z")]), Err(sync::SyncError::Rejected(400, _))));
+    assert!(matches!(sync::push(&url, "ana", None, &[entry(900.0, "2025-09-13T10:00:00Z")]), Err(sync::SyncError::Rejected(400, _))));
+    let refused = sync::push(&url, "", None, &[entry(70.0, "2025-09-13T10:00:00Z")]).unwrap_err();
+    assert_eq!(refused.to_string(), "the server answered 400: invalid player name \"\"");
+}
+
+#[test]
+fn test_offline_queue() {
+    let queue_dir = temp_dir("sync-queue");
+    sync::enqueue(&queue_dir, &[entry(75.0, "2025-09-11T10:00:00Z")]).unwrap();
+    sync::enqueue(&queue_dir, &[entry(75.0, "2025-09-11T10:00:00Z"), entry(77.0, "2025-09-12T10:00:00Z")]).unwrap();
+    assert_eq!(sync::load_queue(&queue_dir).unwrap().len(), 2);
+
+    // Nothing listens on the discard port, so results stay queued
+    let offline = sync::flush_queue(&queue_dir, "http://127.0.0.1:9", "ana", None);
+    assert!(matches!(offline, Err(sync::SyncError::Unreachable(_))));
+    assert_eq!(sync::load_queue(&queue_dir).unwrap().len(), 2);
+
+    let url = start_server(&temp_dir("server-queue"));
+    let summary = sync::flush_queue(&queue_dir, &url, "ana", None).unwrap();
+    assert_eq!(summary.uploaded.added, 2);
+    assert!(sync::load_queue(&queue_dir).unwrap().is_empty());
+    assert_eq!(sync::pull(&url, None, false, 10).unwrap().len(), 2);
+}
+
+#[test]
+fn test_token_is_required_for_uploads() {
+    let url = start_server_with_token(&temp_dir("server-token"), Some("s3cret"));
+    let result = [entry(70.0, "2025-09-11T10:00:00Z")];
+
+    assert!(matches!(sync::push(&url, "ana", None, &result), Err(sync::SyncError::Rejected(401, _))));
+    assert!(matches!(sync::push(&url, "ana", Some("guess"), &result), Err(sync::SyncError::Rejected(401, _))));
+    assert_eq!(sync::push(&url, "ana", Some("s3cret"), &result).unwrap().added, 1);
+
+    // A wrong token is fixable, so the results wait in the queue
+    let queue_dir = temp_dir("sync-token-queue");
+    sync::enqueue(&queue_dir, &[entry(75.0, "2025-09-12T10:00:00Z")]).unwrap();
+    assert!(matches!(sync::flush_queue(&queue_dir, &url, "ana", None), Err(sync::SyncError::Rejected(401, _))));
+    assert_eq!(sync::load_queue(&queue_dir).unwrap().len(), 1);
+    assert_eq!(sync::flush_queue(&queue_dir, &url, "ana", Some("s3cret")).unwrap().uploaded.added, 1);
+
+    // Reading the leaderboard needs no token
+    assert_eq!(sync::pull(&url, None, false, 10).unwrap().len(), 2);
+}
+
+#[test]
+fn test_refused_results_leave_the_queue() {
+    let url = start_server(&temp_dir("server-refused"));
+    let queue_dir = temp_dir("sync-refused");
+    sync::enqueue(&queue_dir, &[entry(75.0, "2025-09-11T10:00:00Z"), entry(900.0, "2025-09-12T10:00:00Z"), entry(80.0, "2025-09-13T10:00:00Z")])
+        .unwrap();
+
+    // The impossible result is set aside and the others still get uploaded
+    let summary = sync::flush_queue(&queue_dir, &url, "ana", None).unwrap();
+    assert_eq!((summary.uploaded.added, summary.rejected), (2, 1));
+    assert!(sync::load_queue(&queue_dir).unwrap().is_empty());
+    let rejected = sync::load_rejected(&queue_dir).unwrap();
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0].wpm, 900.0);
+    assert_eq!(sync::pull(&url, None, false, 10).unwrap().len(), 2);
+
+    // Nothing blocks the next sync
+    sync::enqueue(&queue_dir, &[entry(85.0, "2025-09-14T10:00:00Z")]).unwrap();
+    assert_eq!(sync::flush_queue(&queue_dir, &url, "ana", None).unwrap().uploaded.added, 1);
+}